@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: (C) 2025 Cranky Kernel <crankykernel@proton.me>
+
+//! Subscription status from a provider's user info, and the problems worth warning about
+
+use crate::xtream::UserInfo;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// A provider subscription's status, expiry and connection usage
+#[derive(Debug, Clone, Serialize)]
+pub struct AccountStatus {
+    /// As reported by the provider, e.g. "Active", "Expired" or "Banned"
+    pub status: String,
+    /// None if the subscription doesn't expire
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Whole days until expiry; negative once expired
+    pub days_left: Option<i64>,
+    pub active_connections: Option<u32>,
+    pub max_connections: Option<u32>,
+    pub is_trial: bool,
+}
+
+/// Something about a subscription that needs attention
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AccountAlert {
+    /// The provider reports a status other than active or expired, e.g. "Banned"
+    Inactive {
+        status: String,
+    },
+    Expired,
+    /// Expires within the warning period
+    Expiring {
+        days: i64,
+    },
+    /// Every allowed connection is in use, so starting a stream will fail
+    ConnectionLimit {
+        active: u32,
+        max: u32,
+    },
+}
+
+impl AccountStatus {
+    pub fn from_user_info(user_info: &UserInfo, now: DateTime<Utc>) -> Self {
+        let number =
+            |value: &Option<String>| value.as_deref().and_then(|v| v.trim().parse::<i64>().ok());
+        // Providers use 0 or an empty value for subscriptions that never expire
+        let expires_at = number(&user_info.exp_date)
+            .filter(|timestamp| *timestamp > 0)
+            .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0));
+
+        Self {
+            status: user_info.status.clone(),
+            days_left: expires_at.map(|expires| (expires - now).num_days()),
+            expires_at,
+            active_connections: number(&user_info.active_cons).and_then(|n| n.try_into().ok()),
+            max_connections: number(&user_info.max_connections).and_then(|n| n.try_into().ok()),
+            is_trial: number(&user_info.is_trial).is_some_and(|n| n != 0),
+        }
+    }
+
+    /// Problems to warn about, most serious first
+    pub fn alerts(&self, warning_days: u32, now: DateTime<Utc>) -> Vec<AccountAlert> {
+        let mut alerts = Vec::new();
+        let status = self.status.trim().to_lowercase();
+        let expired = status == "expired" || self.expires_at.is_some_and(|e| e <= now);
+
+        if expired {
+            alerts.push(AccountAlert::Expired);
+        } else if status != "active" {
+            alerts.push(AccountAlert::Inactive {
+                status: self.status.clone(),
+            });
+        } else if let Some(days) = self.days_left
+            && days < i64::from(warning_days)
+        {
+            alerts.push(AccountAlert::Expiring { days });
+        }
+
+        if let (Some(active), Some(max)) = (self.active_connections, self.max_connections)
+            && max > 0
+            && active >= max
+        {
+            alerts.push(AccountAlert::ConnectionLimit { active, max });
+        }
+        alerts
+    }
+}
+
+impl AccountAlert {
+    /// Whether the subscription can't be used at all, rather than needing attention soon
+    pub fn is_critical(&self) -> bool {
+        matches!(self, Self::Inactive { .. } | Self::Expired)
+    }
+}
+
+impl std::fmt::Display for AccountAlert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Inactive { status } => write!(f, "Account {}", status.to_lowercase()),
+            Self::Expired => write!(f, "Subscription expired"),
+            Self::Expiring { days: 0 } => write!(f, "Expires today"),
+            Self::Expiring { days: 1 } => write!(f, "Expires tomorrow"),
+            Self::Expiring { days } => write!(f, "Expires in {} days", days),
+            Self::ConnectionLimit { active, max } => {
+                write!(f, "{}/{} connections in use", active, max)
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MIT
 // SPDX-FileCopyrightText: (C) 2025 Cranky Kernel <crankykernel@proton.me>
 
+use crate::storage;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -100,13 +101,20 @@ impl CacheManager {
         Ok(())
     }
 
-    fn save_provider_index(&self) -> Result<()> {
+    /// Add a provider to the index, re-reading it under the file lock so providers added
+    /// by another process are kept
+    fn save_provider_index(&mut self, provider_url: &str, hash: &str) -> Result<()> {
         let index_path = self.cache_dir.join("index.json");
+        let _lock = storage::lock(&index_path)?;
+
+        self.load_provider_index()?;
+        self.provider_index
+            .insert(provider_url.to_string(), hash.to_string());
+
         let content = serde_json::to_string_pretty(&self.provider_index)
             .with_context(|| "Failed to serialize provider index")?;
-        fs::write(&index_path, content)
-            .with_context(|| format!("Failed to write provider index: {}", index_path.display()))?;
-        Ok(())
+        storage::write_atomic(&index_path, content)
+            .with_context(|| format!("Failed to write provider index: {}", index_path.display()))
     }
 
     pub fn get_provider_hash(
@@ -122,9 +130,7 @@ impl CacheManager {
         hasher.update(provider_url.as_bytes());
         let hash = format!("{:x}", hasher.finalize())[..16].to_string();
 
-        self.provider_index
-            .insert(provider_url.to_string(), hash.clone());
-        self.save_provider_index()?;
+        self.save_provider_index(provider_url, &hash)?;
 
         let provider_dir = self.cache_dir.join("providers").join(&hash);
         if !provider_dir.exists() {
@@ -185,6 +191,49 @@ impl CacheManager {
         Ok(Some(cached_data.data))
     }
 
+    /// All cached entries of a type stored once per item, such as `vod_info_<id>`, keyed by
+    /// the rest of their name
+    ///
+    /// Entries that can't be read or parsed are skipped.
+    pub async fn get_cached_with_prefix<T>(
+        &self,
+        provider_hash: &str,
+        prefix: &str,
+    ) -> Result<HashMap<String, T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let provider_dir = self.cache_dir.join("providers").join(provider_hash);
+        let mut entries = HashMap::new();
+        if !provider_dir.exists() {
+            return Ok(entries);
+        }
+
+        let mut dir = async_fs::read_dir(&provider_dir).await.with_context(|| {
+            format!(
+                "Failed to read provider cache directory: {}",
+                provider_dir.display()
+            )
+        })?;
+        while let Some(entry) = dir.next_entry().await? {
+            let file_name = entry.file_name();
+            let Some(key) = file_name
+                .to_str()
+                .and_then(|name| name.strip_prefix(prefix))
+                .and_then(|name| name.strip_suffix(".json"))
+            else {
+                continue;
+            };
+            let Ok(content) = async_fs::read_to_string(entry.path()).await else {
+                continue;
+            };
+            if let Ok(cached_data) = serde_json::from_str::<CachedData<T>>(&content) {
+                entries.insert(key.to_string(), cached_data.data);
+            }
+        }
+        Ok(entries)
+    }
+
     pub async fn store_cache<T>(
         &self,
         provider_hash: &str,
@@ -210,11 +259,10 @@ impl CacheManager {
         let content = serde_json::to_string_pretty(&cached_data)
             .with_context(|| "Failed to serialize cache data")?;
 
-        async_fs::write(&cache_path, content)
-            .await
-            .with_context(|| format!("Failed to write cache file: {}", cache_path.display()))?;
-
-        Ok(())
+        // Written off the runtime threads, as taking the lock may wait for another process
+        tokio::task::spawn_blocking(move || storage::write_locked(&cache_path, content))
+            .await?
+            .with_context(|| "Failed to write cache file")
     }
 
     pub async fn clear_provider_cache(&self, provider_hash: &str) -> Result<()> {
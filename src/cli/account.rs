@@ -0,0 +1,155 @@
+use super::{CommandContext, OutputFormat, provider_api};
+use anyhow::Result;
+use chrono::{Local, Utc};
+use iptv::account::{AccountAlert, AccountStatus};
+use serde::Serialize;
+
+pub struct AccountCommand {
+    /// Warn about subscriptions expiring within this many days
+    pub warning_days: u32,
+    /// Fail if any subscription has a problem, for use from scripts and cron
+    pub check: bool,
+    pub format: OutputFormat,
+}
+
+/// A provider's account status, or why it couldn't be fetched
+#[derive(Debug, Serialize)]
+struct ProviderAccount {
+    provider: String,
+    #[serde(flatten)]
+    status: Option<AccountStatus>,
+    alerts: Vec<AccountAlert>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl AccountCommand {
+    pub async fn execute(self, context: CommandContext) -> Result<()> {
+        if matches!(self.format, OutputFormat::M3u | OutputFormat::Tsv) {
+            anyhow::bail!("Accounts can only be shown as 'text' or 'json'");
+        }
+
+        let now = Utc::now();
+        let mut accounts = Vec::new();
+        for provider in context.selected_configs()? {
+            let provider_name = provider.label();
+            let (result, api) = match provider_api(&provider) {
+                Ok(mut api) => (api.fetch_user_info().await, Some(api)),
+                Err(e) => (Err(e), None),
+            };
+            accounts.push(match result {
+                Ok(user_info) => {
+                    let status = AccountStatus::from_user_info(&user_info, now);
+                    ProviderAccount {
+                        provider: provider_name,
+                        alerts: status.alerts(self.warning_days, now),
+                        status: Some(status),
+                        error: None,
+                    }
+                }
+                Err(e) => {
+                    // Request errors carry the URL, credentials included
+                    let error = format!("{:#}", e);
+                    ProviderAccount {
+                        provider: provider_name,
+                        status: None,
+                        alerts: Vec::new(),
+                        error: Some(match &api {
+                            Some(api) => api.redact_text(&error),
+                            None => error,
+                        }),
+                    }
+                }
+            });
+        }
+
+        match self.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&accounts)?),
+            _ => print_table(&accounts),
+        }
+
+        if self.check {
+            let problems = accounts
+                .iter()
+                .filter(|a| a.error.is_some() || !a.alerts.is_empty())
+                .count();
+            if problems > 0 {
+                anyhow::bail!("{} of {} accounts need attention", problems, accounts.len());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn print_table(accounts: &[ProviderAccount]) {
+    let header = [
+        "Provider",
+        "Status",
+        "Expires",
+        "Days left",
+        "Connections",
+        "",
+    ];
+    let rows: Vec<[String; 6]> = accounts
+        .iter()
+        .map(|account| {
+            let Some(status) = &account.status else {
+                return [
+                    account.provider.clone(),
+                    "Error".to_string(),
+                    String::new(),
+                    String::new(),
+                    String::new(),
+                    account.error.clone().unwrap_or_default(),
+                ];
+            };
+            let connections = match (status.active_connections, status.max_connections) {
+                (Some(active), Some(max)) => format!("{}/{}", active, max),
+                (Some(active), None) => active.to_string(),
+                _ => "-".to_string(),
+            };
+            let alerts: Vec<String> = account
+                .alerts
+                .iter()
+                .map(|alert| format!("⚠ {}", alert))
+                .collect();
+            let mut state = status.status.clone();
+            if status.is_trial {
+                state.push_str(" (trial)");
+            }
+            [
+                account.provider.clone(),
+                state,
+                status.expires_at.map_or("Never".to_string(), |e| {
+                    e.with_timezone(&Local).format("%Y-%m-%d").to_string()
+                }),
+                status
+                    .days_left
+                    .map_or("-".to_string(), |days| days.max(0).to_string()),
+                connections,
+                alerts.join(", "),
+            ]
+        })
+        .collect();
+
+    let mut widths = header.map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    println!("{}", line(&header.map(String::from)));
+    for row in &rows {
+        println!("{}", line(row));
+    }
+}
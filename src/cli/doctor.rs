@@ -0,0 +1,474 @@
+use super::OutputFormat;
+use crate::launcher::MenuBackend;
+use anyhow::Result;
+use chrono::{Local, Utc};
+use futures_util::future::join_all;
+use iptv::account::AccountStatus;
+use iptv::config::ProviderConfig;
+use iptv::xtream::ServerInfo;
+use iptv::{Config, Player, setup};
+use serde::Serialize;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Instant;
+
+/// Responses slower than this are reported as a warning
+const SLOW_RESPONSE_MS: u128 = 3000;
+/// Server clocks further off than this are reported as a warning, as they put the EPG out
+const CLOCK_SKEW_SECS: i64 = 300;
+
+pub struct DoctorCommand {
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+/// One finding of `iptv doctor`
+#[derive(Debug, Serialize)]
+struct Check {
+    /// "environment", "config", "storage" or the provider's name
+    section: String,
+    name: String,
+    status: CheckStatus,
+    message: String,
+}
+
+/// The JSON schema of `iptv doctor`
+#[derive(Debug, Serialize)]
+struct DoctorReport {
+    checks: Vec<Check>,
+    passed: usize,
+    warnings: usize,
+    failed: usize,
+}
+
+impl Check {
+    fn new(section: &str, name: &str, status: CheckStatus, message: impl Into<String>) -> Self {
+        Self {
+            section: section.to_string(),
+            name: name.to_string(),
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+impl DoctorCommand {
+    /// Runs before the config is loaded, so a config that doesn't parse is reported rather
+    /// than stopping the command
+    pub async fn execute(self, config_path: PathBuf) -> Result<()> {
+        if matches!(self.format, OutputFormat::M3u | OutputFormat::Tsv) {
+            anyhow::bail!("The report can only be shown as 'text' or 'json'");
+        }
+
+        let mut checks = vec![check_mpv(), check_menu()];
+        checks.extend(check_sockets());
+        let config = check_config(&config_path, &mut checks);
+        checks.extend(check_storage(&config_path));
+        if let Some(config) = config {
+            let warning_days = config.settings.expiry_warning_days;
+            let tests = config
+                .providers
+                .iter()
+                .map(|provider| check_provider(provider, warning_days));
+            checks.extend(join_all(tests).await.into_iter().flatten());
+        }
+
+        let count = |status| checks.iter().filter(|c| c.status == status).count();
+        let report = DoctorReport {
+            passed: count(CheckStatus::Pass),
+            warnings: count(CheckStatus::Warn),
+            failed: count(CheckStatus::Fail),
+            checks,
+        };
+        match self.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            _ => print_report(&report),
+        }
+
+        if report.failed > 0 {
+            anyhow::bail!("{} of {} checks failed", report.failed, report.checks.len());
+        }
+        Ok(())
+    }
+}
+
+/// First line of a program's version output, or None if it can't be run
+fn program_version(program: &str, flag: &str) -> Option<String> {
+    let output = Command::new(program)
+        .arg(flag)
+        .stdin(Stdio::null())
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    // Some programs print their version to stderr
+    stdout
+        .lines()
+        .chain(stderr.lines())
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+fn check_mpv() -> Check {
+    match program_version("mpv", "--version") {
+        Some(version) => Check::new("environment", "mpv", CheckStatus::Pass, version),
+        None => Check::new(
+            "environment",
+            "mpv",
+            CheckStatus::Fail,
+            "mpv not found on PATH; install it to play streams",
+        ),
+    }
+}
+
+fn check_menu() -> Check {
+    if let Some(version) = program_version("rofi", "-version") {
+        return Check::new("environment", "rofi", CheckStatus::Pass, version);
+    }
+    let fallback = [
+        MenuBackend::Fzf,
+        MenuBackend::Fuzzel,
+        MenuBackend::Wofi,
+        MenuBackend::Dmenu,
+    ]
+    .into_iter()
+    .find(|backend| backend.is_installed());
+    let message = match fallback {
+        Some(backend) => format!(
+            "rofi not found; 'iptv rofi' will use {} instead",
+            backend.binary()
+        ),
+        None => {
+            "No menu program found; 'iptv rofi' needs rofi, dmenu, wofi, fuzzel or fzf".to_string()
+        }
+    };
+    Check::new("environment", "rofi", CheckStatus::Warn, message)
+}
+
+/// Check the config file, returning it if it parses
+fn check_config(path: &Path, checks: &mut Vec<Check>) -> Option<Config> {
+    let section = "config";
+    if !path.exists() {
+        checks.push(Check::new(
+            section,
+            "file",
+            CheckStatus::Warn,
+            format!(
+                "{} doesn't exist; run 'iptv' to set up a provider",
+                path.display()
+            ),
+        ));
+        return None;
+    }
+
+    let config = match Config::load(path) {
+        Ok(config) => {
+            checks.push(Check::new(
+                section,
+                "file",
+                CheckStatus::Pass,
+                format!("{} parses", path.display()),
+            ));
+            config
+        }
+        Err(e) => {
+            checks.push(Check::new(
+                section,
+                "file",
+                CheckStatus::Fail,
+                format!("{}: {:#}", path.display(), e),
+            ));
+            return None;
+        }
+    };
+
+    // The config holds provider passwords
+    if let Ok(metadata) = std::fs::metadata(path) {
+        let mode = metadata.permissions().mode() & 0o777;
+        checks.push(if mode & 0o077 == 0 {
+            Check::new(
+                section,
+                "permissions",
+                CheckStatus::Pass,
+                format!("{:o}", mode),
+            )
+        } else {
+            Check::new(
+                section,
+                "permissions",
+                CheckStatus::Warn,
+                format!(
+                    "{:o}: other users can read the provider passwords; run 'chmod 600 {}'",
+                    mode,
+                    path.display()
+                ),
+            )
+        });
+    }
+
+    checks.push(match config.providers.len() {
+        0 => Check::new(
+            section,
+            "providers",
+            CheckStatus::Warn,
+            "No providers configured; add one with 'iptv config provider add'",
+        ),
+        count => Check::new(
+            section,
+            "providers",
+            CheckStatus::Pass,
+            format!("{} configured", count),
+        ),
+    });
+    Some(config)
+}
+
+/// Check the config, cache and state directories can be written to
+fn check_storage(config_path: &Path) -> Vec<Check> {
+    let state_dir = std::env::var("XDG_STATE_HOME")
+        .ok()
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".local").join("state")))
+        .map(|dir| dir.join("iptv"));
+    [
+        (
+            "config directory",
+            config_path.parent().map(Path::to_path_buf),
+        ),
+        (
+            "cache directory",
+            dirs::cache_dir().map(|dir| dir.join("iptv")),
+        ),
+        ("state directory", state_dir),
+    ]
+    .into_iter()
+    .map(|(name, dir)| match dir {
+        Some(dir) => match check_writable(&dir) {
+            Ok(()) => Check::new(
+                "storage",
+                name,
+                CheckStatus::Pass,
+                dir.display().to_string(),
+            ),
+            Err(e) => Check::new(
+                "storage",
+                name,
+                CheckStatus::Fail,
+                format!("{} isn't writable: {}", dir.display(), e),
+            ),
+        },
+        None => Check::new(
+            "storage",
+            name,
+            CheckStatus::Fail,
+            "Could not determine the directory",
+        ),
+    })
+    .collect()
+}
+
+/// Create the directory if needed and write and remove a file in it
+fn check_writable(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let probe = dir.join(format!(".doctor-{}", std::process::id()));
+    std::fs::write(&probe, b"")?;
+    std::fs::remove_file(&probe)
+}
+
+fn check_sockets() -> Vec<Check> {
+    let sockets = Player::mpv_sockets();
+    if sockets.is_empty() {
+        return vec![Check::new(
+            "environment",
+            "mpv sockets",
+            CheckStatus::Pass,
+            "None left over",
+        )];
+    }
+    sockets
+        .into_iter()
+        .map(|(path, live)| {
+            if live {
+                Check::new(
+                    "environment",
+                    "mpv sockets",
+                    CheckStatus::Pass,
+                    format!("{} is in use by a running mpv", path.display()),
+                )
+            } else {
+                Check::new(
+                    "environment",
+                    "mpv sockets",
+                    CheckStatus::Warn,
+                    format!(
+                        "{} is stale; nothing answers on it, so it can be removed",
+                        path.display()
+                    ),
+                )
+            }
+        })
+        .collect()
+}
+
+/// Log in to a provider and check its response time, server info and subscription
+async fn check_provider(provider: &ProviderConfig, warning_days: u32) -> Vec<Check> {
+    let section = provider.label();
+    let started = Instant::now();
+    let result = setup::check_provider(provider).await;
+    let elapsed = started.elapsed().as_millis();
+
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            return vec![Check::new(
+                &section,
+                "authentication",
+                CheckStatus::Fail,
+                format!("{:#}", e),
+            )];
+        }
+    };
+
+    let mut checks = vec![
+        Check::new(
+            &section,
+            "authentication",
+            CheckStatus::Pass,
+            format!("Logged in as {}", response.user_info.username),
+        ),
+        Check::new(
+            &section,
+            "latency",
+            if elapsed > SLOW_RESPONSE_MS {
+                CheckStatus::Warn
+            } else {
+                CheckStatus::Pass
+            },
+            format!("{} ms", elapsed),
+        ),
+    ];
+    checks.extend(check_server_info(
+        &section,
+        &provider.url,
+        &response.server_info,
+    ));
+
+    let now = Utc::now();
+    let account = AccountStatus::from_user_info(&response.user_info, now);
+    let alerts = account.alerts(warning_days, now);
+    let expiry = match account.expires_at {
+        Some(expires) => format!(
+            "{} {}",
+            if expires <= now { "expired" } else { "expires" },
+            expires.with_timezone(&Local).format("%Y-%m-%d")
+        ),
+        None => "never expires".to_string(),
+    };
+    checks.push(if alerts.is_empty() {
+        Check::new(
+            &section,
+            "subscription",
+            CheckStatus::Pass,
+            format!("{}, {}", account.status, expiry),
+        )
+    } else {
+        let status = if alerts.iter().any(|alert| alert.is_critical()) {
+            CheckStatus::Fail
+        } else {
+            CheckStatus::Warn
+        };
+        let alerts: Vec<String> = alerts.iter().map(ToString::to_string).collect();
+        Check::new(
+            &section,
+            "subscription",
+            status,
+            format!("{} ({})", alerts.join(", "), expiry),
+        )
+    });
+    checks
+}
+
+/// Compare the server info with the configured URL and the local clock
+fn check_server_info(section: &str, url: &str, server: &ServerInfo) -> Vec<Check> {
+    let mut checks = Vec::new();
+    if let Ok(url) = reqwest::Url::parse(url) {
+        let host = url.host_str().unwrap_or_default();
+        let port = url.port_or_known_default().unwrap_or_default().to_string();
+        let server_port = if server.server_protocol.eq_ignore_ascii_case("https") {
+            &server.https_port
+        } else {
+            &server.port
+        };
+        checks.push(
+            if server.url.is_empty() || (server.url == host && *server_port == port) {
+                Check::new(
+                    section,
+                    "server info",
+                    CheckStatus::Pass,
+                    format!("{}://{}:{}", server.server_protocol, host, port),
+                )
+            } else {
+                Check::new(
+                    section,
+                    "server info",
+                    CheckStatus::Warn,
+                    format!(
+                        "Configured as {}:{} but the server reports {}://{}:{}; streams may \
+                         come from there",
+                        host, port, server.server_protocol, server.url, server_port
+                    ),
+                )
+            },
+        );
+    }
+
+    let skew = i64::try_from(server.timestamp_now).unwrap_or_default() - Utc::now().timestamp();
+    if server.timestamp_now > 0 && skew.abs() > CLOCK_SKEW_SECS {
+        checks.push(Check::new(
+            section,
+            "server clock",
+            CheckStatus::Warn,
+            format!(
+                "{} minutes {} the local clock; EPG times may be off",
+                skew.abs() / 60,
+                if skew > 0 { "ahead of" } else { "behind" }
+            ),
+        ));
+    }
+    checks
+}
+
+fn print_report(report: &DoctorReport) {
+    let mut section = None;
+    for check in &report.checks {
+        if section != Some(&check.section) {
+            if section.is_some() {
+                println!();
+            }
+            println!("{}", check.section);
+            section = Some(&check.section);
+        }
+        let mark = match check.status {
+            CheckStatus::Pass => "✓",
+            CheckStatus::Warn => "⚠",
+            CheckStatus::Fail => "✗",
+        };
+        println!("  {} {}: {}", mark, check.name, check.message);
+    }
+    println!(
+        "\n{} passed, {} warning{}, {} failed",
+        report.passed,
+        report.warnings,
+        if report.warnings == 1 { "" } else { "s" },
+        report.failed
+    );
+}
@@ -0,0 +1,1211 @@
+use super::play::{PlayTarget, find_target, match_score};
+use super::{CommandContext, ContentType, OutputFormat, find_provider, provider_api};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use inquire::Select;
+use iptv::CacheManager;
+use iptv::favourites::{
+    FavouriteHealth, FavouriteKey, FavouritesManager, RematchCandidate, StreamCatalogue,
+};
+use iptv::ignore::IgnoreConfig;
+use iptv::xtream::{FavouriteStream, XTreamAPI};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// How to handle an imported favourite that clashes with an existing one
+#[derive(Debug, Clone, Copy)]
+pub enum ConflictPolicy {
+    /// Leave the existing favourite as it is
+    Keep,
+    /// Let the imported favourite win
+    Replace,
+}
+
+impl ConflictPolicy {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "keep" => Ok(Self::Keep),
+            "replace" => Ok(Self::Replace),
+            _ => anyhow::bail!("Invalid conflict policy: {}. Use 'keep' or 'replace'", s),
+        }
+    }
+}
+
+pub enum FavouritesCommand {
+    /// List favourites in group order, optionally only one group
+    List {
+        group: Option<String>,
+        format: OutputFormat,
+    },
+    /// Look a channel, movie or series up and add it as a favourite
+    Add {
+        target: PlayTarget,
+        group: Option<String>,
+        format: OutputFormat,
+    },
+    /// Remove a favourite, given by ID or name
+    Remove {
+        target: PlayTarget,
+        format: OutputFormat,
+    },
+    /// Move a favourite within its group or to another group
+    Move {
+        target: PlayTarget,
+        to: MoveTo,
+        format: OutputFormat,
+    },
+    /// Write favourites in a portable form to a file or stdout
+    Export {
+        format: OutputFormat,
+        output: Option<PathBuf>,
+    },
+    /// Merge favourites from an exported JSON or M3U file
+    Import {
+        input: PathBuf,
+        on_conflict: ConflictPolicy,
+    },
+    /// Check favourites against the cached stream lists and re-match dead ones
+    Verify {
+        fix: bool,
+        auto: bool,
+        min_score: f64,
+        other_providers: bool,
+        format: OutputFormat,
+    },
+}
+
+/// Where `favourites move` puts a favourite
+pub enum MoveTo {
+    Up,
+    Down,
+    /// Position in a group counting from 1, in the favourite's own group unless one is given
+    Position {
+        position: usize,
+        group: Option<String>,
+    },
+    /// End of a group, which is created if it doesn't exist
+    Group(String),
+}
+
+/// A favourite as listed by `favourites list` and the other management commands
+#[derive(Debug, Serialize)]
+struct ListedFavourite {
+    provider: String,
+    #[serde(skip)]
+    key: FavouriteKey,
+    group: Option<String>,
+    /// Position in the group, counting from 1
+    position: Option<usize>,
+    #[serde(rename = "type")]
+    stream_type: String,
+    id: u32,
+    name: String,
+    category_id: Option<String>,
+    added_date: DateTime<Utc>,
+}
+
+/// Portable favourites file, keyed by provider and stream name instead of the local provider hash
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FavouritesExport {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub groups: Vec<ExportedGroup>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedGroup {
+    pub name: String,
+    pub favourites: Vec<ExportedFavourite>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedFavourite {
+    /// Provider name, or URL for unnamed providers
+    pub provider: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_id: Option<String>,
+    pub name: String,
+    pub stream_type: String,
+    /// Only a hint on import; the stream name is matched first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_id: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub added_date: Option<DateTime<Utc>>,
+    /// Episodes only: container extension and series ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_extension: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series_id: Option<u32>,
+}
+
+const EXPORT_VERSION: u32 = 1;
+
+/// Number of re-match candidates offered for a dead favourite
+const MAX_CANDIDATES: usize = 5;
+
+/// Outcome of verifying one favourite, as printed by `favourites verify`
+#[derive(Debug, Serialize)]
+struct VerifiedFavourite {
+    /// None if the provider is no longer configured
+    provider: Option<String>,
+    provider_hash: String,
+    name: String,
+    stream_type: String,
+    stream_id: u32,
+    /// ok, renamed, changed, missing, orphaned or unverified
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current_name: Option<String>,
+    candidates: Vec<VerifiedCandidate>,
+    /// What --fix did: renamed, relinked or removed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    action: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct VerifiedCandidate {
+    provider: String,
+    stream_id: u32,
+    name: String,
+    category_id: Option<String>,
+    score: f64,
+}
+
+impl FavouritesCommand {
+    pub async fn execute(self, context: CommandContext) -> Result<()> {
+        match self {
+            Self::List { group, format } => Self::list(&context, group, format),
+            Self::Add {
+                target,
+                group,
+                format,
+            } => Self::add(&context, target, group, format).await,
+            Self::Remove { target, format } => Self::remove(&context, target, format),
+            Self::Move { target, to, format } => Self::move_favourite(&context, target, to, format),
+            Self::Export { format, output } => Self::export(&context, format, output).await,
+            Self::Import { input, on_conflict } => Self::import(&context, input, on_conflict).await,
+            Self::Verify {
+                fix,
+                auto,
+                min_score,
+                other_providers,
+                format,
+            } => Self::verify(&context, fix, auto, min_score, other_providers, format).await,
+        }
+    }
+
+    fn list(context: &CommandContext, group: Option<String>, format: OutputFormat) -> Result<()> {
+        let favourites_manager = FavouritesManager::new()?;
+        let mut favourites = list_favourites(context, &favourites_manager)?;
+        if let Some(group) = &group {
+            favourites.retain(|f| f.group.as_deref() == Some(group.as_str()));
+            if favourites.is_empty()
+                && !favourites_manager
+                    .get_groups()?
+                    .iter()
+                    .any(|g| &g.name == group)
+            {
+                anyhow::bail!("No favourite group named '{}'", group);
+            }
+        }
+
+        match format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&favourites)?),
+            OutputFormat::Tsv => {
+                println!("provider\tgroup\tposition\ttype\tid\tname");
+                for f in &favourites {
+                    println!(
+                        "{}\t{}\t{}\t{}\t{}\t{}",
+                        f.provider,
+                        f.group.as_deref().unwrap_or_default(),
+                        f.position.map(|p| p.to_string()).unwrap_or_default(),
+                        f.stream_type,
+                        f.id,
+                        f.name
+                    );
+                }
+            }
+            OutputFormat::Text => {
+                if favourites.is_empty() {
+                    println!("No favourites");
+                }
+                let mut group = None;
+                for f in &favourites {
+                    if group.is_none() || f.group != group {
+                        group = f.group.clone();
+                        println!("{}:", group.as_deref().unwrap_or("No group"));
+                    }
+                    println!(
+                        "  {:>3}. {} [{} {}] {}",
+                        f.position.unwrap_or_default(),
+                        f.name,
+                        f.stream_type,
+                        f.id,
+                        f.provider
+                    );
+                }
+            }
+            OutputFormat::M3u => {
+                anyhow::bail!("Use 'favourites export --format m3u' for an M3U playlist")
+            }
+        }
+        Ok(())
+    }
+
+    async fn add(
+        context: &CommandContext,
+        target: PlayTarget,
+        group: Option<String>,
+        format: OutputFormat,
+    ) -> Result<()> {
+        let mut providers = context.get_providers_for_search().await?;
+        let favourites_manager = FavouritesManager::new()?;
+        let (provider, item) = find_target(
+            context,
+            &mut providers,
+            &favourites_manager,
+            &target,
+            &[ContentType::Live, ContentType::Movie, ContentType::Series],
+        )
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("{}", target.not_found_message()))?;
+        let (api, provider_name) = &providers[provider];
+
+        let key = FavouriteKey::of(&item);
+        let existed = favourites_manager.is_favourite(
+            &api.provider_hash,
+            item.stream_id,
+            &item.stream_type,
+        )?;
+        if !existed {
+            favourites_manager.add_favourite(&api.provider_hash, item)?;
+        }
+        if let Some(group) = &group
+            && favourites_manager.group_of(&key)?.as_ref() != Some(group)
+        {
+            favourites_manager.move_to_group(&key, group)?;
+        }
+
+        let listed = find_listed(context, &favourites_manager, &key)?;
+        match format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&listed)?),
+            _ => {
+                let verb = if existed {
+                    "Already a favourite"
+                } else {
+                    "Added"
+                };
+                match &listed {
+                    Some(f) => println!(
+                        "{}: {} [{} {}] in {} on {}",
+                        verb,
+                        f.name,
+                        f.stream_type,
+                        f.id,
+                        f.group.as_deref().unwrap_or("no group"),
+                        provider_name
+                    ),
+                    None => println!("{} on {}", verb, provider_name),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn remove(context: &CommandContext, target: PlayTarget, format: OutputFormat) -> Result<()> {
+        let favourites_manager = FavouritesManager::new()?;
+        let (key, listed) = resolve_favourite(context, &favourites_manager, &target)?;
+        favourites_manager.remove_favourite(&key.provider_hash, key.stream_id, &key.stream_type)?;
+
+        match format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&listed)?),
+            _ => println!(
+                "Removed {} [{} {}] from {}",
+                listed.name, listed.stream_type, listed.id, listed.provider
+            ),
+        }
+        Ok(())
+    }
+
+    fn move_favourite(
+        context: &CommandContext,
+        target: PlayTarget,
+        to: MoveTo,
+        format: OutputFormat,
+    ) -> Result<()> {
+        let favourites_manager = FavouritesManager::new()?;
+        let (key, _) = resolve_favourite(context, &favourites_manager, &target)?;
+        match to {
+            MoveTo::Up => {
+                favourites_manager.move_within_group(&key, true)?;
+            }
+            MoveTo::Down => {
+                favourites_manager.move_within_group(&key, false)?;
+            }
+            MoveTo::Position { position, group } => {
+                if let Some(group) = &group
+                    && favourites_manager.group_of(&key)?.as_ref() != Some(group)
+                {
+                    favourites_manager.move_to_group(&key, group)?;
+                }
+                favourites_manager.move_to_position(&key, position.saturating_sub(1))?;
+            }
+            MoveTo::Group(group) => {
+                if favourites_manager.group_of(&key)?.as_ref() != Some(&group) {
+                    favourites_manager.move_to_group(&key, &group)?;
+                }
+            }
+        }
+
+        let listed = find_listed(context, &favourites_manager, &key)?
+            .context("Favourite disappeared while moving it")?;
+        match format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&listed)?),
+            _ => println!(
+                "Moved {} to position {} in {}",
+                listed.name,
+                listed.position.unwrap_or_default(),
+                listed.group.as_deref().unwrap_or("no group")
+            ),
+        }
+        Ok(())
+    }
+
+    async fn export(
+        context: &CommandContext,
+        format: OutputFormat,
+        output: Option<PathBuf>,
+    ) -> Result<()> {
+        let favourites_manager = FavouritesManager::new()?;
+        let ignore = IgnoreConfig::load()?;
+
+        let mut providers = Vec::new();
+        for provider in context.selected_configs()? {
+            let api = provider_api(&provider)?;
+            let mut matcher = ignore
+                .matcher(&api.provider_hash)
+                .with_parental(context.parental.clone());
+            matcher
+                .load_cached_categories(&api.cache_manager, &api.provider_hash)
+                .await;
+
+            // Favourites hidden by ignore rules or parental controls are left out, as in the
+            // TUI and rofi
+            let mut favourites = favourites_manager.get_favourites(&api.provider_hash)?;
+            favourites.retain(|f| !matcher.hides_favourite(f));
+            providers.push((provider, api, favourites));
+        }
+
+        let mut groups = Vec::new();
+        for group in favourites_manager.get_groups()? {
+            let favourites: Vec<(ExportedFavourite, &XTreamAPI)> = group
+                .members
+                .iter()
+                .filter_map(|key| {
+                    providers.iter().find_map(|(provider, api, favourites)| {
+                        let favourite = favourites.iter().find(|f| FavouriteKey::of(f) == *key)?;
+                        Some((
+                            ExportedFavourite {
+                                provider: provider.label(),
+                                provider_id: provider.id.clone(),
+                                name: favourite.name.clone(),
+                                stream_type: favourite.stream_type.clone(),
+                                stream_id: Some(favourite.stream_id),
+                                category_id: favourite.category_id.clone(),
+                                added_date: Some(favourite.added_date),
+                                container_extension: favourite.container_extension.clone(),
+                                series_id: favourite.series_id,
+                            },
+                            api,
+                        ))
+                    })
+                })
+                .collect();
+            groups.push((group.name, favourites));
+        }
+
+        let count: usize = groups.iter().map(|(_, favourites)| favourites.len()).sum();
+        let content = match format {
+            OutputFormat::Json => {
+                let export = FavouritesExport {
+                    version: EXPORT_VERSION,
+                    exported_at: Utc::now(),
+                    groups: groups
+                        .into_iter()
+                        .map(|(name, favourites)| ExportedGroup {
+                            name,
+                            favourites: favourites.into_iter().map(|(f, _)| f).collect(),
+                        })
+                        .collect(),
+                };
+                serde_json::to_string_pretty(&export)? + "\n"
+            }
+            OutputFormat::M3u => {
+                let mut content = String::from("#EXTM3U\n");
+                for (group, favourites) in &groups {
+                    for (favourite, api) in favourites {
+                        content.push_str(&m3u_entry(group, favourite, api));
+                    }
+                }
+                content
+            }
+            OutputFormat::Text | OutputFormat::Tsv => {
+                anyhow::bail!("Favourites can only be exported as 'json' or 'm3u'")
+            }
+        };
+
+        match output {
+            Some(path) => {
+                fs::write(&path, content)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                eprintln!("Exported {} favourites to {}", count, path.display());
+            }
+            None => print!("{}", content),
+        }
+
+        Ok(())
+    }
+
+    async fn import(
+        context: &CommandContext,
+        input: PathBuf,
+        on_conflict: ConflictPolicy,
+    ) -> Result<()> {
+        let content = fs::read_to_string(&input)
+            .with_context(|| format!("Failed to read {}", input.display()))?;
+
+        let groups = if content.trim_start().starts_with("#EXTM3U") {
+            parse_m3u(&content)
+        } else {
+            let export: FavouritesExport = serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", input.display()))?;
+            if export.version > EXPORT_VERSION {
+                anyhow::bail!(
+                    "Unsupported favourites file version {} (expected {} or older)",
+                    export.version,
+                    EXPORT_VERSION
+                );
+            }
+            export
+                .groups
+                .into_iter()
+                .map(|g| (g.name, g.favourites))
+                .collect()
+        };
+
+        let favourites_manager = FavouritesManager::new()?;
+        let mut importer = Importer::new(context, &favourites_manager, on_conflict);
+
+        for (group, favourites) in groups {
+            for favourite in favourites {
+                importer.import(&group, &favourite).await?;
+            }
+        }
+
+        println!(
+            "Imported {} favourites: {} added, {} moved to their imported group, {} replaced, {} already present, {} skipped",
+            importer.added + importer.moved + importer.replaced + importer.unchanged,
+            importer.added,
+            importer.moved,
+            importer.replaced,
+            importer.unchanged,
+            importer.skipped
+        );
+
+        Ok(())
+    }
+
+    async fn verify(
+        context: &CommandContext,
+        fix: bool,
+        auto: bool,
+        min_score: f64,
+        other_providers: bool,
+        format: OutputFormat,
+    ) -> Result<()> {
+        if matches!(format, OutputFormat::M3u) {
+            anyhow::bail!("Verification results can only be shown as 'text' or 'json'");
+        }
+
+        let favourites_manager = FavouritesManager::new()?;
+        let cache = CacheManager::new()?;
+
+        // Hashes of the configured providers, in config order, with their labels
+        let mut providers: Vec<(String, String)> = Vec::new();
+        for provider in &context.providers {
+            let api = provider_api(provider)?;
+            providers.push((api.provider_hash.clone(), provider.label()));
+        }
+        let all_hashes: Vec<String> = providers.iter().map(|(hash, _)| hash.clone()).collect();
+        let label_of = |hash: &str| {
+            providers
+                .iter()
+                .find(|(h, _)| h == hash)
+                .map(|(_, label)| label.clone())
+        };
+
+        let mut favourites = favourites_manager.get_all_favourites()?;
+        if context.selected_provider.is_some() {
+            let selected: Vec<String> = context
+                .selected_configs()?
+                .iter()
+                .map(|p| provider_api(p).map(|api| api.provider_hash.clone()))
+                .collect::<Result<_>>()?;
+            favourites.retain(|f| selected.contains(&f.provider_hash));
+        }
+
+        let mut catalogue = StreamCatalogue::new();
+        catalogue.load_for(&cache, &favourites).await;
+
+        let mut results = Vec::new();
+        // Fixes are applied together once every favourite has been checked, so one that
+        // takes another's old stream ID isn't mistaken for a duplicate of it
+        let mut fixes: Vec<(usize, FavouriteKey, Option<FavouriteStream>)> = Vec::new();
+        for favourite in &favourites {
+            let provider = label_of(&favourite.provider_hash);
+            let health = catalogue.check(favourite);
+
+            let (status, current_name) = match (&provider, &health) {
+                (None, _) => ("orphaned", None),
+                (Some(_), FavouriteHealth::Ok) => ("ok", None),
+                (Some(_), FavouriteHealth::Renamed { current_name, .. }) => (
+                    if health.is_dead() {
+                        "changed"
+                    } else {
+                        "renamed"
+                    },
+                    Some(current_name.clone()),
+                ),
+                (Some(_), FavouriteHealth::Missing) => ("missing", None),
+                (Some(_), FavouriteHealth::Unverified) => ("unverified", None),
+            };
+
+            // Favourites of a removed provider can only be re-matched elsewhere
+            let dead = provider.is_none() || health.is_dead();
+            let candidates = if dead {
+                let search: Vec<String> = if provider.is_none() || other_providers {
+                    all_hashes.clone()
+                } else {
+                    vec![favourite.provider_hash.clone()]
+                };
+                for hash in &search {
+                    catalogue.load(&cache, hash, &favourite.stream_type).await;
+                }
+                catalogue.candidates(favourite, &search, min_score, MAX_CANDIDATES)
+            } else {
+                Vec::new()
+            };
+
+            let planned = if !fix {
+                None
+            } else if status == "renamed" {
+                let mut renamed = favourite.clone();
+                renamed.name = current_name.clone().unwrap_or_default();
+                Some((Some(renamed), "renamed".to_string()))
+            } else if dead {
+                Self::fix_dead_favourite(favourite, &candidates, auto, &label_of)?
+            } else {
+                None
+            };
+            let action = planned.map(|(change, action)| {
+                fixes.push((results.len(), FavouriteKey::of(favourite), change));
+                action
+            });
+
+            results.push(VerifiedFavourite {
+                provider,
+                provider_hash: favourite.provider_hash.clone(),
+                name: favourite.name.clone(),
+                stream_type: favourite.stream_type.clone(),
+                stream_id: favourite.stream_id,
+                status,
+                current_name,
+                candidates: candidates
+                    .iter()
+                    .map(|c| VerifiedCandidate {
+                        provider: label_of(&c.provider_hash).unwrap_or_default(),
+                        stream_id: c.entry.stream_id,
+                        name: c.entry.name.clone(),
+                        category_id: c.entry.category_id.clone(),
+                        score: c.score,
+                    })
+                    .collect(),
+                action,
+            });
+        }
+
+        if !fixes.is_empty() {
+            let (indices, changes): (Vec<usize>, Vec<_>) = fixes
+                .into_iter()
+                .map(|(index, key, change)| (index, (key, change)))
+                .unzip();
+            let applied = favourites_manager.relink_favourites(changes)?;
+            for (index, applied) in indices.into_iter().zip(applied) {
+                if !applied {
+                    results[index].action = Some("skipped, changed by another process".to_string());
+                }
+            }
+        }
+
+        if matches!(format, OutputFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(&results)?);
+            return Ok(());
+        }
+
+        for result in results.iter().filter(|r| r.status != "ok") {
+            let provider = result
+                .provider
+                .clone()
+                .unwrap_or_else(|| "provider no longer configured".to_string());
+            let detail = match (result.status, &result.current_name) {
+                ("renamed", Some(name)) => format!("renamed to '{}'", name),
+                ("changed", Some(name)) => format!("stream ID now belongs to '{}'", name),
+                ("missing", _) => "no longer listed".to_string(),
+                ("orphaned", _) => "provider removed".to_string(),
+                _ => "no cached stream list".to_string(),
+            };
+            println!(
+                "[{}] {} ({}, stream {}): {}",
+                result.stream_type, result.name, provider, result.stream_id, detail
+            );
+            for candidate in &result.candidates {
+                println!(
+                    "    {:>3.0}% {} (stream {}) [{}]",
+                    candidate.score * 100.0,
+                    candidate.name,
+                    candidate.stream_id,
+                    candidate.provider
+                );
+            }
+            if let Some(action) = &result.action {
+                println!("    -> {}", action);
+            }
+        }
+
+        let count = |status: &str| results.iter().filter(|r| r.status == status).count();
+        let dead = count("changed") + count("missing") + count("orphaned");
+        println!(
+            "Checked {} favourites: {} ok, {} renamed, {} dead, {} unverified",
+            results.len(),
+            count("ok"),
+            count("renamed"),
+            dead,
+            count("unverified")
+        );
+        if count("unverified") > 0 {
+            println!(
+                "Run 'iptv cli cache refresh' to fetch stream lists for unverified favourites"
+            );
+        }
+        if !fix && (dead > 0 || count("renamed") > 0) {
+            println!("Run 'iptv cli favourites verify --fix' to re-match or remove them");
+        }
+
+        Ok(())
+    }
+
+    /// How to re-match or remove a dead favourite, asking unless `auto` is set
+    ///
+    /// Returns the favourite to relink it to, or None to remove it, and the action taken.
+    fn fix_dead_favourite(
+        favourite: &FavouriteStream,
+        candidates: &[RematchCandidate],
+        auto: bool,
+        label_of: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<Option<(Option<FavouriteStream>, String)>> {
+        let choice = if auto {
+            // Never remove without asking; only take the best match
+            match candidates.first() {
+                Some(_) => Some(0),
+                None => return Ok(None),
+            }
+        } else {
+            const KEEP: &str = "Keep as is";
+            const REMOVE: &str = "Remove favourite";
+            let mut options: Vec<String> = candidates
+                .iter()
+                .map(|c| {
+                    format!(
+                        "{:.0}% {} (stream {}) [{}]",
+                        c.score * 100.0,
+                        c.entry.name,
+                        c.entry.stream_id,
+                        label_of(&c.provider_hash).unwrap_or_default()
+                    )
+                })
+                .collect();
+            options.push(KEEP.to_string());
+            options.push(REMOVE.to_string());
+
+            let selection = Select::new(
+                &format!("Replace [{}] {}:", favourite.stream_type, favourite.name),
+                options.clone(),
+            )
+            .prompt()?;
+            match selection.as_str() {
+                KEEP => return Ok(None),
+                REMOVE => return Ok(Some((None, "removed".to_string()))),
+                _ => options.iter().position(|o| *o == selection),
+            }
+        };
+
+        let Some(candidate) = choice.and_then(|index| candidates.get(index)) else {
+            return Ok(None);
+        };
+        Ok(Some((
+            Some(candidate.to_favourite(favourite)),
+            format!(
+                "relinked to '{}' (stream {})",
+                candidate.entry.name, candidate.entry.stream_id
+            ),
+        )))
+    }
+}
+
+/// Favourites of the selected providers in group order
+fn list_favourites(
+    context: &CommandContext,
+    favourites_manager: &FavouritesManager,
+) -> Result<Vec<ListedFavourite>> {
+    let mut providers = Vec::new();
+    for provider in context.selected_configs()? {
+        let provider_hash = provider_api(&provider)?.provider_hash;
+        let favourites = favourites_manager.get_favourites(&provider_hash)?;
+        providers.push((provider.label(), favourites));
+    }
+
+    let mut listed = Vec::new();
+    for group in favourites_manager.get_groups()? {
+        for (index, key) in group.members.iter().enumerate() {
+            let found = providers.iter().find_map(|(label, favourites)| {
+                let favourite = favourites.iter().find(|f| FavouriteKey::of(f) == *key)?;
+                Some((label, favourite))
+            });
+            if let Some((label, favourite)) = found {
+                listed.push(ListedFavourite {
+                    provider: label.clone(),
+                    key: key.clone(),
+                    group: Some(group.name.clone()),
+                    position: Some(index + 1),
+                    stream_type: favourite.stream_type.clone(),
+                    id: favourite.stream_id,
+                    name: favourite.name.clone(),
+                    category_id: favourite.category_id.clone(),
+                    added_date: favourite.added_date,
+                });
+            }
+        }
+    }
+    Ok(listed)
+}
+
+fn find_listed(
+    context: &CommandContext,
+    favourites_manager: &FavouritesManager,
+    key: &FavouriteKey,
+) -> Result<Option<ListedFavourite>> {
+    Ok(list_favourites(context, favourites_manager)?
+        .into_iter()
+        .find(|f| f.key == *key))
+}
+
+/// Find the one favourite of the selected providers a target names
+///
+/// Names that match several favourites equally well are an error rather than a guess,
+/// as the favourite is about to be changed.
+fn resolve_favourite(
+    context: &CommandContext,
+    favourites_manager: &FavouritesManager,
+    target: &PlayTarget,
+) -> Result<(FavouriteKey, ListedFavourite)> {
+    let mut candidates = Vec::new();
+    for provider in context.selected_configs()? {
+        let provider_hash = provider_api(&provider)?.provider_hash;
+        for favourite in favourites_manager.get_favourites(&provider_hash)? {
+            let score = match target {
+                PlayTarget::Id { id, content_type } => (favourite.stream_id == *id
+                    && favourite.stream_type == content_type.stream_type())
+                .then_some(1.0),
+                PlayTarget::Name { name, exact, .. } | PlayTarget::Favourite { name, exact } => {
+                    match_score(name, &favourite.name, *exact)
+                }
+            };
+            if let Some(score) = score {
+                candidates.push((score, FavouriteKey::of(&favourite)));
+            }
+        }
+    }
+
+    let best = candidates
+        .iter()
+        .map(|(score, _)| *score)
+        .fold(0.0, f64::max);
+    let mut best: Vec<FavouriteKey> = candidates
+        .into_iter()
+        .filter(|(score, _)| *score >= best)
+        .map(|(_, key)| key)
+        .collect();
+    if best.len() > 1 {
+        let listed = list_favourites(context, favourites_manager)?;
+        let names: Vec<String> = listed
+            .iter()
+            .filter(|f| best.contains(&f.key))
+            .map(|f| format!("{} [{} {}] on {}", f.name, f.stream_type, f.id, f.provider))
+            .collect();
+        anyhow::bail!(
+            "{} favourites match; use --id or --provider to pick one:\n  {}",
+            best.len(),
+            names.join("\n  ")
+        );
+    }
+    let key = best
+        .pop()
+        .ok_or_else(|| anyhow::anyhow!("{}", target.not_found_message()))?;
+    let listed =
+        find_listed(context, favourites_manager, &key)?.context("Favourite isn't in any group")?;
+    Ok((key, listed))
+}
+
+pub(super) fn m3u_escape(value: &str) -> String {
+    value.replace('"', "'")
+}
+
+fn m3u_entry(group: &str, favourite: &ExportedFavourite, api: &XTreamAPI) -> String {
+    let mut attributes = vec![
+        format!("tvg-name=\"{}\"", m3u_escape(&favourite.name)),
+        format!("group-title=\"{}\"", m3u_escape(group)),
+        format!("iptv-provider=\"{}\"", m3u_escape(&favourite.provider)),
+        format!("iptv-type=\"{}\"", favourite.stream_type),
+    ];
+    if let Some(id) = &favourite.provider_id {
+        attributes.push(format!("iptv-provider-id=\"{}\"", m3u_escape(id)));
+    }
+    if let Some(stream_id) = favourite.stream_id {
+        attributes.push(format!("iptv-stream-id=\"{}\"", stream_id));
+    }
+    if let Some(series_id) = favourite.series_id {
+        attributes.push(format!("iptv-series-id=\"{}\"", series_id));
+    }
+    if let Some(extension) = &favourite.container_extension {
+        attributes.push(format!("iptv-container=\"{}\"", m3u_escape(extension)));
+    }
+
+    let url = match (favourite.stream_id, favourite.stream_type.as_str()) {
+        (Some(id), "episode") => {
+            api.get_stream_url(id, "series", favourite.container_extension.as_deref())
+        }
+        (Some(id), stream_type) => api.get_stream_url(id, stream_type, None),
+        (None, _) => String::new(),
+    };
+
+    format!(
+        "#EXTINF:-1 {},{}\n{}\n",
+        attributes.join(" "),
+        favourite.name,
+        url
+    )
+}
+
+/// Split an `#EXTINF` line into its attributes and title
+fn parse_extinf(line: &str) -> (HashMap<String, String>, String) {
+    let rest = line.trim_start_matches("#EXTINF:");
+
+    // The title follows the first comma outside quotes
+    let mut in_quotes = false;
+    let split = rest.char_indices().find(|&(_, c)| {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        }
+        c == ',' && !in_quotes
+    });
+    let (header, title) = match split {
+        Some((index, _)) => (&rest[..index], rest[index + 1..].trim().to_string()),
+        None => (rest, String::new()),
+    };
+
+    let mut attributes = HashMap::new();
+    let mut remaining = header;
+    while let Some(eq) = remaining.find("=\"") {
+        let key = remaining[..eq]
+            .rsplit(char::is_whitespace)
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let value_start = eq + 2;
+        let Some(value_len) = remaining[value_start..].find('"') else {
+            break;
+        };
+        attributes.insert(
+            key,
+            remaining[value_start..value_start + value_len].to_string(),
+        );
+        remaining = &remaining[value_start + value_len + 1..];
+    }
+
+    (attributes, title)
+}
+
+/// Read favourites from an M3U file written by `export`
+fn parse_m3u(content: &str) -> Vec<(String, Vec<ExportedFavourite>)> {
+    let mut groups: Vec<(String, Vec<ExportedFavourite>)> = Vec::new();
+
+    for line in content.lines().filter(|l| l.starts_with("#EXTINF")) {
+        let (attributes, title) = parse_extinf(line);
+        let Some(provider) = attributes.get("iptv-provider") else {
+            eprintln!("Skipping '{}': no iptv-provider attribute", title);
+            continue;
+        };
+
+        let favourite = ExportedFavourite {
+            provider: provider.clone(),
+            provider_id: attributes.get("iptv-provider-id").cloned(),
+            name: attributes.get("tvg-name").cloned().unwrap_or(title),
+            stream_type: attributes
+                .get("iptv-type")
+                .cloned()
+                .unwrap_or_else(|| "live".to_string()),
+            stream_id: attributes
+                .get("iptv-stream-id")
+                .and_then(|id| id.parse().ok()),
+            category_id: None,
+            added_date: None,
+            container_extension: attributes.get("iptv-container").cloned(),
+            series_id: attributes
+                .get("iptv-series-id")
+                .and_then(|id| id.parse().ok()),
+        };
+
+        let group = attributes
+            .get("group-title")
+            .cloned()
+            .unwrap_or_else(|| iptv::favourites::DEFAULT_GROUP.to_string());
+        match groups.iter_mut().find(|(name, _)| *name == group) {
+            Some((_, favourites)) => favourites.push(favourite),
+            None => groups.push((group, vec![favourite])),
+        }
+    }
+
+    groups
+}
+
+/// Stream (id, name) lists by provider index and stream type; None if they couldn't be fetched
+type StreamLists = HashMap<(usize, String), Option<Vec<(u32, String)>>>;
+
+/// Resolves imported favourites against the local providers and merges them in
+struct Importer<'a> {
+    context: &'a CommandContext,
+    favourites_manager: &'a FavouritesManager,
+    on_conflict: ConflictPolicy,
+    apis: HashMap<usize, XTreamAPI>,
+    streams: StreamLists,
+    added: usize,
+    moved: usize,
+    replaced: usize,
+    unchanged: usize,
+    skipped: usize,
+}
+
+impl<'a> Importer<'a> {
+    fn new(
+        context: &'a CommandContext,
+        favourites_manager: &'a FavouritesManager,
+        on_conflict: ConflictPolicy,
+    ) -> Self {
+        Self {
+            context,
+            favourites_manager,
+            on_conflict,
+            apis: HashMap::new(),
+            streams: HashMap::new(),
+            added: 0,
+            moved: 0,
+            replaced: 0,
+            unchanged: 0,
+            skipped: 0,
+        }
+    }
+
+    /// Find the local provider for an imported favourite, falling back to the selected provider
+    fn resolve_provider(&self, favourite: &ExportedFavourite) -> Option<usize> {
+        let providers = &self.context.providers;
+        find_provider(
+            providers,
+            favourite.provider_id.as_deref(),
+            &favourite.provider,
+        )
+        .or_else(|| {
+            self.context
+                .selected_provider
+                .as_ref()
+                .and_then(|name| find_provider(providers, None, name))
+        })
+        .and_then(|provider| providers.iter().position(|p| std::ptr::eq(p, provider)))
+    }
+
+    async fn stream_list(
+        &mut self,
+        provider: usize,
+        stream_type: &str,
+    ) -> Option<&[(u32, String)]> {
+        let key = (provider, stream_type.to_string());
+        if !self.streams.contains_key(&key) {
+            let api = self.apis.get_mut(&provider)?;
+            let list =
+                match stream_type {
+                    "live" => api.get_live_streams(None).await.map(|streams| {
+                        streams.into_iter().map(|s| (s.stream_id, s.name)).collect()
+                    }),
+                    "movie" => api.get_vod_streams(None).await.map(|streams| {
+                        streams.into_iter().map(|s| (s.stream_id, s.name)).collect()
+                    }),
+                    "series" => api
+                        .get_series(None)
+                        .await
+                        .map(|series| series.into_iter().map(|s| (s.series_id, s.name)).collect()),
+                    _ => Err(anyhow::anyhow!("unknown stream type '{}'", stream_type)),
+                };
+            let list = match list {
+                Ok(list) => Some(list),
+                Err(e) => {
+                    eprintln!(
+                        "Warning: could not load {} streams for {}, trusting exported stream IDs: {}",
+                        stream_type,
+                        self.context.providers[provider].label(),
+                        e
+                    );
+                    None
+                }
+            };
+            self.streams.insert(key.clone(), list);
+        }
+        self.streams.get(&key)?.as_deref()
+    }
+
+    /// Match the stream by name, then by exported ID; trust the ID if the list is unavailable
+    async fn resolve_stream(
+        &mut self,
+        provider: usize,
+        favourite: &ExportedFavourite,
+    ) -> Option<(u32, String)> {
+        // Episodes aren't in any stream list; their IDs are kept as exported
+        if favourite.stream_type == "episode" {
+            return favourite.stream_id.map(|id| (id, favourite.name.clone()));
+        }
+
+        match self.stream_list(provider, &favourite.stream_type).await {
+            Some(list) => list
+                .iter()
+                .find(|(_, name)| *name == favourite.name)
+                .or_else(|| {
+                    list.iter()
+                        .find(|(_, name)| name.to_lowercase() == favourite.name.to_lowercase())
+                })
+                .or_else(|| {
+                    favourite
+                        .stream_id
+                        .and_then(|id| list.iter().find(|(stream_id, _)| *stream_id == id))
+                })
+                .cloned(),
+            None => favourite.stream_id.map(|id| (id, favourite.name.clone())),
+        }
+    }
+
+    async fn import(&mut self, group: &str, favourite: &ExportedFavourite) -> Result<()> {
+        let Some(provider) = self.resolve_provider(favourite) else {
+            eprintln!(
+                "Skipping '{}': provider '{}' is not configured",
+                favourite.name, favourite.provider
+            );
+            self.skipped += 1;
+            return Ok(());
+        };
+
+        if !self.apis.contains_key(&provider) {
+            let api = provider_api(&self.context.providers[provider])?;
+            self.apis.insert(provider, api);
+        }
+
+        let Some((stream_id, name)) = self.resolve_stream(provider, favourite).await else {
+            eprintln!(
+                "Skipping '{}': no matching {} stream on {}",
+                favourite.name,
+                favourite.stream_type,
+                self.context.providers[provider].label()
+            );
+            self.skipped += 1;
+            return Ok(());
+        };
+
+        let provider_hash = self.apis[&provider].provider_hash.clone();
+        let existing = self.favourites_manager.get_favourites(&provider_hash)?;
+        let key = FavouriteKey {
+            provider_hash: provider_hash.clone(),
+            stream_id,
+            stream_type: favourite.stream_type.clone(),
+        };
+
+        // Same stream already a favourite: only its group can differ
+        if existing.iter().any(|f| FavouriteKey::of(f) == key) {
+            let current_group = self.favourites_manager.group_of(&key)?;
+            if current_group.as_deref() != Some(group)
+                && matches!(self.on_conflict, ConflictPolicy::Replace)
+            {
+                self.favourites_manager.move_to_group(&key, group)?;
+                self.moved += 1;
+            } else {
+                self.unchanged += 1;
+            }
+            return Ok(());
+        }
+
+        // A favourite with the same name but another stream ID, e.g. after a renumbering
+        if let Some(clash) = existing
+            .iter()
+            .find(|f| f.stream_type == favourite.stream_type && f.name == name)
+        {
+            match self.on_conflict {
+                ConflictPolicy::Keep => {
+                    eprintln!(
+                        "Keeping existing '{}' (stream {}) instead of imported stream {}",
+                        clash.name, clash.stream_id, stream_id
+                    );
+                    self.unchanged += 1;
+                    return Ok(());
+                }
+                ConflictPolicy::Replace => {
+                    self.favourites_manager.remove_favourite(
+                        &provider_hash,
+                        clash.stream_id,
+                        &clash.stream_type,
+                    )?;
+                    self.replaced += 1;
+                }
+            }
+        } else {
+            self.added += 1;
+        }
+
+        self.favourites_manager.add_favourite(
+            &provider_hash,
+            FavouriteStream {
+                added_date: favourite.added_date.unwrap_or_else(Utc::now),
+                container_extension: favourite.container_extension.clone(),
+                series_id: favourite.series_id,
+                ..FavouriteStream::new(
+                    &provider_hash,
+                    stream_id,
+                    name,
+                    &favourite.stream_type,
+                    favourite.category_id.clone(),
+                )
+            },
+        )?;
+        self.favourites_manager.move_to_group(&key, group)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,539 @@
+use super::{CommandContext, ContentType, OutputFormat, provider_api};
+use anyhow::Result;
+use inquire::Confirm;
+use iptv::ignore::{
+    IgnoreConfig, IgnoreKind, IgnoreMatcher, IgnoreRule, PatternMode, PatternRule, PatternSyntax,
+};
+use iptv::xtream::XTreamAPI;
+use serde::Serialize;
+
+/// Number of hidden names listed per provider and content type in text output
+const MAX_LISTED: usize = 50;
+
+/// A pattern rule as given on the command line
+pub struct PatternArgs {
+    pub pattern: String,
+    pub regex: bool,
+    pub allow: bool,
+    pub channels: bool,
+    /// Select categories or channels by exact name or ID instead of a pattern
+    pub exact: bool,
+    pub content_type: Option<ContentType>,
+}
+
+impl PatternArgs {
+    fn rule(&self) -> PatternRule {
+        PatternRule {
+            pattern: self.pattern.clone(),
+            syntax: if self.regex {
+                PatternSyntax::Regex
+            } else {
+                PatternSyntax::Glob
+            },
+            mode: if self.allow {
+                PatternMode::Allow
+            } else {
+                PatternMode::Ignore
+            },
+            kind: if self.channels {
+                IgnoreKind::Channel
+            } else {
+                IgnoreKind::Category
+            },
+            content: self.content_type.map(|ct| ct.stream_type().to_string()),
+        }
+    }
+
+    /// Check the arguments and build what they select
+    fn selector(&self) -> Result<Selector> {
+        let rule = self.rule();
+        if self.exact {
+            return Ok(Selector::Exact(rule));
+        }
+        rule.compile()?;
+        let matcher = IgnoreMatcher::new(Vec::new(), std::slice::from_ref(&rule));
+        Ok(Selector::Pattern(rule, matcher))
+    }
+}
+
+/// Categories or channels picked out by a pattern rule, or by exact name or ID
+enum Selector {
+    Pattern(PatternRule, IgnoreMatcher),
+    /// The rule's pattern is the name (ignoring case) or ID
+    Exact(PatternRule),
+}
+
+impl Selector {
+    fn rule(&self) -> &PatternRule {
+        match self {
+            Self::Pattern(rule, _) | Self::Exact(rule) => rule,
+        }
+    }
+
+    fn selects(&self, content: &str, id: &str, name: &str) -> bool {
+        match self {
+            Self::Pattern(rule, matcher) => matcher.hides(rule.kind, content, id, name),
+            Self::Exact(rule) => {
+                rule.content.as_deref().is_none_or(|c| c == content)
+                    && (id == rule.pattern || name.eq_ignore_ascii_case(rule.pattern.trim()))
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Pattern(rule, _) => rule.describe(),
+            Self::Exact(rule) => {
+                let kind = match rule.kind {
+                    IgnoreKind::Category => "categories",
+                    IgnoreKind::Channel => "channels",
+                };
+                let content = rule
+                    .content
+                    .as_deref()
+                    .map(|c| format!("{} ", c))
+                    .unwrap_or_default();
+                format!(
+                    "hide {}{} named '{}' or with that ID",
+                    content, kind, rule.pattern
+                )
+            }
+        }
+    }
+
+    /// Whether an existing name or ID rule is one these arguments select, for `ignore remove`
+    fn selects_rule(&self, rule: &IgnoreRule) -> bool {
+        let Self::Exact(wanted) = self else {
+            return false;
+        };
+        (wanted.content.is_none() || rule.content == wanted.content)
+            && (rule.id.as_deref() == Some(wanted.pattern.as_str())
+                || rule.name.eq_ignore_ascii_case(wanted.pattern.trim()))
+    }
+}
+
+pub enum IgnoreCommand {
+    /// List the rules that apply to the selected providers
+    List { format: OutputFormat },
+    /// Show what a pattern rule would hide without saving it
+    Preview {
+        rule: PatternArgs,
+        format: OutputFormat,
+    },
+    /// Show what a pattern rule would hide, then save it
+    Add { rule: PatternArgs, yes: bool },
+    /// Remove a pattern rule, or the rules hiding a category or channel by name or ID
+    Remove {
+        rule: PatternArgs,
+        format: OutputFormat,
+    },
+}
+
+/// What a rule would hide on one provider for one content type
+#[derive(Debug, Serialize)]
+struct RulePreview {
+    provider: String,
+    #[serde(skip)]
+    provider_hash: String,
+    content: &'static str,
+    total: usize,
+    /// Names the rule would hide
+    hidden: Vec<String>,
+    /// IDs of the hidden names, in the same order
+    #[serde(skip)]
+    hidden_ids: Vec<String>,
+    /// How many of `hidden` existing rules already hide
+    already_hidden: usize,
+}
+
+/// A saved rule, as listed by `ignore list` and `ignore remove`
+#[derive(Debug, Serialize)]
+struct ListedRule {
+    /// Provider the rule applies to; None for rules for every provider
+    provider: Option<String>,
+    /// category or channel
+    kind: IgnoreKind,
+    /// live, movie or series; None for every content type
+    content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pattern: Option<PatternRule>,
+}
+
+impl ListedRule {
+    fn from_rule(provider: Option<String>, kind: IgnoreKind, rule: &IgnoreRule) -> Self {
+        Self {
+            provider,
+            kind,
+            content: rule.content.clone(),
+            id: rule.id.clone(),
+            name: Some(rule.name.clone()),
+            pattern: None,
+        }
+    }
+
+    fn from_pattern(provider: Option<String>, rule: &PatternRule) -> Self {
+        Self {
+            provider,
+            kind: rule.kind,
+            content: rule.content.clone(),
+            id: None,
+            name: None,
+            pattern: Some(rule.clone()),
+        }
+    }
+
+    fn describe(&self) -> String {
+        if let Some(pattern) = &self.pattern {
+            return pattern.describe();
+        }
+        let kind = match self.kind {
+            IgnoreKind::Category => "category",
+            IgnoreKind::Channel => "channel",
+        };
+        let content = self
+            .content
+            .as_deref()
+            .map(|c| format!("{} ", c))
+            .unwrap_or_default();
+        let name = self.name.as_deref().unwrap_or_default();
+        match &self.id {
+            Some(id) => format!("hide {}{} '{}' (ID {})", content, kind, name, id),
+            None => format!("hide {}{} named '{}'", content, kind, name),
+        }
+    }
+}
+
+impl IgnoreCommand {
+    pub async fn execute(self, context: CommandContext) -> Result<()> {
+        match self {
+            Self::List { format } => {
+                let rules = list_rules(&context)?;
+                match format {
+                    OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&rules)?),
+                    OutputFormat::Text => print_rules(&rules),
+                    OutputFormat::M3u | OutputFormat::Tsv => {
+                        anyhow::bail!("Rules can only be listed as 'text' or 'json'")
+                    }
+                }
+                Ok(())
+            }
+            Self::Preview { rule, format } => {
+                let selector = rule.selector()?;
+                let previews = preview(&context, &selector).await?;
+                match format {
+                    OutputFormat::Json => {
+                        println!("{}", serde_json::to_string_pretty(&previews)?)
+                    }
+                    OutputFormat::Text => print_previews(&selector, &previews),
+                    OutputFormat::M3u | OutputFormat::Tsv => {
+                        anyhow::bail!("Previews can only be shown as 'text' or 'json'")
+                    }
+                }
+                Ok(())
+            }
+            Self::Add { rule, yes } => {
+                let selector = rule.selector()?;
+                let previews = preview(&context, &selector).await?;
+                print_previews(&selector, &previews);
+
+                if let Selector::Exact(rule) = &selector
+                    && previews.iter().all(|p| p.hidden.is_empty())
+                {
+                    anyhow::bail!("Nothing named '{}' or with that ID found", rule.pattern);
+                }
+                if !yes
+                    && !Confirm::new("Save this rule?")
+                        .with_default(false)
+                        .prompt()?
+                {
+                    println!("Rule not saved");
+                    return Ok(());
+                }
+
+                match selector {
+                    // Exact matches are hidden by ID on each provider, as the TUI does
+                    Selector::Exact(rule) => {
+                        let mut ignore = IgnoreConfig::load()?;
+                        let mut added = 0;
+                        for preview in &previews {
+                            for (id, name) in preview.hidden_ids.iter().zip(&preview.hidden) {
+                                if ignore.hide(
+                                    &preview.provider_hash,
+                                    &preview.provider,
+                                    rule.kind,
+                                    preview.content,
+                                    id,
+                                    name,
+                                )? {
+                                    added += 1;
+                                }
+                            }
+                        }
+                        println!("Saved {} rule{}", added, if added == 1 { "" } else { "s" });
+                    }
+                    Selector::Pattern(rule, _) => {
+                        // With --provider the rule only applies to that provider
+                        let scope = match &context.selected_provider {
+                            Some(_) => {
+                                let provider = &context.selected_configs()?[0];
+                                Some((
+                                    provider_api(provider)?.provider_hash.clone(),
+                                    provider.label(),
+                                ))
+                            }
+                            None => None,
+                        };
+
+                        let mut ignore = IgnoreConfig::load()?;
+                        ignore.add_pattern(
+                            scope
+                                .as_ref()
+                                .map(|(hash, name)| (hash.as_str(), name.as_str())),
+                            rule.clone(),
+                        )?;
+                        match scope {
+                            Some((_, name)) => {
+                                println!("Saved rule for {}: {}", name, rule.describe())
+                            }
+                            None => println!("Saved rule for all providers: {}", rule.describe()),
+                        }
+                    }
+                }
+                Ok(())
+            }
+            Self::Remove { rule, format } => {
+                let selector = rule.selector()?;
+                let removed = remove_rules(&context, &selector)?;
+                if removed.is_empty() {
+                    anyhow::bail!("No saved rule matches: {}", selector.describe());
+                }
+                match format {
+                    OutputFormat::Json => {
+                        println!("{}", serde_json::to_string_pretty(&removed)?)
+                    }
+                    _ => {
+                        for rule in &removed {
+                            let provider = rule.provider.as_deref().unwrap_or("all providers");
+                            println!("Removed rule for {}: {}", provider, rule.describe());
+                        }
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Rules for every provider, then the rules of each selected provider
+fn list_rules(context: &CommandContext) -> Result<Vec<ListedRule>> {
+    let ignore = IgnoreConfig::load()?;
+    let mut rules: Vec<ListedRule> = ignore
+        .patterns
+        .iter()
+        .map(|rule| ListedRule::from_pattern(None, rule))
+        .collect();
+
+    for provider in context.selected_configs()? {
+        let provider_hash = provider_api(&provider)?.provider_hash;
+        let label = provider.label();
+        for kind in [IgnoreKind::Category, IgnoreKind::Channel] {
+            rules.extend(
+                ignore
+                    .rules(&provider_hash, kind)
+                    .iter()
+                    .map(|rule| ListedRule::from_rule(Some(label.clone()), kind, rule)),
+            );
+        }
+        if let Some(ignores) = ignore.providers.get(&provider_hash) {
+            rules.extend(
+                ignores
+                    .patterns
+                    .iter()
+                    .map(|rule| ListedRule::from_pattern(Some(label.clone()), rule)),
+            );
+        }
+    }
+    Ok(rules)
+}
+
+/// Remove the saved rules the arguments select, returning what was removed
+///
+/// A pattern is removed from the selected provider with --provider, otherwise from the
+/// rules for every provider and from each provider's own rules.
+fn remove_rules(context: &CommandContext, selector: &Selector) -> Result<Vec<ListedRule>> {
+    let mut ignore = IgnoreConfig::load()?;
+    let mut removed = Vec::new();
+
+    if let Selector::Pattern(rule, _) = selector
+        && context.selected_provider.is_none()
+        && ignore.remove_pattern(None, rule)?
+    {
+        removed.push(ListedRule::from_pattern(None, rule));
+    }
+
+    for provider in context.selected_configs()? {
+        let provider_hash = provider_api(&provider)?.provider_hash;
+        let label = provider.label();
+        match selector {
+            Selector::Pattern(rule, _) => {
+                if ignore.remove_pattern(Some(&provider_hash), rule)? {
+                    removed.push(ListedRule::from_pattern(Some(label), rule));
+                }
+            }
+            Selector::Exact(wanted) => {
+                for rule in ignore.rules(&provider_hash, wanted.kind) {
+                    if selector.selects_rule(&rule) {
+                        ignore.remove_rule(&provider_hash, wanted.kind, &rule)?;
+                        removed.push(ListedRule::from_rule(
+                            Some(label.clone()),
+                            wanted.kind,
+                            &rule,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    Ok(removed)
+}
+
+fn print_rules(rules: &[ListedRule]) {
+    if rules.is_empty() {
+        println!("No ignore rules");
+        return;
+    }
+    let mut provider = None;
+    for (index, rule) in rules.iter().enumerate() {
+        if index == 0 || rule.provider != provider {
+            provider = rule.provider.clone();
+            let heading = provider.as_deref().unwrap_or("All providers");
+            println!("{}{}:", if index == 0 { "" } else { "\n" }, heading);
+        }
+        println!("    {}", rule.describe());
+    }
+}
+
+/// Run a rule against the categories or streams of the providers it would apply to
+async fn preview(context: &CommandContext, selector: &Selector) -> Result<Vec<RulePreview>> {
+    let ignore = IgnoreConfig::load()?;
+    let rule = selector.rule();
+    let content_types = match &rule.content {
+        Some(content) => vec![ContentType::from_str(content)?],
+        None => vec![ContentType::Live, ContentType::Movie, ContentType::Series],
+    };
+
+    let mut previews = Vec::new();
+    for provider in context.selected_configs()? {
+        let mut api = provider_api(&provider)?;
+        let existing = ignore.matcher(&api.provider_hash);
+
+        for content_type in &content_types {
+            let content = content_type.stream_type();
+            let names = match load_names(&mut api, rule.kind, *content_type).await {
+                Ok(names) => names,
+                Err(e) => {
+                    eprintln!(
+                        "Warning: could not load {} for {}: {}",
+                        content,
+                        provider.label(),
+                        e
+                    );
+                    continue;
+                }
+            };
+
+            let mut hidden = Vec::new();
+            let mut hidden_ids = Vec::new();
+            let mut already_hidden = 0;
+            for (id, name) in &names {
+                if selector.selects(content, id, name) {
+                    if existing.hides(rule.kind, content, id, name) {
+                        already_hidden += 1;
+                    }
+                    hidden.push(name.clone());
+                    hidden_ids.push(id.clone());
+                }
+            }
+
+            previews.push(RulePreview {
+                provider: provider.label(),
+                provider_hash: api.provider_hash.clone(),
+                content,
+                total: names.len(),
+                hidden,
+                hidden_ids,
+                already_hidden,
+            });
+        }
+    }
+
+    Ok(previews)
+}
+
+/// (id, name) of every category or stream of a content type
+async fn load_names(
+    api: &mut XTreamAPI,
+    kind: IgnoreKind,
+    content_type: ContentType,
+) -> Result<Vec<(String, String)>> {
+    let names = match (kind, content_type) {
+        (IgnoreKind::Category, ContentType::Live) => api.get_live_categories().await?,
+        (IgnoreKind::Category, ContentType::Movie) => api.get_vod_categories().await?,
+        (IgnoreKind::Category, ContentType::Series) => api.get_series_categories().await?,
+        (IgnoreKind::Channel, ContentType::Live) => {
+            return Ok(streams_to_names(api.get_live_streams(None).await?));
+        }
+        (IgnoreKind::Channel, ContentType::Movie) => {
+            return Ok(streams_to_names(api.get_vod_streams(None).await?));
+        }
+        (IgnoreKind::Channel, ContentType::Series) => {
+            return Ok(api
+                .get_series(None)
+                .await?
+                .into_iter()
+                .map(|s| (s.series_id.to_string(), s.name))
+                .collect());
+        }
+    };
+    Ok(names
+        .into_iter()
+        .map(|c| (c.category_id, c.category_name))
+        .collect())
+}
+
+fn streams_to_names(streams: Vec<iptv::xtream::Stream>) -> Vec<(String, String)> {
+    streams
+        .into_iter()
+        .map(|s| (s.stream_id.to_string(), s.name))
+        .collect()
+}
+
+fn print_previews(selector: &Selector, previews: &[RulePreview]) {
+    let kind = match selector.rule().kind {
+        IgnoreKind::Category => "categories",
+        IgnoreKind::Channel => "channels",
+    };
+
+    println!("Rule: {}", selector.describe());
+    for preview in previews {
+        println!(
+            "\n{}: would hide {} of {} {} {} ({} already hidden)",
+            preview.provider,
+            preview.hidden.len(),
+            preview.total,
+            preview.content,
+            kind,
+            preview.already_hidden
+        );
+        for name in preview.hidden.iter().take(MAX_LISTED) {
+            println!("    {}", name);
+        }
+        if preview.hidden.len() > MAX_LISTED {
+            println!("    ... and {} more", preview.hidden.len() - MAX_LISTED);
+        }
+    }
+}
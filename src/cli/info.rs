@@ -0,0 +1,324 @@
+use super::play::{PlayTarget, find_target};
+use super::{CommandContext, ContentType, OutputFormat, categories_by_id};
+use anyhow::{Context, Result};
+use iptv::favourites::FavouritesManager;
+use iptv::xtream::{FavouriteStream, XTreamAPI};
+use serde::Serialize;
+use serde_json::Value;
+
+pub struct InfoCommand {
+    /// Movie or series to describe; names are looked up in movies and series unless a
+    /// content type is given
+    pub target: PlayTarget,
+    /// List every episode of a series in text output; JSON always includes them
+    pub episodes: bool,
+    pub format: OutputFormat,
+}
+
+/// Summary of a movie or series
+///
+/// This is the JSON schema of `cli info`: every field is always present, null when the
+/// provider doesn't give it, and `seasons` is empty for movies.
+#[derive(Debug, Serialize)]
+struct InfoSummary {
+    provider: String,
+    /// movie or series
+    #[serde(rename = "type")]
+    content_type: &'static str,
+    id: u32,
+    name: String,
+    category_id: Option<String>,
+    category: Option<String>,
+    plot: Option<String>,
+    cast: Option<String>,
+    director: Option<String>,
+    genre: Option<String>,
+    release_date: Option<String>,
+    /// Out of 10
+    rating: Option<f64>,
+    /// As given by the provider, e.g. "01:52:10" for movies or "45" (minutes) for series
+    duration: Option<String>,
+    duration_secs: Option<u64>,
+    cover: Option<String>,
+    trailer: Option<String>,
+    container_extension: Option<String>,
+    /// Playback URL of a movie; series are played by episode
+    url: Option<String>,
+    seasons: Vec<SeasonSummary>,
+}
+
+#[derive(Debug, Serialize)]
+struct SeasonSummary {
+    season: u32,
+    name: Option<String>,
+    episode_count: usize,
+    episodes: Vec<EpisodeSummary>,
+}
+
+#[derive(Debug, Serialize)]
+struct EpisodeSummary {
+    id: String,
+    season: u32,
+    episode: u32,
+    title: String,
+    plot: Option<String>,
+    rating: Option<f64>,
+    duration: Option<String>,
+    duration_secs: Option<u64>,
+    release_date: Option<String>,
+    url: String,
+}
+
+impl InfoCommand {
+    pub async fn execute(self, context: CommandContext) -> Result<()> {
+        if matches!(self.format, OutputFormat::M3u | OutputFormat::Tsv) {
+            anyhow::bail!("Info can only be shown as 'text' or 'json'");
+        }
+        if let PlayTarget::Id {
+            content_type: ContentType::Live,
+            ..
+        }
+        | PlayTarget::Name {
+            content_type: Some(ContentType::Live),
+            ..
+        } = self.target
+        {
+            anyhow::bail!("Info is only available for movies and series");
+        }
+
+        let mut providers = context.get_providers_for_search().await?;
+        let favourites_manager = FavouritesManager::new()?;
+        let (provider, item) = find_target(
+            &context,
+            &mut providers,
+            &favourites_manager,
+            &self.target,
+            &[ContentType::Movie, ContentType::Series],
+        )
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("{}", self.target.not_found_message()))?;
+        let (api, provider_name) = &mut providers[provider];
+
+        let summary = match item.stream_type.as_str() {
+            "series" => series_summary(api, provider_name, item).await?,
+            "movie" => movie_summary(api, provider_name, item).await?,
+            // A favourite or recent can resolve to a channel or episode
+            _ => anyhow::bail!("Info is only available for movies and series"),
+        };
+
+        match self.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&summary)?),
+            _ => print_summary(&summary, self.episodes),
+        }
+        Ok(())
+    }
+}
+
+async fn movie_summary(
+    api: &mut XTreamAPI,
+    provider_name: &str,
+    item: FavouriteStream,
+) -> Result<InfoSummary> {
+    let vod = api
+        .get_vod_info(item.stream_id)
+        .await
+        .with_context(|| format!("Failed to load movie info for {}", item.name))?;
+    let categories = categories_by_id(api, ContentType::Movie).await;
+    let category_id = vod.movie_data.category_id.clone().or(item.category_id);
+    let extension = vod.movie_data.container_extension.clone();
+    let info = vod.info;
+
+    Ok(InfoSummary {
+        provider: provider_name.to_string(),
+        content_type: "movie",
+        id: item.stream_id,
+        name: text(Some(info.name)).unwrap_or(item.name),
+        category: category_id
+            .as_ref()
+            .and_then(|id| categories.get(id))
+            .map(|c| c.category_name.clone()),
+        category_id,
+        plot: text(info.plot),
+        cast: text(info.cast),
+        director: text(info.director),
+        genre: text(info.genre),
+        release_date: text(info.releasedate),
+        rating: rating(info.rating.as_deref()),
+        duration: text(info.duration),
+        duration_secs: info.duration_secs.as_ref().and_then(seconds),
+        cover: text(info.movie_image),
+        trailer: text(info.youtube_trailer),
+        url: Some(api.get_stream_url(item.stream_id, "movie", Some(&extension))),
+        container_extension: Some(extension),
+        seasons: Vec::new(),
+    })
+}
+
+async fn series_summary(
+    api: &mut XTreamAPI,
+    provider_name: &str,
+    item: FavouriteStream,
+) -> Result<InfoSummary> {
+    let series = api
+        .get_series_info(item.stream_id)
+        .await
+        .with_context(|| format!("Failed to load series info for {}", item.name))?;
+    let categories = categories_by_id(api, ContentType::Series).await;
+
+    let mut seasons: Vec<SeasonSummary> = Vec::new();
+    for episode in series.sorted_episodes() {
+        let info = episode.info.as_ref();
+        let summary = EpisodeSummary {
+            id: episode.id.clone(),
+            season: episode.season,
+            episode: episode.episode_num,
+            title: episode.title.clone(),
+            plot: text(info.and_then(|i| i.plot.clone())),
+            rating: rating(info.and_then(|i| i.rating.as_deref())),
+            duration: text(info.and_then(|i| i.duration.clone())),
+            duration_secs: info.and_then(|i| i.duration_secs).map(u64::from),
+            release_date: text(info.and_then(|i| i.releasedate.clone())),
+            url: api.get_episode_stream_url(&episode.id, episode.container_extension.as_deref()),
+        };
+        match seasons.last_mut() {
+            Some(season) if season.season == episode.season => season.episodes.push(summary),
+            _ => seasons.push(SeasonSummary {
+                season: episode.season,
+                name: series
+                    .seasons
+                    .iter()
+                    .find(|s| s.season_number == episode.season)
+                    .and_then(|s| text(Some(s.name.clone()))),
+                episode_count: 0,
+                episodes: vec![summary],
+            }),
+        }
+    }
+    for season in &mut seasons {
+        season.episode_count = season.episodes.len();
+    }
+
+    let info = series.info;
+    let category_id = info
+        .as_ref()
+        .and_then(|i| i.category_id.clone())
+        .or(item.category_id);
+    Ok(InfoSummary {
+        provider: provider_name.to_string(),
+        content_type: "series",
+        id: item.stream_id,
+        name: text(info.as_ref().map(|i| i.name.clone())).unwrap_or(item.name),
+        category: category_id
+            .as_ref()
+            .and_then(|id| categories.get(id))
+            .map(|c| c.category_name.clone()),
+        category_id,
+        plot: text(info.as_ref().and_then(|i| i.plot.clone())),
+        cast: text(info.as_ref().and_then(|i| i.cast.clone())),
+        director: text(info.as_ref().and_then(|i| i.director.clone())),
+        genre: text(info.as_ref().and_then(|i| i.genre.clone())),
+        release_date: text(info.as_ref().and_then(|i| i.release_date.clone())),
+        rating: info.as_ref().and_then(|i| i.rating_value()),
+        duration: text(info.as_ref().and_then(|i| i.episode_run_time.clone())),
+        duration_secs: None,
+        cover: text(info.as_ref().and_then(|i| i.cover.clone())),
+        trailer: text(info.as_ref().and_then(|i| i.youtube_trailer.clone())),
+        container_extension: None,
+        url: None,
+        seasons,
+    })
+}
+
+fn print_summary(summary: &InfoSummary, episodes: bool) {
+    println!("{}", summary.name);
+    let mut details = vec![summary.content_type.to_string(), summary.id.to_string()];
+    details.extend(summary.category.clone());
+    details.push(summary.provider.clone());
+    println!("{}", details.join(" · "));
+
+    if let Some(plot) = &summary.plot {
+        println!("\n{}", plot);
+    }
+
+    println!();
+    let rating = summary.rating.map(|r| format!("{:.1}/10", r));
+    // Series give their episode run time in minutes
+    let duration = summary.duration.as_ref().map(|d| match d.parse::<u32>() {
+        Ok(minutes) => format!("{} min", minutes),
+        Err(_) => d.clone(),
+    });
+    for (label, value) in [
+        ("Genre", &summary.genre),
+        ("Release", &summary.release_date),
+        ("Rating", &rating),
+        ("Duration", &duration),
+        ("Director", &summary.director),
+        ("Cast", &summary.cast),
+        ("Trailer", &summary.trailer),
+        ("Format", &summary.container_extension),
+    ] {
+        if let Some(value) = value {
+            println!("{:<10}{}", format!("{}:", label), value);
+        }
+    }
+
+    if let Some(url) = &summary.url {
+        println!("\nStream URL:\n   {}", url);
+    }
+
+    if summary.content_type == "series" {
+        if summary.seasons.is_empty() {
+            println!("\nNo episodes");
+        } else {
+            println!("\nSeasons:");
+        }
+        for season in &summary.seasons {
+            let name = season
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("Season {}", season.season));
+            let plural = if season.episode_count == 1 { "" } else { "s" };
+            println!("  {}: {} episode{}", name, season.episode_count, plural);
+            if !episodes {
+                continue;
+            }
+            for episode in &season.episodes {
+                match &episode.duration {
+                    Some(duration) => println!(
+                        "    S{:02}E{:02} {} ({})",
+                        episode.season, episode.episode, episode.title, duration
+                    ),
+                    None => println!(
+                        "    S{:02}E{:02} {}",
+                        episode.season, episode.episode, episode.title
+                    ),
+                }
+                println!("      {}", episode.url);
+            }
+        }
+    }
+}
+
+/// Trimmed text, or None if empty
+fn text(value: Option<String>) -> Option<String> {
+    value
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// A positive rating parsed from the provider's string
+fn rating(value: Option<&str>) -> Option<f64> {
+    value
+        .and_then(|r| r.trim().parse::<f64>().ok())
+        .filter(|r| *r > 0.0)
+}
+
+/// Seconds from a provider value given as a number or a numeric string
+fn seconds(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+    .filter(|s| *s > 0)
+}
@@ -0,0 +1,497 @@
+use super::{CommandContext, ContentType, OutputFormat, categories_by_id, favourites::m3u_escape};
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use iptv::ignore::{IgnoreConfig, IgnoreMatcher};
+use iptv::parental;
+use iptv::xtream::{Category, XTreamAPI};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// What to list
+pub enum ListTarget {
+    /// Categories of one content type, or of all of them
+    Categories { content_type: Option<ContentType> },
+    /// Channels and movies, or series, optionally in one category
+    Streams {
+        content_types: Vec<ContentType>,
+        category: Option<String>,
+    },
+    /// Episodes of a series, looked up by ID or name
+    Episodes { series: String, season: Option<u32> },
+}
+
+pub struct ListCommand {
+    pub target: ListTarget,
+    /// Only list items added at or after this time
+    pub added_since: Option<DateTime<Utc>>,
+    pub format: OutputFormat,
+}
+
+/// One listed category, stream, series or episode
+///
+/// This is the JSON schema of `cli list`; IDs are strings as categories and episodes use
+/// string IDs.
+#[derive(Debug, Serialize)]
+struct ListEntry {
+    provider: String,
+    /// live, movie, series or episode; for categories, the content type they hold
+    #[serde(rename = "type")]
+    content_type: &'static str,
+    id: String,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    category_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    series_id: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    season: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    episode: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    added: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rating: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    icon: Option<String>,
+    /// Playback URL; left out of JSON and TSV as it contains the provider credentials
+    #[serde(skip)]
+    url: Option<String>,
+}
+
+impl ListCommand {
+    pub async fn execute(self, context: CommandContext) -> Result<()> {
+        if matches!(self.target, ListTarget::Categories { .. })
+            && matches!(self.format, OutputFormat::M3u)
+        {
+            anyhow::bail!("Categories can only be listed as 'text', 'json' or 'tsv'");
+        }
+
+        let providers = context.get_providers_for_search().await?;
+        let ignore = IgnoreConfig::load()?;
+
+        let mut entries = Vec::new();
+        let mut category_found = false;
+        let mut series_found = false;
+        for (mut api, provider_name) in providers {
+            api.disable_progress();
+            let mut matcher = context.ignore_matcher(&ignore, &provider_name)?;
+            matcher
+                .load_cached_categories(&api.cache_manager, &api.provider_hash)
+                .await;
+
+            match &self.target {
+                ListTarget::Categories { content_type } => {
+                    let content_types = match content_type {
+                        Some(content_type) => vec![*content_type],
+                        None => vec![ContentType::Live, ContentType::Movie, ContentType::Series],
+                    };
+                    for content_type in content_types {
+                        entries.extend(
+                            list_categories(&mut api, &matcher, &provider_name, content_type)
+                                .await?,
+                        );
+                    }
+                }
+                ListTarget::Streams {
+                    content_types,
+                    category,
+                } => {
+                    for content_type in content_types {
+                        let categories = categories_by_id(&mut api, *content_type).await;
+                        let wanted = match category {
+                            Some(category) => {
+                                let wanted = matching_categories(&categories, category);
+                                if wanted.is_empty() {
+                                    continue;
+                                }
+                                category_found = true;
+                                Some(wanted)
+                            }
+                            None => None,
+                        };
+                        let streams = self
+                            .list_streams(
+                                &mut api,
+                                &matcher,
+                                &provider_name,
+                                *content_type,
+                                &categories,
+                                wanted.as_deref(),
+                            )
+                            .await;
+                        match streams {
+                            Ok(streams) => entries.extend(streams),
+                            // Listing channels and movies together, one failing doesn't stop the other
+                            Err(e) if content_types.len() > 1 => eprintln!(
+                                "Warning: Failed to list {} in {}: {:#}",
+                                content_type.stream_type(),
+                                provider_name,
+                                e
+                            ),
+                            Err(e) => return Err(e),
+                        }
+                    }
+                }
+                ListTarget::Episodes { series, season } => {
+                    // With several providers, only some of them need to have the series
+                    if let Some(episodes) = self
+                        .list_episodes(&mut api, &matcher, &provider_name, series, *season)
+                        .await?
+                    {
+                        series_found = true;
+                        entries.extend(episodes);
+                    }
+                }
+            }
+        }
+
+        if let ListTarget::Streams {
+            category: Some(category),
+            ..
+        } = &self.target
+            && !category_found
+        {
+            anyhow::bail!("No category matching '{}'", category);
+        }
+        if let ListTarget::Episodes { series, .. } = &self.target
+            && !series_found
+        {
+            anyhow::bail!("No series '{}'", series);
+        }
+
+        match self.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&entries)?),
+            OutputFormat::Tsv => print_tsv(&entries),
+            OutputFormat::M3u => print_m3u(&entries),
+            OutputFormat::Text => print_text(&entries),
+        }
+        Ok(())
+    }
+
+    /// Channels, movies or series in the wanted categories that aren't hidden
+    async fn list_streams(
+        &self,
+        api: &mut XTreamAPI,
+        matcher: &IgnoreMatcher,
+        provider_name: &str,
+        content_type: ContentType,
+        categories: &HashMap<String, Category>,
+        wanted: Option<&[String]>,
+    ) -> Result<Vec<ListEntry>> {
+        let stream_type = content_type.stream_type();
+        let in_wanted = |category_id: Option<&String>| {
+            wanted.is_none_or(|wanted| category_id.is_some_and(|id| wanted.contains(id)))
+        };
+
+        let mut entries = Vec::new();
+        if matches!(content_type, ContentType::Series) {
+            for series in api.get_series(None).await? {
+                let category = series
+                    .category_id
+                    .as_ref()
+                    .and_then(|id| categories.get(id));
+                let added = series.added_at();
+                if !in_wanted(series.category_id.as_ref())
+                    || !self.added_in_range(added)
+                    || matcher.hides_stream(
+                        stream_type,
+                        series.series_id,
+                        &series.name,
+                        category,
+                        parental::is_adult(series.is_adult.as_ref()),
+                    )
+                {
+                    continue;
+                }
+                entries.push(ListEntry {
+                    provider: provider_name.to_string(),
+                    content_type: stream_type,
+                    id: series.series_id.to_string(),
+                    added,
+                    rating: series.rating_value(),
+                    name: series.name,
+                    category_id: series.category_id,
+                    category: category.map(|c| c.category_name.clone()),
+                    series_id: None,
+                    season: None,
+                    episode: None,
+                    icon: series.cover,
+                    url: None,
+                });
+            }
+            return Ok(entries);
+        }
+
+        let streams = if matches!(content_type, ContentType::Live) {
+            api.get_live_streams(None).await?
+        } else {
+            api.get_vod_streams(None).await?
+        };
+        for stream in streams {
+            let category = stream
+                .category_id
+                .as_ref()
+                .and_then(|id| categories.get(id));
+            if !in_wanted(stream.category_id.as_ref())
+                || !self.added_in_range(stream.added_at())
+                || matcher.hides_stream(
+                    stream_type,
+                    stream.stream_id,
+                    &stream.name,
+                    category,
+                    parental::is_adult(stream.is_adult.as_ref()),
+                )
+            {
+                continue;
+            }
+            let extension = match content_type {
+                ContentType::Movie => stream.container_extension.as_deref(),
+                _ => Some("ts"),
+            };
+            entries.push(ListEntry {
+                provider: provider_name.to_string(),
+                content_type: stream_type,
+                id: stream.stream_id.to_string(),
+                url: Some(api.get_stream_url(stream.stream_id, stream_type, extension)),
+                added: stream.added_at(),
+                rating: stream.rating_value(),
+                name: stream.name,
+                category_id: stream.category_id,
+                category: category.map(|c| c.category_name.clone()),
+                series_id: None,
+                season: None,
+                episode: None,
+                icon: stream.stream_icon.filter(|icon| !icon.is_empty()),
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Episodes of the series with this ID or name (ignoring case)
+    ///
+    /// Returns None if the provider has no such series or it is hidden.
+    async fn list_episodes(
+        &self,
+        api: &mut XTreamAPI,
+        matcher: &IgnoreMatcher,
+        provider_name: &str,
+        series: &str,
+        season: Option<u32>,
+    ) -> Result<Option<Vec<ListEntry>>> {
+        let categories = categories_by_id(api, ContentType::Series).await;
+        let all_series = api.get_series(None).await?;
+        let wanted_id = series.trim().parse::<u32>().ok();
+        let Some(found) = all_series.into_iter().find(|s| {
+            Some(s.series_id) == wanted_id || s.name.trim().eq_ignore_ascii_case(series.trim())
+        }) else {
+            return Ok(None);
+        };
+
+        let category = found.category_id.as_ref().and_then(|id| categories.get(id));
+        if matcher.hides_stream(
+            "series",
+            found.series_id,
+            &found.name,
+            category,
+            parental::is_adult(found.is_adult.as_ref()),
+        ) {
+            return Ok(None);
+        }
+
+        let info = api
+            .get_series_info(found.series_id)
+            .await
+            .with_context(|| format!("Failed to load episodes of {}", found.name))?;
+        let mut entries = Vec::new();
+        for episode in info.sorted_episodes() {
+            let added = episode
+                .added
+                .as_deref()
+                .and_then(|added| added.trim().parse::<i64>().ok())
+                .and_then(|timestamp| DateTime::from_timestamp(timestamp, 0));
+            if season.is_some_and(|season| season != episode.season) || !self.added_in_range(added)
+            {
+                continue;
+            }
+            entries.push(ListEntry {
+                provider: provider_name.to_string(),
+                content_type: "episode",
+                id: episode.id.clone(),
+                name: episode.label(),
+                category_id: found.category_id.clone(),
+                category: category.map(|c| c.category_name.clone()),
+                series_id: Some(found.series_id),
+                season: Some(episode.season),
+                episode: Some(episode.episode_num),
+                added,
+                rating: episode
+                    .info
+                    .as_ref()
+                    .and_then(|info| info.rating.as_deref())
+                    .and_then(|r| r.trim().parse::<f64>().ok())
+                    .filter(|r| *r > 0.0),
+                icon: episode
+                    .info
+                    .as_ref()
+                    .and_then(|info| info.movie_image.clone())
+                    .filter(|icon| !icon.is_empty()),
+                url: Some(
+                    api.get_episode_stream_url(&episode.id, episode.container_extension.as_deref()),
+                ),
+            });
+        }
+        Ok(Some(entries))
+    }
+
+    /// Whether an item passes --added-since; items without a date are dropped when it is set
+    fn added_in_range(&self, added: Option<DateTime<Utc>>) -> bool {
+        match self.added_since {
+            Some(since) => added.is_some_and(|added| added >= since),
+            None => true,
+        }
+    }
+}
+
+/// Parse --added-since: a date (2025-01-31) or an age in hours, days or weeks (12h, 7d, 2w)
+pub fn parse_added_since(s: &str) -> Result<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+
+    let invalid = || {
+        anyhow::anyhow!(
+            "Invalid date: {}. Use YYYY-MM-DD or an age such as 12h, 7d or 2w",
+            s
+        )
+    };
+    let (split, unit) = s.char_indices().last().ok_or_else(invalid)?;
+    let amount: i64 = s[..split]
+        .parse()
+        .ok()
+        .filter(|amount| *amount >= 0)
+        .ok_or_else(invalid)?;
+    // Ages too large to represent are invalid rather than a panic
+    let age = match unit {
+        'h' => chrono::TimeDelta::try_hours(amount),
+        'd' => chrono::TimeDelta::try_days(amount),
+        'w' => chrono::TimeDelta::try_weeks(amount),
+        _ => None,
+    };
+    age.and_then(|age| Utc::now().checked_sub_signed(age))
+        .ok_or_else(invalid)
+}
+
+/// Visible categories of one content type
+async fn list_categories(
+    api: &mut XTreamAPI,
+    matcher: &IgnoreMatcher,
+    provider_name: &str,
+    content_type: ContentType,
+) -> Result<Vec<ListEntry>> {
+    let categories = match content_type {
+        ContentType::Live => api.get_live_categories().await?,
+        ContentType::Movie => api.get_vod_categories().await?,
+        ContentType::Series => api.get_series_categories().await?,
+    };
+    let stream_type = content_type.stream_type();
+    Ok(categories
+        .into_iter()
+        .filter(|category| !matcher.hides_category(stream_type, category))
+        .map(|category| ListEntry {
+            provider: provider_name.to_string(),
+            content_type: stream_type,
+            id: category.category_id,
+            name: category.category_name,
+            category_id: None,
+            category: None,
+            series_id: None,
+            season: None,
+            episode: None,
+            added: None,
+            rating: None,
+            icon: None,
+            url: None,
+        })
+        .collect())
+}
+
+/// IDs of the categories whose ID is `wanted`, or whose name is (ignoring case)
+pub(super) fn matching_categories(
+    categories: &HashMap<String, Category>,
+    wanted: &str,
+) -> Vec<String> {
+    let wanted = wanted.trim();
+    categories
+        .values()
+        .filter(|c| c.category_id == wanted || c.category_name.trim().eq_ignore_ascii_case(wanted))
+        .map(|c| c.category_id.clone())
+        .collect()
+}
+
+fn print_text(entries: &[ListEntry]) {
+    if entries.is_empty() {
+        eprintln!("Nothing found");
+        return;
+    }
+    let multi_provider = entries.iter().any(|e| e.provider != entries[0].provider);
+    let mut provider = None;
+    for entry in entries {
+        if multi_provider && provider != Some(&entry.provider) {
+            println!("\n{}:", entry.provider);
+            provider = Some(&entry.provider);
+        }
+        match &entry.category {
+            Some(category) => println!(
+                "[{}] {} ({}) - {}",
+                entry.content_type, entry.name, entry.id, category
+            ),
+            None => println!("[{}] {} ({})", entry.content_type, entry.name, entry.id),
+        }
+    }
+}
+
+/// Tab-separated values with a header row; tabs and newlines in names become spaces
+fn print_tsv(entries: &[ListEntry]) {
+    let clean = |value: &str| value.replace(['\t', '\n', '\r'], " ");
+    println!("provider\ttype\tid\tname\tcategory_id\tcategory\tadded\trating");
+    for entry in entries {
+        println!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            clean(&entry.provider),
+            entry.content_type,
+            clean(&entry.id),
+            clean(&entry.name),
+            clean(entry.category_id.as_deref().unwrap_or_default()),
+            clean(entry.category.as_deref().unwrap_or_default()),
+            entry.added.map(|a| a.to_rfc3339()).unwrap_or_default(),
+            entry.rating.map(|r| r.to_string()).unwrap_or_default(),
+        );
+    }
+}
+
+/// A playlist of the playable entries; series themselves have no stream, only episodes
+fn print_m3u(entries: &[ListEntry]) {
+    println!("#EXTM3U");
+    for entry in entries {
+        let Some(url) = &entry.url else {
+            continue;
+        };
+        let mut attributes = vec![
+            format!("tvg-name=\"{}\"", m3u_escape(&entry.name)),
+            format!(
+                "group-title=\"{}\"",
+                m3u_escape(entry.category.as_deref().unwrap_or_default())
+            ),
+            format!("iptv-provider=\"{}\"", m3u_escape(&entry.provider)),
+            format!("iptv-type=\"{}\"", entry.content_type),
+        ];
+        if let Some(icon) = &entry.icon {
+            attributes.push(format!("tvg-logo=\"{}\"", m3u_escape(icon)));
+        }
+        println!("#EXTINF:-1 {},{}", attributes.join(" "), entry.name);
+        println!("{}", url);
+    }
+}
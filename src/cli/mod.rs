@@ -3,13 +3,65 @@ use inquire::Select;
 use serde::{Deserialize, Serialize};
 
 use iptv::config::ProviderConfig;
-use iptv::xtream::XTreamAPI;
+use iptv::ignore::{IgnoreConfig, IgnoreMatcher};
+use iptv::parental::ParentalFilter;
+use iptv::xtream::{Category, XTreamAPI};
+use std::collections::HashMap;
 
+pub mod account;
 pub mod cache;
+pub mod doctor;
+pub mod favourites;
+pub mod ignore;
+pub mod info;
+pub mod list;
+pub mod parental;
+pub mod play;
+pub mod provider;
 pub mod search;
 
+pub use account::AccountCommand;
 pub use cache::CacheCommand;
-pub use search::SearchCommand;
+pub use doctor::DoctorCommand;
+pub use favourites::{ConflictPolicy, FavouritesCommand, MoveTo};
+pub use ignore::{IgnoreCommand, PatternArgs};
+pub use info::InfoCommand;
+pub use list::{ListCommand, ListTarget};
+pub use parental::ParentalCommand;
+pub use play::{EpisodeRef, PlayCommand, PlayTarget, PlaybackMode, StreamFormat};
+pub use provider::ProviderCommand;
+pub use search::{SearchCommand, SearchField};
+
+/// Create an API client whose provider hash matches the one the TUI uses for favourites
+/// and ignore rules
+pub fn provider_api(provider: &ProviderConfig) -> Result<XTreamAPI> {
+    let mut api = XTreamAPI::new_with_id(
+        provider.url.clone(),
+        provider.username.clone(),
+        provider.password.clone(),
+        provider.name.clone(),
+        provider.id.clone(),
+    )?;
+    api.disable_progress();
+    Ok(api)
+}
+
+/// Categories by ID, so items in ignored categories can be dropped
+pub async fn categories_by_id(
+    api: &mut XTreamAPI,
+    content_type: ContentType,
+) -> HashMap<String, Category> {
+    let categories = match content_type {
+        ContentType::Live => api.get_live_categories().await,
+        ContentType::Movie => api.get_vod_categories().await,
+        ContentType::Series => api.get_series_categories().await,
+    };
+    categories
+        .unwrap_or_default()
+        .into_iter()
+        .map(|c| (c.category_id.clone(), c))
+        .collect()
+}
 
 /// Output format for command results
 #[derive(Debug, Clone, Copy)]
@@ -17,6 +69,8 @@ pub enum OutputFormat {
     Text,
     Json,
     M3u,
+    /// Tab-separated values with a header row
+    Tsv,
 }
 
 impl OutputFormat {
@@ -25,16 +79,36 @@ impl OutputFormat {
             "text" => Ok(Self::Text),
             "json" => Ok(Self::Json),
             "m3u" => Ok(Self::M3u),
-            _ => anyhow::bail!("Invalid format: {}. Use 'text', 'json', or 'm3u'", s),
+            "tsv" => Ok(Self::Tsv),
+            _ => anyhow::bail!("Invalid format: {}. Use 'text', 'json', 'm3u' or 'tsv'", s),
         }
     }
 }
 
+/// Find a configured provider by id first, then by name or URL (case-insensitive)
+pub fn find_provider<'a>(
+    providers: &'a [ProviderConfig],
+    id: Option<&str>,
+    name: &str,
+) -> Option<&'a ProviderConfig> {
+    id.and_then(|id| providers.iter().find(|p| p.id.as_deref() == Some(id)))
+        .or_else(|| {
+            providers.iter().find(|p| {
+                p.name
+                    .as_ref()
+                    .is_some_and(|n| n.to_lowercase() == name.to_lowercase())
+            })
+        })
+        .or_else(|| providers.iter().find(|p| p.url == name))
+}
+
 /// Context for command execution with provider management
 pub struct CommandContext {
     pub providers: Vec<ProviderConfig>,
     pub selected_provider: Option<String>,
     pub all_providers: bool,
+    /// Parental filter for this run; None when parental controls are off or unlocked
+    pub parental: Option<ParentalFilter>,
 }
 
 impl CommandContext {
@@ -47,9 +121,15 @@ impl CommandContext {
             providers,
             selected_provider,
             all_providers,
+            parental: None,
         }
     }
 
+    pub fn with_parental(mut self, parental: Option<ParentalFilter>) -> Self {
+        self.parental = parental;
+        self
+    }
+
     /// Get a single provider for commands that require exactly one
     pub async fn get_single_provider(&self) -> Result<(XTreamAPI, String)> {
         if self.providers.is_empty() {
@@ -57,15 +137,8 @@ impl CommandContext {
         }
 
         let provider = if let Some(name) = &self.selected_provider {
-            // Find provider by name (case-insensitive)
-            self.providers
-                .iter()
-                .find(|p| {
-                    p.name
-                        .as_ref()
-                        .map(|n| n.to_lowercase() == name.to_lowercase())
-                        .unwrap_or(false)
-                })
+            // Find provider by name (case-insensitive) or URL, as `selected_configs` does
+            find_provider(&self.providers, None, name)
                 .ok_or_else(|| anyhow::anyhow!("Provider '{}' not found", name))?
         } else if self.providers.len() == 1 {
             // Auto-select single provider
@@ -143,6 +216,40 @@ impl CommandContext {
         }
     }
 
+    /// Provider configs to work on: the selected one, or all of them
+    pub fn selected_configs(&self) -> Result<Vec<ProviderConfig>> {
+        match &self.selected_provider {
+            Some(name) => {
+                let provider = find_provider(&self.providers, None, name)
+                    .ok_or_else(|| anyhow::anyhow!("Provider '{}' not found", name))?;
+                Ok(vec![provider.clone()])
+            }
+            None => Ok(self.providers.clone()),
+        }
+    }
+
+    /// Ignore rules and parental controls for a provider, looked up by the name
+    /// `get_providers` returns
+    pub fn ignore_matcher(
+        &self,
+        ignore: &IgnoreConfig,
+        provider_name: &str,
+    ) -> Result<IgnoreMatcher> {
+        let provider = self
+            .providers
+            .iter()
+            .find(|p| {
+                p.name
+                    .clone()
+                    .unwrap_or_else(|| format!("{}@{}", p.username, p.url))
+                    == provider_name
+            })
+            .ok_or_else(|| anyhow::anyhow!("Provider '{}' not found", provider_name))?;
+        Ok(ignore
+            .matcher(&provider_api(provider)?.provider_hash)
+            .with_parental(self.parental.clone()))
+    }
+
     /// Prompt user to select a provider
     fn prompt_provider_selection(&self) -> Result<&ProviderConfig> {
         let provider_names: Vec<String> = self
@@ -188,4 +295,13 @@ impl ContentType {
             _ => anyhow::bail!("Invalid type: {}. Use 'live', 'movie', or 'series'", s),
         }
     }
+
+    /// The provider's stream type for this content
+    pub fn stream_type(self) -> &'static str {
+        match self {
+            Self::Live => "live",
+            Self::Movie => "movie",
+            Self::Series => "series",
+        }
+    }
 }
@@ -0,0 +1,129 @@
+use anyhow::Result;
+use inquire::Password;
+use iptv::Config;
+use iptv::config::ParentalSettings;
+use iptv::parental::{self, ParentalFilter};
+use std::path::PathBuf;
+
+pub enum ParentalCommand {
+    /// Show whether parental controls are on and which keywords they use
+    Status,
+    /// Turn parental controls on, setting a PIN first if there is none
+    Enable,
+    /// Turn parental controls off (needs the PIN)
+    Disable,
+    /// Change the PIN (needs the current PIN)
+    SetPin,
+    /// Add or remove category keywords (needs the PIN)
+    Keywords {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+}
+
+impl ParentalCommand {
+    pub fn execute(self, mut config: Config, config_path: PathBuf) -> Result<()> {
+        let mut parental = config.settings.parental.clone();
+        let settings = &mut parental;
+        match self {
+            Self::Status => {
+                println!(
+                    "Parental controls: {}",
+                    if settings.enabled {
+                        "enabled"
+                    } else {
+                        "disabled"
+                    }
+                );
+                println!(
+                    "PIN: {}",
+                    if settings.pin_hash.is_some() {
+                        "set"
+                    } else {
+                        "not set"
+                    }
+                );
+                println!("Category keywords: {}", settings.keywords.join(", "));
+                return Ok(());
+            }
+            Self::Enable => {
+                if settings.enabled {
+                    println!("Parental controls are already enabled");
+                    return Ok(());
+                }
+                if settings.pin_hash.is_none() {
+                    settings.pin_hash = Some(parental::hash_pin(&prompt_new_pin()?));
+                }
+                settings.enabled = true;
+                println!("Parental controls enabled");
+            }
+            Self::Disable => {
+                check_pin(settings)?;
+                settings.enabled = false;
+                println!("Parental controls disabled");
+            }
+            Self::SetPin => {
+                check_pin(settings)?;
+                settings.pin_hash = Some(parental::hash_pin(&prompt_new_pin()?));
+                println!("Parental PIN changed");
+            }
+            Self::Keywords { add, remove } => {
+                if add.is_empty() && remove.is_empty() {
+                    anyhow::bail!("Give keywords to --add or --remove");
+                }
+                check_pin(settings)?;
+                for keyword in remove {
+                    let keyword = keyword.trim().to_lowercase();
+                    settings.keywords.retain(|k| k.to_lowercase() != keyword);
+                }
+                for keyword in add {
+                    let keyword = keyword.trim().to_lowercase();
+                    if !keyword.is_empty() && !settings.keywords.contains(&keyword) {
+                        settings.keywords.push(keyword);
+                    }
+                }
+                println!("Category keywords: {}", settings.keywords.join(", "));
+            }
+        }
+
+        // Only the parental settings are written back, over the config as it is on disk now
+        config.update(&config_path, |config| {
+            config.settings.parental = parental;
+            Ok(())
+        })
+    }
+}
+
+/// The parental filter for a command run, prompting for the PIN when `unlock` is set
+pub fn session_filter(settings: &ParentalSettings, unlock: bool) -> Result<Option<ParentalFilter>> {
+    if unlock && settings.enabled {
+        check_pin(settings)?;
+    }
+    Ok(ParentalFilter::from_settings(settings, unlock))
+}
+
+fn check_pin(settings: &ParentalSettings) -> Result<()> {
+    if settings.pin_hash.is_none() {
+        return Ok(());
+    }
+    let pin = Password::new("Parental PIN:")
+        .without_confirmation()
+        .prompt()?;
+    if !parental::verify_pin(settings, &pin) {
+        anyhow::bail!("Wrong PIN");
+    }
+    Ok(())
+}
+
+fn prompt_new_pin() -> Result<String> {
+    let pin = Password::new("New parental PIN:")
+        .with_custom_confirmation_message("Repeat PIN:")
+        .prompt()?;
+    if pin.trim().len() < parental::MIN_PIN_LENGTH {
+        anyhow::bail!(
+            "The PIN needs at least {} characters",
+            parental::MIN_PIN_LENGTH
+        );
+    }
+    Ok(pin.trim().to_string())
+}
@@ -0,0 +1,446 @@
+use super::{CommandContext, ContentType, categories_by_id};
+use anyhow::Result;
+use iptv::Player;
+use iptv::favourites::{FavouritesManager, name_similarity};
+use iptv::ignore::{IgnoreConfig, IgnoreMatcher};
+use iptv::parental;
+use iptv::recents::RecentsManager;
+use iptv::xtream::{FavouriteStream, XTreamAPI};
+
+/// Names at least this similar to the query are accepted when not matching exactly
+const MIN_FUZZY_SIMILARITY: f64 = 0.5;
+
+/// Score of a name that contains the query, so "bbc one" finds "UK: BBC One FHD"
+const CONTAINS_SCORE: f64 = 0.75;
+
+/// What to play
+pub enum PlayTarget {
+    /// A channel, movie or series by its provider ID
+    Id { id: u32, content_type: ContentType },
+    /// A channel, movie or series by name, in every content type unless one is given
+    Name {
+        name: String,
+        content_type: Option<ContentType>,
+        exact: bool,
+    },
+    /// A favourite by name
+    Favourite { name: String, exact: bool },
+}
+
+impl PlayTarget {
+    pub(super) fn not_found_message(&self) -> String {
+        match self {
+            Self::Id { id, content_type } => {
+                format!("No {} with ID {}", content_type.stream_type(), id)
+            }
+            Self::Name { name, .. } => format!("Nothing found matching '{}'", name),
+            Self::Favourite { name, .. } => format!("No favourite matching '{}'", name),
+        }
+    }
+}
+
+/// Season and episode numbers, from "S02E05" or "2x05"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpisodeRef {
+    pub season: u32,
+    pub episode: u32,
+}
+
+impl EpisodeRef {
+    pub fn from_str(s: &str) -> Result<Self> {
+        let lower = s.trim().to_lowercase();
+        let parsed = if let Some(rest) = lower.strip_prefix('s') {
+            rest.split_once('e')
+        } else {
+            lower.split_once('x')
+        }
+        .and_then(|(season, episode)| Some((season.parse().ok()?, episode.parse().ok()?)));
+
+        match parsed {
+            Some((season, episode)) => Ok(Self { season, episode }),
+            None => anyhow::bail!("Invalid episode: {}. Use e.g. 'S02E05' or '2x05'", s),
+        }
+    }
+
+    /// Split "S02E05 of Some Series" (optionally starting with "episode") into the
+    /// episode and the series name
+    pub fn split_phrase(phrase: &str) -> Option<(Self, String)> {
+        let lower = phrase.to_lowercase();
+        let at = lower.find(" of ")?;
+        let episode = lower[..at].trim();
+        let episode = episode.strip_prefix("episode").unwrap_or(episode);
+        let series = phrase[at + " of ".len()..].trim();
+        if series.is_empty() {
+            return None;
+        }
+        Some((Self::from_str(episode).ok()?, series.to_string()))
+    }
+}
+
+impl std::fmt::Display for EpisodeRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "S{:02}E{:02}", self.season, self.episode)
+    }
+}
+
+/// Container for live streams
+#[derive(Debug, Clone, Copy)]
+pub enum StreamFormat {
+    Ts,
+    Hls,
+}
+
+impl StreamFormat {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "ts" | "mpegts" => Ok(Self::Ts),
+            "hls" | "m3u8" => Ok(Self::Hls),
+            _ => anyhow::bail!("Invalid stream format: {}. Use 'ts' or 'hls'", s),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Ts => "ts",
+            Self::Hls => "m3u8",
+        }
+    }
+}
+
+/// How the player is started
+#[derive(Debug, Clone, Copy)]
+pub enum PlaybackMode {
+    /// In the background, replacing what a previous detached player is showing
+    Detached,
+    /// In the foreground, returning when the player exits
+    Blocking,
+    /// With the player's output in this terminal
+    Terminal,
+    /// In a new, independent player window
+    Independent,
+}
+
+impl PlaybackMode {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "detached" => Ok(Self::Detached),
+            "blocking" => Ok(Self::Blocking),
+            "terminal" => Ok(Self::Terminal),
+            "independent" => Ok(Self::Independent),
+            _ => anyhow::bail!(
+                "Invalid mode: {}. Use 'detached', 'blocking', 'terminal' or 'independent'",
+                s
+            ),
+        }
+    }
+}
+
+pub struct PlayCommand {
+    pub target: PlayTarget,
+    /// Episode to play when the target is a series; the next unwatched one if None
+    pub episode: Option<EpisodeRef>,
+    /// Container for live streams; the provider's default (HLS) if None
+    pub format: Option<StreamFormat>,
+    pub mode: PlaybackMode,
+    /// Print the stream URL instead of playing it
+    pub print_url: bool,
+}
+
+/// A matching item and how well its name matched
+struct Candidate {
+    provider: usize,
+    item: FavouriteStream,
+    score: f64,
+}
+
+impl PlayCommand {
+    pub async fn execute(self, context: CommandContext, player: Player) -> Result<()> {
+        let mut providers = context.get_providers_for_search().await?;
+        let favourites_manager = FavouritesManager::new()?;
+
+        let (provider, item) = find_target(
+            &context,
+            &mut providers,
+            &favourites_manager,
+            &self.target,
+            &[ContentType::Live, ContentType::Movie, ContentType::Series],
+        )
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("{}", self.target.not_found_message()))?;
+        let (api, provider_name) = &mut providers[provider];
+
+        let item = match item.stream_type.as_str() {
+            "series" => self.pick_episode(api, &favourites_manager, item).await?,
+            _ if self.episode.is_some() => {
+                anyhow::bail!("'{}' is not a series, so has no episodes", item.name)
+            }
+            _ => item,
+        };
+
+        let extension = match item.stream_type.as_str() {
+            "live" => self.format.map(StreamFormat::extension),
+            "movie" => item.container_extension.as_deref(),
+            _ => None,
+        };
+        let url = api.get_favourite_url(&item, extension);
+
+        if self.print_url {
+            println!("{}", url);
+            return Ok(());
+        }
+
+        eprintln!("Playing {} ({})", item.name, provider_name);
+        match self.mode {
+            PlaybackMode::Detached => player.play_detached(&url).await?,
+            PlaybackMode::Blocking => player.play_blocking(&url).await?,
+            PlaybackMode::Terminal => player.play_in_terminal(&url).await?,
+            PlaybackMode::Independent => player.play_disassociated(&url).await?,
+        }
+
+        if let Some(series_id) = item.series_id {
+            favourites_manager.mark_episode_watched(
+                &api.provider_hash,
+                series_id,
+                &item.stream_id.to_string(),
+            )?;
+        }
+        if let Err(e) = RecentsManager::new().and_then(|recents| recents.record(item)) {
+            tracing::warn!("Failed to record recently played item: {}", e);
+        }
+        Ok(())
+    }
+
+    /// The requested episode of a series, or the next one not yet watched from its favourite
+    async fn pick_episode(
+        &self,
+        api: &mut XTreamAPI,
+        favourites_manager: &FavouritesManager,
+        series: FavouriteStream,
+    ) -> Result<FavouriteStream> {
+        let info = api.get_series_info(series.stream_id).await?;
+        let episodes = info.sorted_episodes();
+
+        let episode = match self.episode {
+            Some(wanted) => episodes
+                .iter()
+                .find(|e| e.season == wanted.season && e.episode_num == wanted.episode)
+                .copied()
+                .ok_or_else(|| anyhow::anyhow!("No episode {} in {}", wanted, series.name))?,
+            None => {
+                let watched = favourites_manager
+                    .index(&api.provider_hash)?
+                    .get("series", series.stream_id)
+                    .map(|f| f.watched_episodes.clone())
+                    .unwrap_or_default();
+                info.next_unwatched(&watched)
+                    .or(episodes.first().copied())
+                    .ok_or_else(|| anyhow::anyhow!("No episodes found for {}", series.name))?
+            }
+        };
+
+        FavouriteStream::from_episode(
+            &api.provider_hash,
+            series.stream_id,
+            &series.name,
+            series.category_id.clone(),
+            episode,
+        )
+        .ok_or_else(|| anyhow::anyhow!("Unsupported episode ID: {}", episode.id))
+    }
+}
+
+/// The best match for a target over every provider, skipping hidden items
+///
+/// Names without a content type are looked up in `content_types`. Returns the index of the
+/// provider the match was found in.
+pub(super) async fn find_target(
+    context: &CommandContext,
+    providers: &mut [(XTreamAPI, String)],
+    favourites_manager: &FavouritesManager,
+    target: &PlayTarget,
+    content_types: &[ContentType],
+) -> Result<Option<(usize, FavouriteStream)>> {
+    let ignore = IgnoreConfig::load()?;
+
+    // Best match over every provider; earlier providers and content types win ties
+    let mut best: Option<Candidate> = None;
+    for (index, (api, provider_name)) in providers.iter_mut().enumerate() {
+        api.disable_progress();
+        let mut matcher = context.ignore_matcher(&ignore, provider_name)?;
+        matcher
+            .load_cached_categories(&api.cache_manager, &api.provider_hash)
+            .await;
+
+        let candidate = match target {
+            PlayTarget::Favourite { name, exact } => {
+                let favourites = favourites_manager.get_favourites(&api.provider_hash)?;
+                favourites
+                    .into_iter()
+                    .filter(|f| !matcher.hides_favourite(f))
+                    .filter_map(|f| {
+                        let score = match_score(name, &f.name, *exact)?;
+                        Some(Candidate {
+                            provider: index,
+                            item: f,
+                            score,
+                        })
+                    })
+                    .fold(None, pick_better)
+            }
+            target => find_stream(api, &matcher, target, content_types, index).await?,
+        };
+
+        if let Some(candidate) = candidate {
+            let exact_match = candidate.score >= 1.0;
+            best = pick_better(best, candidate);
+            if exact_match {
+                break;
+            }
+        }
+    }
+
+    Ok(best.map(|candidate| (candidate.provider, candidate.item)))
+}
+
+/// Look a channel, movie or series up in a provider's lists, skipping hidden ones
+async fn find_stream(
+    api: &mut XTreamAPI,
+    matcher: &IgnoreMatcher,
+    target: &PlayTarget,
+    any_type: &[ContentType],
+    provider: usize,
+) -> Result<Option<Candidate>> {
+    let (query, content_types, exact) = match target {
+        PlayTarget::Id { content_type, .. } => (None, vec![*content_type], true),
+        PlayTarget::Name {
+            name,
+            content_type: Some(content_type),
+            exact,
+        } => (Some(name.as_str()), vec![*content_type], *exact),
+        PlayTarget::Name {
+            name,
+            content_type: None,
+            exact,
+        } => (Some(name.as_str()), any_type.to_vec(), *exact),
+        PlayTarget::Favourite { .. } => return Ok(None),
+    };
+    let wanted_id = match target {
+        PlayTarget::Id { id, .. } => Some(*id),
+        _ => None,
+    };
+
+    let searching_all = content_types.len() > 1;
+    let mut best = None;
+    for content_type in content_types {
+        let categories = categories_by_id(api, content_type).await;
+        let stream_type = content_type.stream_type();
+
+        let items = match list_items(api, content_type).await {
+            Ok(items) => items,
+            // When looking in every content type, one failing to load doesn't stop the rest
+            Err(e) if searching_all => {
+                tracing::warn!("Failed to load {} list: {}", stream_type, e);
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+
+        for (item, is_adult) in items {
+            let score = match (wanted_id, query) {
+                (Some(id), _) => (item.stream_id == id).then_some(1.0),
+                (None, Some(query)) => match_score(query, &item.name, exact),
+                (None, None) => None,
+            };
+            let Some(score) = score else {
+                continue;
+            };
+            let category = item.category_id.as_ref().and_then(|id| categories.get(id));
+            if matcher.hides_stream(stream_type, item.stream_id, &item.name, category, is_adult) {
+                continue;
+            }
+            best = pick_better(
+                best,
+                Candidate {
+                    provider,
+                    item,
+                    score,
+                },
+            );
+        }
+    }
+    Ok(best)
+}
+
+/// How well a name matches the query: 1.0 if equal ignoring case, otherwise (unless
+/// `exact`) its similarity if high enough
+pub(super) fn match_score(query: &str, name: &str, exact: bool) -> Option<f64> {
+    if name.trim().eq_ignore_ascii_case(query.trim()) {
+        return Some(1.0);
+    }
+    if exact {
+        return None;
+    }
+
+    let mut score = name_similarity(query, name).min(0.99);
+    if name.to_lowercase().contains(&query.trim().to_lowercase()) {
+        score = score.max(CONTAINS_SCORE);
+    }
+    (score >= MIN_FUZZY_SIMILARITY).then_some(score)
+}
+
+/// A provider's series or streams in the shape favourites and recents use, with their
+/// adult flags
+async fn list_items(
+    api: &mut XTreamAPI,
+    content_type: ContentType,
+) -> Result<Vec<(FavouriteStream, bool)>> {
+    let stream_type = content_type.stream_type();
+    let items = match content_type {
+        ContentType::Series => api
+            .get_series(None)
+            .await?
+            .into_iter()
+            .map(|s| {
+                let is_adult = parental::is_adult(s.is_adult.as_ref());
+                let item = FavouriteStream::new(
+                    &api.provider_hash,
+                    s.series_id,
+                    s.name,
+                    stream_type,
+                    s.category_id,
+                );
+                (item, is_adult)
+            })
+            .collect(),
+        _ => {
+            let streams = if matches!(content_type, ContentType::Live) {
+                api.get_live_streams(None).await?
+            } else {
+                api.get_vod_streams(None).await?
+            };
+            streams
+                .into_iter()
+                .map(|s| {
+                    let is_adult = parental::is_adult(s.is_adult.as_ref());
+                    let mut item = FavouriteStream::new(
+                        &api.provider_hash,
+                        s.stream_id,
+                        s.name,
+                        stream_type,
+                        s.category_id,
+                    );
+                    item.container_extension = s.container_extension;
+                    (item, is_adult)
+                })
+                .collect()
+        }
+    };
+    Ok(items)
+}
+
+fn pick_better(best: Option<Candidate>, candidate: Candidate) -> Option<Candidate> {
+    match best {
+        Some(best) if best.score >= candidate.score => Some(best),
+        _ => Some(candidate),
+    }
+}
@@ -0,0 +1,286 @@
+use super::{OutputFormat, find_provider};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use inquire::{Confirm, Password, Text};
+use iptv::Config;
+use iptv::account::{AccountAlert, AccountStatus};
+use iptv::config::ProviderConfig;
+use iptv::setup;
+use iptv::xtream::ServerInfo;
+use serde::Serialize;
+use std::path::PathBuf;
+
+pub enum ProviderCommand {
+    /// List the configured providers, without their passwords
+    List { format: OutputFormat },
+    /// Add a provider, prompting for anything not given, after testing the connection
+    Add {
+        name: Option<String>,
+        url: Option<String>,
+        username: Option<String>,
+        password: Option<String>,
+        /// Save without testing the connection
+        no_test: bool,
+        format: OutputFormat,
+    },
+    /// Remove a provider from the config
+    Remove { name: String, yes: bool },
+    /// Rename a provider, keeping its favourites, cache and ignore rules
+    Rename { name: String, new_name: String },
+    /// Check the URL and credentials of one or all providers
+    Test {
+        name: Option<String>,
+        format: OutputFormat,
+    },
+}
+
+/// A configured provider as listed by `config provider list`
+#[derive(Debug, Serialize)]
+struct ListedProvider {
+    name: Option<String>,
+    id: Option<String>,
+    url: String,
+    username: String,
+}
+
+/// Outcome of testing a provider
+#[derive(Debug, Serialize)]
+struct ProviderTest {
+    provider: String,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    account: Option<AccountStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    server: Option<ServerInfo>,
+    alerts: Vec<AccountAlert>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// Account and server details for text output
+    #[serde(skip)]
+    details: Vec<String>,
+}
+
+impl ProviderCommand {
+    pub async fn execute(self, mut config: Config, config_path: PathBuf) -> Result<()> {
+        // A missing config is loaded as the example one; don't keep its placeholder provider
+        if setup::should_run_setup(&config_path, &config) {
+            config.providers.clear();
+        }
+
+        match self {
+            Self::List { format } => {
+                let providers: Vec<ListedProvider> = config
+                    .providers
+                    .iter()
+                    .map(|p| ListedProvider {
+                        name: p.name.clone(),
+                        id: p.id.clone(),
+                        url: p.url.clone(),
+                        username: p.username.clone(),
+                    })
+                    .collect();
+                match format {
+                    OutputFormat::Json => {
+                        println!("{}", serde_json::to_string_pretty(&providers)?)
+                    }
+                    OutputFormat::Text => {
+                        if providers.is_empty() {
+                            println!("No providers configured");
+                        }
+                        for p in &providers {
+                            println!(
+                                "{}\n    URL: {}\n    Username: {}",
+                                p.name.as_deref().unwrap_or("(unnamed)"),
+                                p.url,
+                                p.username
+                            );
+                            if let Some(id) = &p.id {
+                                println!("    ID: {}", id);
+                            }
+                        }
+                    }
+                    OutputFormat::M3u | OutputFormat::Tsv => {
+                        anyhow::bail!("Providers can only be listed as 'text' or 'json'")
+                    }
+                }
+                Ok(())
+            }
+            Self::Add {
+                name,
+                url,
+                username,
+                password,
+                no_test,
+                format,
+            } => {
+                let name = match name {
+                    Some(name) => Some(name),
+                    None => Text::new("Provider name (optional):").prompt_skippable()?,
+                }
+                .map(|n| n.trim().to_string())
+                .filter(|n| !n.is_empty());
+                let url = match url {
+                    Some(url) => url,
+                    None => Text::new("Server URL or link:")
+                        .with_help_message(
+                            "A get.php or player_api.php link gives the credentials too",
+                        )
+                        .prompt()?,
+                };
+                // Credentials given as options win over the ones in a pasted link
+                let link = setup::parse_provider_link(url.trim())?;
+                let provider = ProviderConfig {
+                    id: None,
+                    name,
+                    username: match username.or(link.username) {
+                        Some(username) => username,
+                        None => Text::new("Username:").prompt()?,
+                    }
+                    .trim()
+                    .to_string(),
+                    password: match password.or(link.password) {
+                        Some(password) => password,
+                        None => Password::new("Password:").without_confirmation().prompt()?,
+                    },
+                    url: link.server,
+                };
+                setup::validate_provider(&provider, &config.providers)?;
+
+                if !no_test {
+                    let test = test_provider(&provider, config.settings.expiry_warning_days).await;
+                    match format {
+                        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&test)?),
+                        _ => print_test(&test),
+                    }
+                    if !test.ok {
+                        anyhow::bail!("Provider not saved; use --no-test to save it anyway");
+                    }
+                }
+
+                let label = provider.label();
+                config.update(&config_path, |config| {
+                    if setup::should_run_setup(&config_path, config) {
+                        config.providers.clear();
+                    }
+                    setup::validate_provider(&provider, &config.providers)?;
+                    config.providers.push(provider);
+                    Ok(())
+                })?;
+                eprintln!("Added provider {}", label);
+                Ok(())
+            }
+            Self::Remove { name, yes } => {
+                let index = provider_index(&config, &name)?;
+                let label = config.providers[index].label();
+                if !yes
+                    && !Confirm::new(&format!("Remove provider {}?", label))
+                        .with_default(false)
+                        .prompt()?
+                {
+                    println!("Provider not removed");
+                    return Ok(());
+                }
+                config.update(&config_path, |config| {
+                    let index = provider_index(config, &name)?;
+                    config.providers.remove(index);
+                    Ok(())
+                })?;
+                println!(
+                    "Removed provider {}; its favourites and cache are kept in case it's added back",
+                    label
+                );
+                Ok(())
+            }
+            Self::Rename { name, new_name } => {
+                let (previous, provider) = config.update(&config_path, |config| {
+                    let index = provider_index(config, &name)?;
+                    let previous = config.providers.remove(index);
+                    let mut provider = previous.clone();
+                    provider.name = Some(new_name.trim().to_string());
+                    provider.keep_identity(&previous);
+                    setup::validate_provider(&provider, &config.providers)?;
+                    config.providers.insert(index, provider.clone());
+                    Ok((previous, provider))
+                })?;
+
+                println!(
+                    "Renamed provider {} to {}",
+                    previous.label(),
+                    provider.label()
+                );
+                Ok(())
+            }
+            Self::Test { name, format } => {
+                let providers = match &name {
+                    Some(name) => vec![config.providers[provider_index(&config, name)?].clone()],
+                    None => config.providers.clone(),
+                };
+                if providers.is_empty() {
+                    anyhow::bail!("No providers configured");
+                }
+
+                let mut tests = Vec::new();
+                for provider in &providers {
+                    tests.push(test_provider(provider, config.settings.expiry_warning_days).await);
+                }
+                match format {
+                    OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&tests)?),
+                    _ => tests.iter().for_each(print_test),
+                }
+
+                let failed = tests.iter().filter(|t| !t.ok).count();
+                if failed > 0 {
+                    anyhow::bail!("{} of {} providers failed", failed, tests.len());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn provider_index(config: &Config, name: &str) -> Result<usize> {
+    let provider = find_provider(&config.providers, Some(name), name)
+        .with_context(|| format!("Provider '{}' not found", name))?;
+    Ok(config
+        .providers
+        .iter()
+        .position(|p| std::ptr::eq(p, provider))
+        .unwrap_or_default())
+}
+
+async fn test_provider(provider: &ProviderConfig, warning_days: u32) -> ProviderTest {
+    let now = Utc::now();
+    match setup::check_provider(provider).await {
+        Ok(response) => {
+            let account = AccountStatus::from_user_info(&response.user_info, now);
+            ProviderTest {
+                provider: provider.label(),
+                ok: true,
+                alerts: account.alerts(warning_days, now),
+                account: Some(account),
+                details: setup::account_lines(&response, warning_days),
+                server: Some(response.server_info),
+                error: None,
+            }
+        }
+        Err(e) => ProviderTest {
+            provider: provider.label(),
+            ok: false,
+            account: None,
+            server: None,
+            alerts: Vec::new(),
+            error: Some(format!("{:#}", e)),
+            details: Vec::new(),
+        },
+    }
+}
+
+fn print_test(test: &ProviderTest) {
+    match &test.error {
+        Some(error) => println!("✗ {}: {}", test.provider, error),
+        None => println!("✓ {}: connected", test.provider),
+    }
+    for line in &test.details {
+        println!("    {}", line);
+    }
+}
@@ -1,17 +1,168 @@
-use super::{CommandContext, ContentType, OutputFormat};
-use anyhow::Result;
+use super::list::matching_categories;
+use super::{CommandContext, ContentType, OutputFormat, categories_by_id};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use futures_util::future::join_all;
+use iptv::favourites::title_key;
+use iptv::ignore::{IgnoreConfig, IgnoreMatcher};
+use iptv::parental;
+use iptv::xtream::{Category, VodInfoResponse, XTreamAPI};
+use regex::{Regex, RegexBuilder};
+use serde::Serialize;
 use serde_json::json;
+use std::collections::HashMap;
 
 pub struct SearchCommand {
     pub query: String,
     pub content_type: Option<ContentType>,
+    /// Only match items in the category with this name or ID
+    pub category: Option<String>,
+    /// Only match items added at or after this time
+    pub added_since: Option<DateTime<Utc>>,
+    /// Only match items rated at least this, out of 10
+    pub min_rating: Option<f64>,
+    /// Only match live channels with a catch-up archive
+    pub archive: bool,
+    /// Treat the query as a regular expression instead of a substring
+    pub regex: bool,
+    /// Fields matched besides the name
+    pub fields: Vec<SearchField>,
+    /// Show each title once with the providers that carry it
+    pub group_by_title: bool,
     pub format: OutputFormat,
 }
 
+/// Details that can be searched besides the name; movies only have them once their info
+/// has been cached, series have them in the series list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchField {
+    Plot,
+    Cast,
+    Genre,
+}
+
+impl SearchField {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "plot" => Ok(Self::Plot),
+            "cast" => Ok(Self::Cast),
+            "genre" => Ok(Self::Genre),
+            _ => anyhow::bail!("Invalid field: {}. Use 'plot', 'cast' or 'genre'", s),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Plot => "plot",
+            Self::Cast => "cast",
+            Self::Genre => "genre",
+        }
+    }
+}
+
+/// The search query, matched case-insensitively
+enum Query {
+    Substring(String),
+    Regex(Regex),
+}
+
+impl Query {
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            Self::Substring(query) => text.to_lowercase().contains(query),
+            Self::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+/// One search result
+///
+/// This is the JSON schema of `cli search`; the optional fields are left out when the
+/// provider doesn't give them.
+#[derive(Debug, Clone, Serialize)]
+struct SearchResult {
+    id: u32,
+    name: String,
+    #[serde(rename = "type")]
+    content_type: &'static str,
+    provider: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    category_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    added: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rating: Option<f64>,
+    /// Days of catch-up archive for live channels that have one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    archive_days: Option<u32>,
+    /// The field the query matched, when it wasn't the name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    matched: Option<&'static str>,
+}
+
+/// Results for the same title from one or more providers, for --group-by-title
+#[derive(Debug, Serialize)]
+struct TitleGroup {
+    title: String,
+    #[serde(rename = "type")]
+    content_type: &'static str,
+    providers: Vec<String>,
+    results: Vec<SearchResult>,
+}
+
+/// What one provider's candidates of one content type are checked against
+struct CheckContext<'a> {
+    matcher: &'a IgnoreMatcher,
+    query: &'a Query,
+    content_type: ContentType,
+    categories: &'a HashMap<String, Category>,
+    /// IDs of the categories matching --category, if given
+    wanted: Option<&'a [String]>,
+    /// Plot, cast and genre of the movies whose info is cached, by stream ID
+    movie_details: &'a HashMap<u32, [Option<String>; 3]>,
+}
+
+/// A channel, movie or series in the shape the filters work on
+struct Candidate {
+    id: u32,
+    name: String,
+    category_id: Option<String>,
+    added: Option<DateTime<Utc>>,
+    rating: Option<f64>,
+    /// Some for live channels with a catch-up archive; 0 when the length isn't given
+    archive_days: Option<u32>,
+    is_adult: bool,
+    /// Plot, cast and genre, where known without fetching anything
+    details: Option<[Option<String>; 3]>,
+}
+
 impl SearchCommand {
     pub async fn execute(self, context: CommandContext) -> Result<()> {
+        if self.archive
+            && matches!(
+                self.content_type,
+                Some(ContentType::Movie | ContentType::Series)
+            )
+        {
+            anyhow::bail!("Only live channels have a catch-up archive");
+        }
+        if self.group_by_title && matches!(self.format, OutputFormat::M3u) {
+            anyhow::bail!("Grouped results can only be shown as 'text', 'json' or 'tsv'");
+        }
+        let query = if self.regex {
+            Query::Regex(
+                RegexBuilder::new(&self.query)
+                    .case_insensitive(true)
+                    .build()
+                    .with_context(|| format!("Invalid regular expression: {}", self.query))?,
+            )
+        } else {
+            Query::Substring(self.query.to_lowercase())
+        };
+
         let providers = context.get_providers_for_search().await?;
-        let query_lower = self.query.to_lowercase();
 
         // Check if we're searching multiple providers
         let is_multi_provider = providers.len() > 1;
@@ -19,81 +170,53 @@ impl SearchCommand {
             eprintln!("Searching across {} providers...", providers.len());
         }
 
-        let mut all_results = Vec::new();
-
-        for (mut api, provider_name) in providers {
+        let ignore = IgnoreConfig::load()?;
+        let mut searches = Vec::new();
+        for (api, provider_name) in providers {
             if is_multi_provider {
                 eprintln!("  Searching in {}...", provider_name);
             } else {
                 eprintln!("Searching in {}...", provider_name);
             }
+            let matcher = context.ignore_matcher(&ignore, &provider_name)?;
+            searches.push(self.search_provider(api, provider_name, matcher, &query));
+        }
+        let mut category_found = false;
+        let mut provider_results = Vec::new();
+        for (provider_name, results, found) in join_all(searches).await {
+            category_found |= found;
+            provider_results.push((provider_name, results));
+        }
 
-            let mut provider_results = Vec::new();
-
-            // Search based on content type
-            let search_types = if let Some(ct) = self.content_type {
-                vec![ct]
-            } else {
-                vec![ContentType::Live, ContentType::Movie, ContentType::Series]
-            };
-
-            for content_type in search_types {
-                match content_type {
-                    ContentType::Live => {
-                        if let Ok(streams) = api.get_live_streams(None).await {
-                            for stream in streams {
-                                if stream.name.to_lowercase().contains(&query_lower) {
-                                    provider_results.push(json!({
-                                        "id": stream.stream_id,
-                                        "name": stream.name,
-                                        "type": "live",
-                                        "provider": &provider_name,
-                                    }));
-                                }
-                            }
-                        }
-                    }
-                    ContentType::Movie => {
-                        if let Ok(streams) = api.get_vod_streams(None).await {
-                            for stream in streams {
-                                if stream.name.to_lowercase().contains(&query_lower) {
-                                    provider_results.push(json!({
-                                        "id": stream.stream_id,
-                                        "name": stream.name,
-                                        "type": "movie",
-                                        "provider": &provider_name,
-                                    }));
-                                }
-                            }
-                        }
-                    }
-                    ContentType::Series => {
-                        if let Ok(series) = api.get_series(None).await {
-                            for s in series {
-                                if s.name.to_lowercase().contains(&query_lower) {
-                                    provider_results.push(json!({
-                                        "id": s.series_id,
-                                        "name": s.name,
-                                        "type": "series",
-                                        "provider": &provider_name,
-                                    }));
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+        if let Some(category) = &self.category
+            && !category_found
+        {
+            anyhow::bail!("No category matching '{}'", category);
+        }
 
-            if is_multi_provider {
-                all_results.push(json!({
-                    "provider": provider_name,
-                    "results": provider_results,
-                }));
-            } else {
-                all_results.extend(provider_results);
-            }
+        if self.group_by_title {
+            let groups = group_by_title(
+                provider_results
+                    .into_iter()
+                    .flat_map(|(_, results)| results)
+                    .collect(),
+            );
+            return self.print_groups(&groups);
         }
 
+        let all_results: Vec<serde_json::Value> = if is_multi_provider {
+            provider_results
+                .into_iter()
+                .map(|(provider, results)| json!({ "provider": provider, "results": results }))
+                .collect()
+        } else {
+            provider_results
+                .into_iter()
+                .flat_map(|(_, results)| results)
+                .map(|result| json!(result))
+                .collect()
+        };
+
         // Output results in requested format
         match self.format {
             OutputFormat::Json => {
@@ -121,6 +244,16 @@ impl SearchCommand {
                     }
                 }
             }
+            OutputFormat::Tsv => {
+                println!("provider\ttype\tid\tname");
+                for result in all_results {
+                    match result.get("results").and_then(|r| r.as_array()) {
+                        // Multi-provider format
+                        Some(results) => results.iter().for_each(Self::print_tsv_result),
+                        None => Self::print_tsv_result(&result),
+                    }
+                }
+            }
             OutputFormat::M3u => {
                 println!("#EXTM3U");
                 for result in all_results {
@@ -144,21 +277,241 @@ impl SearchCommand {
         Ok(())
     }
 
+    /// Search one provider, returning its name, its results and whether it has the
+    /// wanted category
+    async fn search_provider(
+        &self,
+        mut api: XTreamAPI,
+        provider_name: String,
+        mut matcher: IgnoreMatcher,
+        query: &Query,
+    ) -> (String, Vec<SearchResult>, bool) {
+        // Providers are searched at once, so their progress output would interleave
+        api.disable_progress();
+        matcher
+            .load_cached_categories(&api.cache_manager, &api.provider_hash)
+            .await;
+
+        // Search based on content type; only channels have an archive
+        let search_types = match (self.content_type, self.archive) {
+            (Some(ct), _) => vec![ct],
+            (None, true) => vec![ContentType::Live],
+            (None, false) => vec![ContentType::Live, ContentType::Movie, ContentType::Series],
+        };
+
+        let mut results = Vec::new();
+        let mut category_found = false;
+        let mut movie_details = HashMap::new();
+        for content_type in search_types {
+            let categories = categories_by_id(&mut api, content_type).await;
+            let wanted = match &self.category {
+                Some(category) => {
+                    let wanted = matching_categories(&categories, category);
+                    if wanted.is_empty() {
+                        continue;
+                    }
+                    category_found = true;
+                    Some(wanted)
+                }
+                None => None,
+            };
+
+            let candidates = match candidates(&mut api, content_type).await {
+                Ok(candidates) => candidates,
+                Err(e) => {
+                    eprintln!(
+                        "Warning: Failed to search {} in {}: {:#}",
+                        content_type.stream_type(),
+                        provider_name,
+                        e
+                    );
+                    continue;
+                }
+            };
+            // Movie details are only searched where their info is already cached
+            if matches!(content_type, ContentType::Movie) && !self.fields.is_empty() {
+                movie_details = cached_movie_details(&api).await;
+            }
+            let context = CheckContext {
+                matcher: &matcher,
+                query,
+                content_type,
+                categories: &categories,
+                wanted: wanted.as_deref(),
+                movie_details: &movie_details,
+            };
+            for candidate in candidates {
+                if let Some(result) = self.check(&context, candidate) {
+                    results.push(SearchResult {
+                        provider: provider_name.clone(),
+                        ..result
+                    });
+                }
+            }
+        }
+        (provider_name, results, category_found)
+    }
+
+    /// The result for a candidate if it passes the filters and matches the query
+    fn check(&self, context: &CheckContext, candidate: Candidate) -> Option<SearchResult> {
+        let CheckContext {
+            matcher,
+            query,
+            content_type,
+            categories,
+            wanted,
+            movie_details,
+        } = context;
+        let stream_type = content_type.stream_type();
+        let category = candidate
+            .category_id
+            .as_ref()
+            .and_then(|id| categories.get(id));
+        // Items without a date or rating don't pass filters on them
+        if wanted.is_some_and(|wanted| {
+            candidate
+                .category_id
+                .as_ref()
+                .is_none_or(|id| !wanted.contains(id))
+        }) || self
+            .added_since
+            .is_some_and(|since| candidate.added.is_none_or(|added| added < since))
+            || self
+                .min_rating
+                .is_some_and(|min| candidate.rating.is_none_or(|rating| rating < min))
+            || (self.archive && candidate.archive_days.is_none())
+            || matcher.hides_stream(
+                stream_type,
+                candidate.id,
+                &candidate.name,
+                category,
+                candidate.is_adult,
+            )
+        {
+            return None;
+        }
+
+        let matched = if query.matches(&candidate.name) {
+            None
+        } else {
+            if self.fields.is_empty() {
+                return None;
+            }
+            let details = match candidate.details {
+                Some(details) => details,
+                None if matches!(content_type, ContentType::Movie) => {
+                    movie_details.get(&candidate.id)?.clone()
+                }
+                None => return None,
+            };
+            let field = self.fields.iter().find(|field| {
+                let index = match field {
+                    SearchField::Plot => 0,
+                    SearchField::Cast => 1,
+                    SearchField::Genre => 2,
+                };
+                details[index]
+                    .as_deref()
+                    .is_some_and(|text| query.matches(text))
+            })?;
+            Some(field.name())
+        };
+
+        Some(SearchResult {
+            id: candidate.id,
+            name: candidate.name,
+            content_type: stream_type,
+            provider: String::new(),
+            category: category.map(|c| c.category_name.clone()),
+            category_id: candidate.category_id,
+            added: candidate.added,
+            rating: candidate.rating,
+            archive_days: candidate.archive_days.filter(|days| *days > 0),
+            matched,
+        })
+    }
+
+    fn print_groups(&self, groups: &[TitleGroup]) -> Result<()> {
+        match self.format {
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(groups)?),
+            OutputFormat::Tsv => {
+                println!("title\tprovider\ttype\tid\tname");
+                for group in groups {
+                    for result in &group.results {
+                        println!(
+                            "{}\t{}\t{}\t{}\t{}",
+                            group.title,
+                            result.provider.replace(['\t', '\n'], " "),
+                            result.content_type,
+                            result.id,
+                            result.name.replace(['\t', '\n'], " ")
+                        );
+                    }
+                }
+            }
+            _ => {
+                if groups.is_empty() {
+                    println!("No results found for '{}'", self.query);
+                }
+                for group in groups {
+                    let count = group.providers.len();
+                    println!(
+                        "\n[{}] {} ({} provider{})",
+                        group.content_type,
+                        group.results[0].name,
+                        count,
+                        if count == 1 { "" } else { "s" }
+                    );
+                    for result in &group.results {
+                        println!("    {} ({}) - {}", result.name, result.id, result.provider);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn print_text_result(result: &serde_json::Value) {
         if let Some(obj) = result.as_object() {
             let id = obj["id"].as_u64().unwrap_or(0);
             let name = obj["name"].as_str().unwrap_or("");
             let content_type = obj["type"].as_str().unwrap_or("");
             let provider = obj.get("provider").and_then(|p| p.as_str()).unwrap_or("");
+            let matched = obj
+                .get("matched")
+                .and_then(|m| m.as_str())
+                .map(|m| format!(" [{}]", m))
+                .unwrap_or_default();
 
             if provider.is_empty() {
-                println!("[{}] {} ({})", content_type, name, id);
+                println!("[{}] {} ({}){}", content_type, name, id, matched);
             } else {
-                println!("[{}] {} ({}) - {}", content_type, name, id, provider);
+                println!(
+                    "[{}] {} ({}) - {}{}",
+                    content_type, name, id, provider, matched
+                );
             }
         }
     }
 
+    fn print_tsv_result(result: &serde_json::Value) {
+        let field = |key: &str| {
+            result
+                .get(key)
+                .and_then(|v| v.as_str())
+                .unwrap_or("")
+                .replace(['\t', '\n'], " ")
+        };
+        let id = result.get("id").and_then(|v| v.as_u64()).unwrap_or(0);
+        println!(
+            "{}\t{}\t{}\t{}",
+            field("provider"),
+            field("type"),
+            id,
+            field("name")
+        );
+    }
+
     fn print_m3u_entry(result: &serde_json::Value) {
         if let Some(obj) = result.as_object() {
             let id = obj["id"].as_u64().unwrap_or(0);
@@ -173,3 +526,93 @@ impl SearchCommand {
         }
     }
 }
+
+/// A provider's channels, movies or series
+async fn candidates(api: &mut XTreamAPI, content_type: ContentType) -> Result<Vec<Candidate>> {
+    let text = |value: Option<String>| value.filter(|v| !v.trim().is_empty());
+    if matches!(content_type, ContentType::Series) {
+        return Ok(api
+            .get_series(None)
+            .await?
+            .into_iter()
+            .map(|series| Candidate {
+                id: series.series_id,
+                added: series.added_at(),
+                rating: series.rating_value(),
+                archive_days: None,
+                is_adult: parental::is_adult(series.is_adult.as_ref()),
+                details: Some([text(series.plot), text(series.cast), text(series.genre)]),
+                name: series.name,
+                category_id: series.category_id,
+            })
+            .collect());
+    }
+
+    let streams = if matches!(content_type, ContentType::Live) {
+        api.get_live_streams(None).await?
+    } else {
+        api.get_vod_streams(None).await?
+    };
+    Ok(streams
+        .into_iter()
+        .map(|stream| Candidate {
+            id: stream.stream_id,
+            added: stream.added_at(),
+            rating: stream.rating_value(),
+            archive_days: stream
+                .has_archive()
+                .then(|| stream.archive_days().unwrap_or_default()),
+            is_adult: parental::is_adult(stream.is_adult.as_ref()),
+            details: None,
+            name: stream.name,
+            category_id: stream.category_id,
+        })
+        .collect())
+}
+
+/// Plot, cast and genre of the provider's movies whose info is cached, by stream ID; movie
+/// info isn't fetched for a search
+async fn cached_movie_details(api: &XTreamAPI) -> HashMap<u32, [Option<String>; 3]> {
+    let cached = api
+        .cache_manager
+        .get_cached_with_prefix::<VodInfoResponse>(&api.provider_hash, "vod_info_")
+        .await
+        .unwrap_or_default();
+    cached
+        .into_iter()
+        .filter_map(|(id, vod)| {
+            let info = vod.info;
+            Some((id.parse().ok()?, [info.plot, info.cast, info.genre]))
+        })
+        .collect()
+}
+
+/// Group results for the same channel or film, ordered by how many providers carry it
+fn group_by_title(results: Vec<SearchResult>) -> Vec<TitleGroup> {
+    let mut groups: Vec<TitleGroup> = Vec::new();
+    let mut index: HashMap<(&'static str, String), usize> = HashMap::new();
+    for result in results {
+        let key = (result.content_type, title_key(&result.name));
+        let position = *index.entry(key.clone()).or_insert_with(|| {
+            groups.push(TitleGroup {
+                title: key.1,
+                content_type: result.content_type,
+                providers: Vec::new(),
+                results: Vec::new(),
+            });
+            groups.len() - 1
+        });
+        let group = &mut groups[position];
+        if !group.providers.contains(&result.provider) {
+            group.providers.push(result.provider.clone());
+        }
+        group.results.push(result);
+    }
+    groups.sort_by(|a, b| {
+        b.providers
+            .len()
+            .cmp(&a.providers.len())
+            .then_with(|| a.title.cmp(&b.title))
+    });
+    groups
+}
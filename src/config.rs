@@ -1,6 +1,7 @@
 // SPDX-License-Identifier: MIT
 // SPDX-FileCopyrightText: (C) 2025 Cranky Kernel <crankykernel@proton.me>
 
+use crate::storage;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -12,10 +13,74 @@ pub enum PlayMode {
     MpvInTerminal, // MPV in terminal mode (visible output)
 }
 
+/// Optional columns shown beside the name in stream lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamColumn {
+    Favourite,
+    Number,
+    Added,
+    Rating,
+    Archive,
+}
+
+fn default_stream_columns() -> Vec<StreamColumn> {
+    vec![
+        StreamColumn::Favourite,
+        StreamColumn::Number,
+        StreamColumn::Added,
+        StreamColumn::Rating,
+        StreamColumn::Archive,
+    ]
+}
+
+fn default_adult_keywords() -> Vec<String> {
+    ["adult", "xxx", "18+", "porn"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+/// Parental controls: hide adult-flagged items and categories matching keywords
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParentalSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Salted hash of the PIN that unlocks a session, see `parental::hash_pin`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pin_hash: Option<String>,
+    /// Categories whose names contain any of these as whole words (case-insensitive) are hidden
+    #[serde(default = "default_adult_keywords")]
+    pub keywords: Vec<String>,
+}
+
+impl Default for ParentalSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pin_hash: None,
+            keywords: default_adult_keywords(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
     pub play_mode: PlayMode,
     pub use_ts_for_live: bool,
+    #[serde(default)]
+    pub show_preview: bool,
+    #[serde(default = "default_stream_columns")]
+    pub stream_columns: Vec<StreamColumn>,
+    #[serde(default)]
+    pub parental: ParentalSettings,
+    /// Warn when a provider subscription expires within this many days
+    #[serde(default = "default_expiry_warning_days")]
+    pub expiry_warning_days: u32,
+}
+
+fn default_expiry_warning_days() -> u32 {
+    7
 }
 
 impl Default for Settings {
@@ -23,6 +88,10 @@ impl Default for Settings {
         Self {
             play_mode: PlayMode::Mpv,
             use_ts_for_live: false,
+            show_preview: false,
+            stream_columns: default_stream_columns(),
+            parental: ParentalSettings::default(),
+            expiry_warning_days: default_expiry_warning_days(),
         }
     }
 }
@@ -43,6 +112,36 @@ pub struct ProviderConfig {
     pub password: String,
 }
 
+impl ProviderConfig {
+    /// Name shown for the provider in lists and messages
+    pub fn label(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.url.clone())
+    }
+
+    /// Identifier the provider's cache, favourites and ignore rules are keyed by
+    pub fn identifier(&self) -> String {
+        match reqwest::Url::parse(&self.url) {
+            Ok(url) => {
+                crate::xtream::provider_identifier(&url, self.name.as_deref(), self.id.as_deref())
+            }
+            Err(_) => self
+                .id
+                .clone()
+                .or(self.name.clone())
+                .unwrap_or(self.url.clone()),
+        }
+    }
+
+    /// After editing, pin the identifier the provider had before as its ID if the edit would
+    /// change it, so its favourites, cache and ignore rules stay attached
+    pub fn keep_identity(&mut self, previous: &ProviderConfig) {
+        let identifier = previous.identifier();
+        if self.identifier() != identifier {
+            self.id = Some(identifier);
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -103,12 +202,34 @@ impl Config {
     }
 
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let _lock = storage::lock(path.as_ref())?;
+        self.write(path.as_ref())
+    }
+
+    /// Change the config file under its lock
+    ///
+    /// `self` is replaced by the config on disk first, so changes made by another process
+    /// since this copy was loaded are kept.
+    pub fn update<P: AsRef<Path>, R>(
+        &mut self,
+        path: P,
+        update: impl FnOnce(&mut Self) -> Result<R>,
+    ) -> Result<R> {
+        let path = path.as_ref();
+        let _lock = storage::lock(path)?;
+        if path.exists() {
+            *self = Self::load(path)?;
+        }
+        let result = update(self)?;
+        self.write(path)?;
+        Ok(result)
+    }
+
+    fn write(&self, path: &Path) -> Result<()> {
         let content =
             toml::to_string_pretty(self).with_context(|| "Failed to serialize config to TOML")?;
 
-        fs::write(&path, content)
-            .with_context(|| format!("Failed to write config file: {}", path.as_ref().display()))?;
-
-        Ok(())
+        storage::write_atomic(path, content)
+            .with_context(|| format!("Failed to write config file: {}", path.display()))
     }
 }
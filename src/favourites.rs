@@ -1,22 +1,68 @@
 // SPDX-License-Identifier: MIT
 // SPDX-FileCopyrightText: (C) 2025 Cranky Kernel <crankykernel@proton.me>
 
+use crate::cache::CacheManager;
 use crate::config::Config;
-use crate::xtream::FavouriteStream;
+use crate::storage;
+use crate::xtream::{FavouriteStream, SeriesInfo, Stream};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
+use tokio::sync::watch;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FavouritesData {
     pub favourites: Vec<FavouriteStream>,
 }
 
+/// Name of the group existing favourites are migrated into
+pub const DEFAULT_GROUP: &str = "Favourites";
+
+/// Identifies a favourite across providers
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FavouriteKey {
+    pub provider_hash: String,
+    pub stream_id: u32,
+    pub stream_type: String,
+}
+
+impl FavouriteKey {
+    pub fn of(favourite: &FavouriteStream) -> Self {
+        Self {
+            provider_hash: favourite.provider_hash.clone(),
+            stream_id: favourite.stream_id,
+            stream_type: favourite.stream_type.clone(),
+        }
+    }
+
+    fn matches(&self, favourite: &FavouriteStream) -> bool {
+        self.provider_hash == favourite.provider_hash
+            && self.stream_id == favourite.stream_id
+            && self.stream_type == favourite.stream_type
+    }
+}
+
+/// A named, ordered group of favourites from any provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FavouriteGroup {
+    pub name: String,
+    pub members: Vec<FavouriteKey>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FavouriteGroupsData {
+    pub groups: Vec<FavouriteGroup>,
+}
+
 /// Manages favorites stored in the config directory (not cache)
 #[derive(Debug)]
 pub struct FavouritesManager {
     favourites_dir: PathBuf,
+    groups_path: PathBuf,
 }
 
 impl FavouritesManager {
@@ -34,7 +80,10 @@ impl FavouritesManager {
             })?;
         }
 
-        Ok(Self { favourites_dir })
+        Ok(Self {
+            favourites_dir,
+            groups_path: config_dir.join("favourite_groups.json"),
+        })
     }
 
     /// Get the path to a provider's favourites file
@@ -43,20 +92,53 @@ impl FavouritesManager {
     }
 
     /// Load favourites for a specific provider
+    ///
+    /// A file that fails to parse is treated as empty, so one bad file doesn't break every
+    /// favourites list; it is kept aside as a `.corrupt-*` copy on the next update.
     pub fn get_favourites(&self, provider_hash: &str) -> Result<Vec<FavouriteStream>> {
+        Ok(self.index(provider_hash)?.favourites().to_vec())
+    }
+
+    /// A provider's favourites indexed by stream type and ID
+    ///
+    /// Loaded files are kept in memory for the whole process and only re-read when they
+    /// change on disk, so this is cheap to call for every list that marks favourites.
+    pub fn index(&self, provider_hash: &str) -> Result<FavouritesIndex> {
         let path = self.get_favourites_path(provider_hash);
+        let stamp = FileStamp::of(&path);
 
-        if !path.exists() {
-            return Ok(Vec::new());
+        let mut loaded = LOADED.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(cached) = loaded.get(&path)
+            && cached.stamp == stamp
+        {
+            return Ok(cached.index.clone());
         }
 
-        let content = fs::read_to_string(&path)
-            .with_context(|| format!("Failed to read favourites file: {}", path.display()))?;
+        let favourites = match read_favourites(&path) {
+            Ok(favourites) => favourites,
+            Err(e) if e.is::<serde_json::Error>() => {
+                tracing::warn!("Ignoring corrupt favourites file {}: {}", path.display(), e);
+                Vec::new()
+            }
+            Err(e) => return Err(e),
+        };
 
-        let data: FavouritesData =
-            serde_json::from_str(&content).with_context(|| "Failed to parse favourites JSON")?;
+        // A file that changed since it was last loaded was written by another process
+        let index = FavouritesIndex::new(favourites);
+        let cached = LoadedFavourites {
+            stamp,
+            index: index.clone(),
+        };
+        if loaded.insert(path, cached).is_some() {
+            notify_changed();
+        }
+        Ok(index)
+    }
 
-        Ok(data.favourites)
+    /// Watch for changes to any favourites, made by this process or, once noticed by
+    /// `index`, by another one
+    pub fn subscribe() -> watch::Receiver<u64> {
+        CHANGES.subscribe()
     }
 
     /// Save favourites for a specific provider
@@ -66,31 +148,40 @@ impl FavouritesManager {
         favourites: Vec<FavouriteStream>,
     ) -> Result<()> {
         let path = self.get_favourites_path(provider_hash);
-        let data = FavouritesData { favourites };
+        let _lock = storage::lock(&path)?;
+        write_favourites(&path, favourites)
+    }
 
-        let content = serde_json::to_string_pretty(&data)
-            .with_context(|| "Failed to serialize favourites")?;
+    /// Re-read a provider's favourites under the file lock, change them and save them
+    ///
+    /// Use this rather than `get_favourites` followed by `save_favourites` so changes made
+    /// by another process in between aren't lost.
+    pub fn update_favourites<R>(
+        &self,
+        provider_hash: &str,
+        update: impl FnOnce(&mut Vec<FavouriteStream>) -> Result<R>,
+    ) -> Result<R> {
+        let path = self.get_favourites_path(provider_hash);
+        let _lock = storage::lock(&path)?;
 
-        fs::write(&path, content)
-            .with_context(|| format!("Failed to write favourites file: {}", path.display()))?;
+        let mut favourites = read_favourites_for_update(&path)?;
 
-        Ok(())
+        let result = update(&mut favourites)?;
+        write_favourites(&path, favourites)?;
+        Ok(result)
     }
 
     /// Add a favourite
     pub fn add_favourite(&self, provider_hash: &str, favourite: FavouriteStream) -> Result<()> {
-        let mut favourites = self.get_favourites(provider_hash)?;
-
-        // Check if already exists
-        if !favourites
-            .iter()
-            .any(|f| f.stream_id == favourite.stream_id && f.stream_type == favourite.stream_type)
-        {
-            favourites.push(favourite);
-            self.save_favourites(provider_hash, favourites)?;
-        }
-
-        Ok(())
+        self.update_favourites(provider_hash, |favourites| {
+            // Check if already exists
+            if !favourites.iter().any(|f| {
+                f.stream_id == favourite.stream_id && f.stream_type == favourite.stream_type
+            }) {
+                favourites.push(favourite);
+            }
+            Ok(())
+        })
     }
 
     /// Remove a favourite
@@ -100,10 +191,10 @@ impl FavouritesManager {
         stream_id: u32,
         stream_type: &str,
     ) -> Result<()> {
-        let mut favourites = self.get_favourites(provider_hash)?;
-        favourites.retain(|f| !(f.stream_id == stream_id && f.stream_type == stream_type));
-        self.save_favourites(provider_hash, favourites)?;
-        Ok(())
+        self.update_favourites(provider_hash, |favourites| {
+            favourites.retain(|f| !(f.stream_id == stream_id && f.stream_type == stream_type));
+            Ok(())
+        })
     }
 
     /// Check if a stream is a favourite
@@ -113,10 +204,42 @@ impl FavouritesManager {
         stream_id: u32,
         stream_type: &str,
     ) -> Result<bool> {
-        let favourites = self.get_favourites(provider_hash)?;
-        Ok(favourites
-            .iter()
-            .any(|f| f.stream_id == stream_id && f.stream_type == stream_type))
+        Ok(self.index(provider_hash)?.contains(stream_type, stream_id))
+    }
+
+    /// Record that an episode was played on the series' favourite, if the series is one
+    ///
+    /// Returns whether a series favourite was updated.
+    pub fn mark_episode_watched(
+        &self,
+        provider_hash: &str,
+        series_id: u32,
+        episode_id: &str,
+    ) -> Result<bool> {
+        // Most plays aren't of a favourite series, so check before taking the lock
+        let is_series_favourite = self.get_favourites(provider_hash)?.iter().any(|f| {
+            f.stream_id == series_id
+                && f.stream_type == "series"
+                && !f.watched_episodes.iter().any(|id| id == episode_id)
+        });
+        if !is_series_favourite {
+            return Ok(false);
+        }
+
+        self.update_favourites(provider_hash, |favourites| {
+            let Some(series) = favourites
+                .iter_mut()
+                .find(|f| f.stream_id == series_id && f.stream_type == "series")
+            else {
+                return Ok(false);
+            };
+            if series.watched_episodes.iter().any(|id| id == episode_id) {
+                return Ok(false);
+            }
+
+            series.watched_episodes.push(episode_id.to_string());
+            Ok(true)
+        })
     }
 
     /// Move a favourite up in the list
@@ -126,22 +249,21 @@ impl FavouritesManager {
         stream_id: u32,
         stream_type: &str,
     ) -> Result<bool> {
-        let mut favourites = self.get_favourites(provider_hash)?;
-
-        // Find the index of the favourite
-        if let Some(index) = favourites
-            .iter()
-            .position(|f| f.stream_id == stream_id && f.stream_type == stream_type)
-        {
-            // Can only move up if not at the start
-            if index > 0 {
-                favourites.swap(index, index - 1);
-                self.save_favourites(provider_hash, favourites)?;
-                return Ok(true);
+        self.update_favourites(provider_hash, |favourites| {
+            // Find the index of the favourite
+            if let Some(index) = favourites
+                .iter()
+                .position(|f| f.stream_id == stream_id && f.stream_type == stream_type)
+            {
+                // Can only move up if not at the start
+                if index > 0 {
+                    favourites.swap(index, index - 1);
+                    return Ok(true);
+                }
             }
-        }
 
-        Ok(false)
+            Ok(false)
+        })
     }
 
     /// Move a favourite down in the list
@@ -151,21 +273,879 @@ impl FavouritesManager {
         stream_id: u32,
         stream_type: &str,
     ) -> Result<bool> {
-        let mut favourites = self.get_favourites(provider_hash)?;
+        self.update_favourites(provider_hash, |favourites| {
+            // Find the index of the favourite
+            if let Some(index) = favourites
+                .iter()
+                .position(|f| f.stream_id == stream_id && f.stream_type == stream_type)
+            {
+                // Can only move down if not at the end
+                if index < favourites.len() - 1 {
+                    favourites.swap(index, index + 1);
+                    return Ok(true);
+                }
+            }
 
-        // Find the index of the favourite
-        if let Some(index) = favourites
+            Ok(false)
+        })
+    }
+
+    /// Load favourites from every provider file in the favourites directory, including
+    /// files left behind by providers that are no longer configured
+    pub fn get_all_favourites(&self) -> Result<Vec<FavouriteStream>> {
+        let mut all = Vec::new();
+        let entries = fs::read_dir(&self.favourites_dir).with_context(|| {
+            format!(
+                "Failed to read favourites directory: {}",
+                self.favourites_dir.display()
+            )
+        })?;
+
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            if let Some(provider_hash) = path.file_stem().and_then(|s| s.to_str()) {
+                all.extend(self.get_favourites(provider_hash)?);
+            }
+        }
+
+        Ok(all)
+    }
+
+    /// Load favourite groups, reconciled with the provider favourites files
+    ///
+    /// Before the first group change all existing favourites are shown in the default
+    /// group. Favourites that aren't in any group are appended to the first group, and
+    /// members whose favourite was removed are dropped. This only reads; the reconciled
+    /// groups are saved by the next change to them.
+    pub fn get_groups(&self) -> Result<Vec<FavouriteGroup>> {
+        self.load_groups(false)
+    }
+
+    /// Re-read the groups under the file lock, change them and save them
+    pub fn update_groups<R>(
+        &self,
+        update: impl FnOnce(&mut Vec<FavouriteGroup>) -> Result<R>,
+    ) -> Result<R> {
+        let _lock = storage::lock(&self.groups_path)?;
+        let mut groups = self.load_groups(true)?;
+        let result = update(&mut groups)?;
+        self.write_groups(&groups)?;
+        Ok(result)
+    }
+
+    /// Read and reconcile the groups
+    ///
+    /// A groups file that fails to parse is rebuilt from the favourites; when `repair` is
+    /// set, as it is before saving, the corrupt file is first kept aside.
+    fn load_groups(&self, repair: bool) -> Result<Vec<FavouriteGroup>> {
+        let mut groups = if !self.groups_path.exists() {
+            Vec::new()
+        } else {
+            let content = fs::read_to_string(&self.groups_path).with_context(|| {
+                format!(
+                    "Failed to read favourite groups file: {}",
+                    self.groups_path.display()
+                )
+            })?;
+            match serde_json::from_str::<FavouriteGroupsData>(&content) {
+                Ok(data) => data.groups,
+                Err(e) if repair => {
+                    let backup = storage::preserve_corrupt(&self.groups_path)?;
+                    tracing::warn!(
+                        "Rebuilding corrupt favourite groups file {} (copy kept at {}): {}",
+                        self.groups_path.display(),
+                        backup.display(),
+                        e
+                    );
+                    Vec::new()
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Ignoring corrupt favourite groups file {}: {}",
+                        self.groups_path.display(),
+                        e
+                    );
+                    Vec::new()
+                }
+            }
+        };
+
+        if groups.is_empty() {
+            groups.push(FavouriteGroup {
+                name: DEFAULT_GROUP.to_string(),
+                members: Vec::new(),
+            });
+        }
+
+        let favourites = self.get_all_favourites()?;
+        for group in &mut groups {
+            group
+                .members
+                .retain(|key| favourites.iter().any(|f| key.matches(f)));
+        }
+
+        for favourite in &favourites {
+            let grouped = groups
+                .iter()
+                .any(|g| g.members.iter().any(|key| key.matches(favourite)));
+            if !grouped {
+                groups[0].members.push(FavouriteKey::of(favourite));
+            }
+        }
+
+        Ok(groups)
+    }
+
+    /// Save favourite groups
+    pub fn save_groups(&self, groups: &[FavouriteGroup]) -> Result<()> {
+        let _lock = storage::lock(&self.groups_path)?;
+        self.write_groups(groups)
+    }
+
+    fn write_groups(&self, groups: &[FavouriteGroup]) -> Result<()> {
+        let data = FavouriteGroupsData {
+            groups: groups.to_vec(),
+        };
+
+        let content = serde_json::to_string_pretty(&data)
+            .with_context(|| "Failed to serialize favourite groups")?;
+
+        storage::write_atomic(&self.groups_path, content).with_context(|| {
+            format!(
+                "Failed to write favourite groups file: {}",
+                self.groups_path.display()
+            )
+        })
+    }
+
+    /// Get the favourites of a group in group order
+    pub fn get_group_favourites(&self, group_name: &str) -> Result<Vec<FavouriteStream>> {
+        let groups = self.get_groups()?;
+        let group = groups
             .iter()
-            .position(|f| f.stream_id == stream_id && f.stream_type == stream_type)
-        {
-            // Can only move down if not at the end
-            if index < favourites.len() - 1 {
-                favourites.swap(index, index + 1);
-                self.save_favourites(provider_hash, favourites)?;
+            .find(|g| g.name == group_name)
+            .with_context(|| format!("No favourite group named '{}'", group_name))?;
+
+        let favourites = self.get_all_favourites()?;
+        Ok(group
+            .members
+            .iter()
+            .filter_map(|key| favourites.iter().find(|f| key.matches(f)).cloned())
+            .collect())
+    }
+
+    /// Get the name of the group a favourite belongs to
+    pub fn group_of(&self, key: &FavouriteKey) -> Result<Option<String>> {
+        Ok(self
+            .get_groups()?
+            .into_iter()
+            .find(|g| g.members.contains(key))
+            .map(|g| g.name))
+    }
+
+    /// Create an empty group at the end of the list
+    pub fn create_group(&self, name: &str) -> Result<()> {
+        let name = name.trim();
+        if name.is_empty() {
+            anyhow::bail!("Group name cannot be empty");
+        }
+
+        self.update_groups(|groups| {
+            if groups.iter().any(|g| g.name == name) {
+                anyhow::bail!("Group '{}' already exists", name);
+            }
+
+            groups.push(FavouriteGroup {
+                name: name.to_string(),
+                members: Vec::new(),
+            });
+            Ok(())
+        })
+    }
+
+    /// Rename a group
+    pub fn rename_group(&self, old_name: &str, new_name: &str) -> Result<()> {
+        let new_name = new_name.trim();
+        if new_name.is_empty() {
+            anyhow::bail!("Group name cannot be empty");
+        }
+
+        self.update_groups(|groups| {
+            if old_name != new_name && groups.iter().any(|g| g.name == new_name) {
+                anyhow::bail!("Group '{}' already exists", new_name);
+            }
+
+            let group = groups
+                .iter_mut()
+                .find(|g| g.name == old_name)
+                .with_context(|| format!("No favourite group named '{}'", old_name))?;
+            group.name = new_name.to_string();
+            Ok(())
+        })
+    }
+
+    /// Delete a group, moving its favourites to the first remaining group
+    pub fn delete_group(&self, name: &str) -> Result<()> {
+        self.update_groups(|groups| {
+            if groups.len() == 1 {
+                anyhow::bail!("Cannot delete the last favourite group");
+            }
+
+            let index = groups
+                .iter()
+                .position(|g| g.name == name)
+                .with_context(|| format!("No favourite group named '{}'", name))?;
+            let removed = groups.remove(index);
+            groups[0].members.extend(removed.members);
+            Ok(())
+        })
+    }
+
+    /// Move a group one place up or down in the group list
+    pub fn move_group(&self, name: &str, up: bool) -> Result<bool> {
+        self.update_groups(|groups| {
+            let Some(index) = groups.iter().position(|g| g.name == name) else {
+                return Ok(false);
+            };
+
+            let target = if up {
+                index.checked_sub(1)
+            } else {
+                Some(index + 1).filter(|&i| i < groups.len())
+            };
+
+            match target {
+                Some(target) => {
+                    groups.swap(index, target);
+                    Ok(true)
+                }
+                None => Ok(false),
+            }
+        })
+    }
+
+    /// Move a favourite into a group, creating the group if it doesn't exist
+    pub fn move_to_group(&self, key: &FavouriteKey, group_name: &str) -> Result<()> {
+        let group_name = group_name.trim();
+        if group_name.is_empty() {
+            anyhow::bail!("Group name cannot be empty");
+        }
+
+        self.update_groups(|groups| {
+            for group in groups.iter_mut() {
+                group.members.retain(|member| member != key);
+            }
+
+            match groups.iter_mut().find(|g| g.name == group_name) {
+                Some(group) => group.members.push(key.clone()),
+                None => groups.push(FavouriteGroup {
+                    name: group_name.to_string(),
+                    members: vec![key.clone()],
+                }),
+            }
+            Ok(())
+        })
+    }
+
+    /// Move a favourite one place up or down within its group
+    pub fn move_within_group(&self, key: &FavouriteKey, up: bool) -> Result<bool> {
+        self.update_groups(|groups| {
+            for group in groups.iter_mut() {
+                let Some(index) = group.members.iter().position(|member| member == key) else {
+                    continue;
+                };
+
+                let target = if up {
+                    index.checked_sub(1)
+                } else {
+                    Some(index + 1).filter(|&i| i < group.members.len())
+                };
+
+                if let Some(target) = target {
+                    group.members.swap(index, target);
+                    return Ok(true);
+                }
+                return Ok(false);
+            }
+
+            Ok(false)
+        })
+    }
+
+    /// Move a favourite to a position within its group, counting from 0; positions past the
+    /// end move it to the end. Returns false if it isn't in a group or didn't move.
+    pub fn move_to_position(&self, key: &FavouriteKey, position: usize) -> Result<bool> {
+        self.update_groups(|groups| {
+            for group in groups.iter_mut() {
+                let Some(index) = group.members.iter().position(|member| member == key) else {
+                    continue;
+                };
+
+                let target = position.min(group.members.len() - 1);
+                if target == index {
+                    return Ok(false);
+                }
+                let member = group.members.remove(index);
+                group.members.insert(target, member);
                 return Ok(true);
             }
+
+            Ok(false)
+        })
+    }
+
+    /// Point favourites at other streams, possibly on other providers, keeping their place
+    /// in the provider list and in their group (`Some`), or remove them (`None`)
+    ///
+    /// All changes are made under the groups lock and the locks of every favourites file
+    /// involved, against the files as they are now. A favourite that has gone since it was
+    /// read is skipped, and a relink only counts as a duplicate of a favourite that stays,
+    /// so favourites can swap stream IDs. Returns which changes were applied.
+    pub fn relink_favourites(
+        &self,
+        changes: Vec<(FavouriteKey, Option<FavouriteStream>)>,
+    ) -> Result<Vec<bool>> {
+        let mut hashes: Vec<String> = changes
+            .iter()
+            .flat_map(|(old, new)| {
+                std::iter::once(old.provider_hash.clone())
+                    .chain(new.iter().map(|new| new.provider_hash.clone()))
+            })
+            .collect();
+        hashes.sort();
+        hashes.dedup();
+
+        // Reconcile groups before the favourites files change underneath them, and hold
+        // the groups lock until the favourites are moved
+        self.update_groups(|groups| {
+            self.update_favourites_of(&hashes, |files| {
+                // Only changes to favourites that are still there are applied
+                let applied: Vec<bool> = changes
+                    .iter()
+                    .map(|(old, _)| {
+                        files
+                            .get(&old.provider_hash)
+                            .is_some_and(|favourites| favourites.iter().any(|f| old.matches(f)))
+                    })
+                    .collect();
+                let moving: HashSet<&FavouriteKey> = changes
+                    .iter()
+                    .zip(&applied)
+                    .filter(|(_, applied)| **applied)
+                    .map(|((old, _), _)| old)
+                    .collect();
+
+                // Where each moved favourite ends up, or None if it is dropped, either
+                // removed or because a favourite that stays already has its new stream
+                let mut taken: HashSet<FavouriteKey> = files
+                    .values()
+                    .flatten()
+                    .map(FavouriteKey::of)
+                    .filter(|key| !moving.contains(key))
+                    .collect();
+                let mut targets: HashMap<FavouriteKey, Option<FavouriteStream>> = HashMap::new();
+                for ((old, new), _) in changes.iter().zip(&applied).filter(|(_, a)| **a) {
+                    let new = new
+                        .clone()
+                        .filter(|new| taken.insert(FavouriteKey::of(new)));
+                    targets.insert(old.clone(), new);
+                }
+
+                // Same-provider relinks keep their place; others move to the end of the
+                // new provider's list
+                let mut arriving: Vec<FavouriteStream> = Vec::new();
+                for favourites in files.values_mut() {
+                    let mut kept = Vec::with_capacity(favourites.len());
+                    for favourite in favourites.drain(..) {
+                        match targets.get(&FavouriteKey::of(&favourite)) {
+                            None => kept.push(favourite),
+                            Some(None) => {}
+                            Some(Some(new)) if new.provider_hash == favourite.provider_hash => {
+                                kept.push(new.clone())
+                            }
+                            Some(Some(new)) => arriving.push(new.clone()),
+                        }
+                    }
+                    *favourites = kept;
+                }
+                for new in arriving {
+                    if let Some(favourites) = files.get_mut(&new.provider_hash) {
+                        favourites.push(new);
+                    }
+                }
+
+                // Swap keys in place in the groups, dropping ones whose favourite was
+                // dropped or whose new stream is already grouped
+                let mut grouped: HashSet<FavouriteKey> = groups
+                    .iter()
+                    .flat_map(|g| g.members.iter())
+                    .filter(|member| !targets.contains_key(*member))
+                    .cloned()
+                    .collect();
+                for group in groups.iter_mut() {
+                    group.members = std::mem::take(&mut group.members)
+                        .into_iter()
+                        .filter_map(|member| match targets.get(&member) {
+                            None => Some(member),
+                            Some(new) => new
+                                .as_ref()
+                                .map(FavouriteKey::of)
+                                .filter(|key| grouped.insert(key.clone())),
+                        })
+                        .collect();
+                }
+
+                Ok(applied)
+            })
+        })
+    }
+
+    /// Like `update_favourites`, for several providers' favourites at once, keyed by
+    /// provider hash
+    fn update_favourites_of<R>(
+        &self,
+        provider_hashes: &[String],
+        update: impl FnOnce(&mut HashMap<String, Vec<FavouriteStream>>) -> Result<R>,
+    ) -> Result<R> {
+        // Locked in a fixed order so two processes can't each wait on the other
+        let mut provider_hashes = provider_hashes.to_vec();
+        provider_hashes.sort();
+        provider_hashes.dedup();
+
+        let mut locks = Vec::new();
+        let mut files = HashMap::new();
+        for provider_hash in &provider_hashes {
+            let path = self.get_favourites_path(provider_hash);
+            locks.push(storage::lock(&path)?);
+            files.insert(provider_hash.clone(), read_favourites_for_update(&path)?);
+        }
+
+        let result = update(&mut files)?;
+        for (provider_hash, favourites) in files {
+            write_favourites(&self.get_favourites_path(&provider_hash), favourites)?;
+        }
+        Ok(result)
+    }
+}
+
+/// Read a favourites file; a missing file has no favourites
+fn read_favourites(path: &Path) -> Result<Vec<FavouriteStream>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read favourites file: {}", path.display()))?;
+
+    let data: FavouritesData =
+        serde_json::from_str(&content).with_context(|| "Failed to parse favourites JSON")?;
+
+    Ok(data.favourites)
+}
+
+/// Read a favourites file that is about to be rewritten; a corrupt file is kept aside and
+/// treated as empty
+fn read_favourites_for_update(path: &Path) -> Result<Vec<FavouriteStream>> {
+    match read_favourites(path) {
+        Ok(favourites) => Ok(favourites),
+        Err(e) if e.is::<serde_json::Error>() => {
+            let backup = storage::preserve_corrupt(path)?;
+            tracing::warn!(
+                "Replacing corrupt favourites file {} (copy kept at {}): {}",
+                path.display(),
+                backup.display(),
+                e
+            );
+            Ok(Vec::new())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn write_favourites(path: &Path, favourites: Vec<FavouriteStream>) -> Result<()> {
+    let data = FavouritesData { favourites };
+
+    let content =
+        serde_json::to_string_pretty(&data).with_context(|| "Failed to serialize favourites")?;
+
+    storage::write_atomic(path, &content)
+        .with_context(|| format!("Failed to write favourites file: {}", path.display()))?;
+
+    let cached = LoadedFavourites {
+        stamp: FileStamp::of(path),
+        index: FavouritesIndex::new(data.favourites),
+    };
+    LOADED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(path.to_path_buf(), cached);
+    notify_changed();
+    Ok(())
+}
+
+/// Loaded favourites files, shared by every `FavouritesManager` in the process
+static LOADED: LazyLock<Mutex<HashMap<PathBuf, LoadedFavourites>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Counts changes to any favourites file
+static CHANGES: LazyLock<watch::Sender<u64>> = LazyLock::new(|| watch::channel(0).0);
+
+fn notify_changed() {
+    CHANGES.send_modify(|changes| *changes += 1);
+}
+
+struct LoadedFavourites {
+    stamp: Option<FileStamp>,
+    index: FavouritesIndex,
+}
+
+/// Identifies a version of a file without reading it; None if the file doesn't exist
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: SystemTime,
+    len: u64,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        Some(Self {
+            modified: metadata.modified().ok()?,
+            len: metadata.len(),
+        })
+    }
+}
+
+/// A provider's favourites with a set of (stream type, stream ID) for quick lookups
+#[derive(Debug, Clone, Default)]
+pub struct FavouritesIndex {
+    favourites: Arc<Vec<FavouriteStream>>,
+    keys: Arc<HashSet<(String, u32)>>,
+}
+
+impl FavouritesIndex {
+    fn new(favourites: Vec<FavouriteStream>) -> Self {
+        let keys = favourites
+            .iter()
+            .map(|f| (f.stream_type.clone(), f.stream_id))
+            .collect();
+        Self {
+            favourites: Arc::new(favourites),
+            keys: Arc::new(keys),
         }
+    }
 
-        Ok(false)
+    pub fn contains(&self, stream_type: &str, stream_id: u32) -> bool {
+        self.keys.contains(&(stream_type.to_string(), stream_id))
     }
+
+    /// Favourites in the provider's list order
+    pub fn favourites(&self) -> &[FavouriteStream] {
+        &self.favourites
+    }
+
+    pub fn get(&self, stream_type: &str, stream_id: u32) -> Option<&FavouriteStream> {
+        if !self.contains(stream_type, stream_id) {
+            return None;
+        }
+        self.favourites
+            .iter()
+            .find(|f| f.stream_id == stream_id && f.stream_type == stream_type)
+    }
+}
+
+/// Names at least this similar are taken to be the same stream after a rename
+pub const SAME_STREAM_SIMILARITY: f64 = 0.6;
+
+/// Quality tags that providers add and drop without the stream changing
+const QUALITY_TAGS: &[&str] = &[
+    "hd", "fhd", "uhd", "sd", "4k", "hevc", "h265", "1080p", "720p",
+];
+
+/// Result of checking a favourite against its provider's cached stream list
+#[derive(Debug, Clone, PartialEq)]
+pub enum FavouriteHealth {
+    /// The stream ID is still listed under the same name
+    Ok,
+    /// The stream ID is listed under another name
+    Renamed {
+        current_name: String,
+        similarity: f64,
+    },
+    /// The stream ID is no longer listed
+    Missing,
+    /// There is no cached stream list to check against
+    Unverified,
+}
+
+impl FavouriteHealth {
+    /// Whether the favourite no longer points at the stream it was saved for
+    pub fn is_dead(&self) -> bool {
+        match self {
+            Self::Missing => true,
+            Self::Renamed { similarity, .. } => *similarity < SAME_STREAM_SIMILARITY,
+            Self::Ok | Self::Unverified => false,
+        }
+    }
+}
+
+/// A stream from a provider's cached stream list
+#[derive(Debug, Clone)]
+pub struct CatalogueEntry {
+    pub stream_id: u32,
+    pub name: String,
+    pub category_id: Option<String>,
+}
+
+/// A stream that could replace a dead favourite
+#[derive(Debug, Clone)]
+pub struct RematchCandidate {
+    pub provider_hash: String,
+    pub entry: CatalogueEntry,
+    /// Similarity from 0.0 to 1.0, with a bonus for staying in the same category
+    pub score: f64,
+}
+
+impl RematchCandidate {
+    /// The favourite rewritten to point at this candidate
+    pub fn to_favourite(&self, favourite: &FavouriteStream) -> FavouriteStream {
+        FavouriteStream {
+            stream_id: self.entry.stream_id,
+            name: self.entry.name.clone(),
+            stream_type: favourite.stream_type.clone(),
+            provider_hash: self.provider_hash.clone(),
+            added_date: favourite.added_date,
+            category_id: self.entry.category_id.clone(),
+            ..favourite.clone()
+        }
+    }
+}
+
+/// Cached stream lists used to verify favourites without talking to the providers
+#[derive(Debug, Default)]
+pub struct StreamCatalogue {
+    // Keyed by provider hash and cache type
+    lists: HashMap<(String, String), Vec<CatalogueEntry>>,
+}
+
+/// The cached list a favourite's stream type lives in; episodes have none
+fn cache_type(stream_type: &str) -> Option<&'static str> {
+    match stream_type {
+        "movie" => Some("vod_streams"),
+        "series" => Some("series"),
+        "episode" => None,
+        _ => Some("live_streams"),
+    }
+}
+
+impl StreamCatalogue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a provider's cached list for a stream type unless already loaded
+    ///
+    /// Returns false if there is no usable cached list. This never fetches from the
+    /// provider, so it is cheap enough to run whenever favourites are shown.
+    pub async fn load(
+        &mut self,
+        cache: &CacheManager,
+        provider_hash: &str,
+        stream_type: &str,
+    ) -> bool {
+        let Some(cache_type) = cache_type(stream_type) else {
+            return false;
+        };
+        let key = (provider_hash.to_string(), cache_type.to_string());
+        if self.lists.contains_key(&key) {
+            return true;
+        }
+
+        // An unreadable cache file is treated the same as a missing one
+        let entries: Option<Vec<CatalogueEntry>> = if cache_type == "series" {
+            cache
+                .get_cached::<Vec<SeriesInfo>>(provider_hash, cache_type, None)
+                .await
+                .ok()
+                .flatten()
+                .map(|series| {
+                    series
+                        .into_iter()
+                        .map(|s| CatalogueEntry {
+                            stream_id: s.series_id,
+                            name: s.name,
+                            category_id: s.category_id,
+                        })
+                        .collect()
+                })
+        } else {
+            cache
+                .get_cached::<Vec<Stream>>(provider_hash, cache_type, None)
+                .await
+                .ok()
+                .flatten()
+                .map(|streams| {
+                    streams
+                        .into_iter()
+                        .map(|s| CatalogueEntry {
+                            stream_id: s.stream_id,
+                            name: s.name,
+                            category_id: s.category_id,
+                        })
+                        .collect()
+                })
+        };
+
+        match entries {
+            Some(entries) => {
+                self.lists.insert(key, entries);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Load the lists needed to check every one of `favourites`
+    pub async fn load_for(&mut self, cache: &CacheManager, favourites: &[FavouriteStream]) {
+        for favourite in favourites {
+            self.load(cache, &favourite.provider_hash, &favourite.stream_type)
+                .await;
+        }
+    }
+
+    fn list(&self, provider_hash: &str, stream_type: &str) -> Option<&[CatalogueEntry]> {
+        self.lists
+            .get(&(
+                provider_hash.to_string(),
+                cache_type(stream_type)?.to_string(),
+            ))
+            .map(|list| list.as_slice())
+    }
+
+    /// Check a favourite against its provider's loaded list
+    pub fn check(&self, favourite: &FavouriteStream) -> FavouriteHealth {
+        let Some(list) = self.list(&favourite.provider_hash, &favourite.stream_type) else {
+            return FavouriteHealth::Unverified;
+        };
+
+        match list.iter().find(|e| e.stream_id == favourite.stream_id) {
+            Some(entry) if entry.name == favourite.name => FavouriteHealth::Ok,
+            Some(entry) => FavouriteHealth::Renamed {
+                current_name: entry.name.clone(),
+                similarity: name_similarity(&entry.name, &favourite.name),
+            },
+            None => FavouriteHealth::Missing,
+        }
+    }
+
+    /// Streams on the given providers that could replace a favourite, best match first
+    ///
+    /// Lists must have been loaded for each provider. Candidates in the favourite's
+    /// own category get a small bonus, since renumbered channels rarely change category.
+    pub fn candidates(
+        &self,
+        favourite: &FavouriteStream,
+        provider_hashes: &[String],
+        min_score: f64,
+        limit: usize,
+    ) -> Vec<RematchCandidate> {
+        let mut candidates = Vec::new();
+
+        for provider_hash in provider_hashes {
+            let Some(list) = self.list(provider_hash, &favourite.stream_type) else {
+                continue;
+            };
+            let same_provider = *provider_hash == favourite.provider_hash;
+
+            for entry in list {
+                if same_provider && entry.stream_id == favourite.stream_id {
+                    continue;
+                }
+
+                let mut score = name_similarity(&entry.name, &favourite.name);
+                if same_provider
+                    && favourite.category_id.is_some()
+                    && entry.category_id == favourite.category_id
+                {
+                    score = (score + 0.1).min(1.0);
+                }
+
+                if score >= min_score {
+                    candidates.push(RematchCandidate {
+                        provider_hash: provider_hash.clone(),
+                        entry: entry.clone(),
+                        score,
+                    });
+                }
+            }
+        }
+
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+        candidates.truncate(limit);
+        candidates
+    }
+}
+
+/// Key for telling whether two providers' names are the same channel or film
+///
+/// Lower-cases the name and drops punctuation, quality tags and a short leading
+/// upper-case country prefix, so "UK: BBC One FHD" and "|UK| BBC ONE HD" both give "bbc one".
+pub fn title_key(name: &str) -> String {
+    let name = name.trim();
+    let name = match name.trim_start_matches('|').split_once([':', '|']) {
+        Some((prefix, rest))
+            if (1..=3).contains(&prefix.trim().len())
+                && prefix.trim().chars().all(|c| c.is_ascii_uppercase())
+                && !rest.trim().is_empty() =>
+        {
+            rest
+        }
+        _ => name,
+    };
+    let name = name.to_lowercase();
+    let words: Vec<&str> = name
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty() && !QUALITY_TAGS.contains(word))
+        .collect();
+    words.join(" ")
+}
+
+/// Similarity of two stream names from 0.0 to 1.0
+///
+/// Compares adjacent character pairs of the lower-cased names with punctuation and
+/// quality tags removed, so "UK: BBC One FHD" still closely matches "BBC One HD".
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    fn normalize(name: &str) -> Vec<char> {
+        name.to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty() && !QUALITY_TAGS.contains(word))
+            .flat_map(|word| word.chars())
+            .collect()
+    }
+
+    let a = normalize(a);
+    let b = normalize(b);
+    if a == b {
+        return 1.0;
+    }
+    if a.len() < 2 || b.len() < 2 {
+        return 0.0;
+    }
+
+    let mut pairs: Vec<(char, char)> = a.windows(2).map(|w| (w[0], w[1])).collect();
+    let total = pairs.len() + b.len() - 1;
+    let mut shared = 0;
+    for pair in b.windows(2).map(|w| (w[0], w[1])) {
+        if let Some(index) = pairs.iter().position(|p| *p == pair) {
+            pairs.swap_remove(index);
+            shared += 1;
+        }
+    }
+
+    (2 * shared) as f64 / total as f64
 }
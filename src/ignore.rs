@@ -1,78 +1,516 @@
-use anyhow::Result;
+use crate::cache::CacheManager;
+use crate::parental::{self, ParentalFilter};
+use crate::storage;
+use crate::xtream::{Category, SeriesInfo, Stream};
+use anyhow::{Context, Result};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// A hidden category or channel
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IgnoreRule {
+    /// Category or channel name; matched exactly when no ID is given
+    pub name: String,
+    /// category_id or stream_id; when set only this ID matches, whatever it is called
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// live, movie or series; unset matches every content type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+impl IgnoreRule {
+    pub fn matches(&self, content: &str, id: &str, name: &str) -> bool {
+        if self.content.as_deref().is_some_and(|c| c != content) {
+            return false;
+        }
+        match &self.id {
+            Some(rule_id) => rule_id == id,
+            None => self.name == name,
+        }
+    }
+}
+
+/// Which list of a provider's rules an entry belongs to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IgnoreKind {
+    #[default]
+    Category,
+    Channel,
+}
+
+/// How a pattern rule is written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PatternSyntax {
+    /// `*` and `?` wildcards matched against the whole name
+    #[default]
+    Glob,
+    /// A regular expression that may match anywhere in the name
+    Regex,
+}
+
+/// What a pattern rule does with the names it matches
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PatternMode {
+    /// Hide matching names
+    #[default]
+    Ignore,
+    /// Hide every name that doesn't match an allow rule of the same kind
+    Allow,
+}
+
+/// A glob or regex rule over category or channel names, matched case-insensitively
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatternRule {
+    pub pattern: String,
+    #[serde(default)]
+    pub syntax: PatternSyntax,
+    #[serde(default)]
+    pub mode: PatternMode,
+    #[serde(default)]
+    pub kind: IgnoreKind,
+    /// live, movie or series; unset matches every content type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+impl PatternRule {
+    pub fn compile(&self) -> Result<Regex> {
+        let source = match self.syntax {
+            PatternSyntax::Glob => {
+                let mut source = String::from("(?i)^");
+                for c in self.pattern.chars() {
+                    match c {
+                        '*' => source.push_str(".*"),
+                        '?' => source.push('.'),
+                        c => source.push_str(&regex::escape(&c.to_string())),
+                    }
+                }
+                source.push('$');
+                source
+            }
+            PatternSyntax::Regex => format!("(?i){}", self.pattern),
+        };
+        Regex::new(&source).with_context(|| format!("Invalid pattern '{}'", self.pattern))
+    }
+
+    /// Short description such as "hide live categories matching 'AR |*'"
+    pub fn describe(&self) -> String {
+        let action = match self.mode {
+            PatternMode::Ignore => "hide",
+            PatternMode::Allow => "only show",
+        };
+        let kind = match self.kind {
+            IgnoreKind::Category => "categories",
+            IgnoreKind::Channel => "channels",
+        };
+        let content = self
+            .content
+            .as_deref()
+            .map(|c| format!("{} ", c))
+            .unwrap_or_default();
+        let syntax = match self.syntax {
+            PatternSyntax::Glob => "",
+            PatternSyntax::Regex => "regex ",
+        };
+        format!(
+            "{} {}{} matching {}'{}'",
+            action, content, kind, syntax, self.pattern
+        )
+    }
+
+    fn applies_to(&self, kind: IgnoreKind, content: &str) -> bool {
+        self.kind == kind && self.content.as_deref().is_none_or(|c| c == content)
+    }
+}
+
+/// Rules for a single provider
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderIgnores {
+    /// Provider name when the rules were last changed, to make the file readable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_name: Option<String>,
+    #[serde(default)]
+    pub categories: Vec<IgnoreRule>,
+    #[serde(default)]
+    pub channels: Vec<IgnoreRule>,
+    #[serde(default)]
+    pub patterns: Vec<PatternRule>,
+}
+
+impl ProviderIgnores {
+    pub fn rules(&self, kind: IgnoreKind) -> &[IgnoreRule] {
+        match kind {
+            IgnoreKind::Category => &self.categories,
+            IgnoreKind::Channel => &self.channels,
+        }
+    }
+
+    fn rules_mut(&mut self, kind: IgnoreKind) -> &mut Vec<IgnoreRule> {
+        match kind {
+            IgnoreKind::Category => &mut self.categories,
+            IgnoreKind::Channel => &mut self.channels,
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct IgnoreConfig {
-    #[serde(default)]
+    /// Category names hidden on every provider; migrated into `providers` on startup
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
     pub categories: HashSet<String>,
-    #[serde(default)]
+    /// Channel names hidden on every provider; migrated into `providers` on startup
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
     pub channels: HashSet<String>,
+    /// Pattern rules for every provider
+    #[serde(default)]
+    pub patterns: Vec<PatternRule>,
+    /// Rules by provider hash
+    #[serde(default)]
+    pub providers: BTreeMap<String, ProviderIgnores>,
 }
 
 impl IgnoreConfig {
     pub fn load() -> Result<Self> {
+        Self::read(&Self::config_path()?)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::config_path()?;
+        let _lock = storage::lock(&path)?;
+        self.write(&path)
+    }
+
+    /// Re-read the rules under the file lock, change them and save them
+    ///
+    /// `self` is replaced by the rules on disk first, so changes made by another process
+    /// since this copy was loaded are kept.
+    pub fn update<R>(&mut self, update: impl FnOnce(&mut Self) -> Result<R>) -> Result<R> {
         let path = Self::config_path()?;
+        let _lock = storage::lock(&path)?;
+        *self = Self::read(&path)?;
+        let result = update(self)?;
+        self.write(&path)?;
+        Ok(result)
+    }
+
+    fn read(path: &Path) -> Result<Self> {
         if path.exists() {
-            let content = fs::read_to_string(&path)?;
+            let content = fs::read_to_string(path)?;
             Ok(toml::from_str(&content)?)
         } else {
             Ok(Self::default())
         }
     }
 
-    pub fn save(&self) -> Result<()> {
-        let path = Self::config_path()?;
+    fn write(&self, path: &Path) -> Result<()> {
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
         let content = toml::to_string_pretty(self)?;
-        fs::write(&path, content)?;
-        Ok(())
+        storage::write_atomic(path, content)
     }
 
-    pub fn toggle_category(&mut self, category: &str) -> Result<bool> {
-        let is_ignored = if self.categories.contains(category) {
-            self.categories.remove(category);
-            false
-        } else {
-            self.categories.insert(category.to_string());
-            true
-        };
+    /// Copy global name rules to each of the given (provider hash, name) pairs and drop
+    /// them, so ignoring something on one provider no longer hides it on the others
+    ///
+    /// Returns true if anything was migrated. The rules are only dropped once there is a
+    /// provider to move them to, and are applied globally until then.
+    pub fn migrate_global_rules(&mut self, providers: &[(String, String)]) -> Result<bool> {
+        if providers.is_empty() || (self.categories.is_empty() && self.channels.is_empty()) {
+            return Ok(false);
+        }
+
+        self.update(|config| {
+            if config.categories.is_empty() && config.channels.is_empty() {
+                return Ok(false);
+            }
+
+            let mut categories: Vec<&String> = config.categories.iter().collect();
+            categories.sort();
+            let mut channels: Vec<&String> = config.channels.iter().collect();
+            channels.sort();
+
+            for (provider_hash, provider_name) in providers {
+                let ignores = config.providers.entry(provider_hash.clone()).or_default();
+                ignores.provider_name = Some(provider_name.clone());
+                for (kind, names) in [
+                    (IgnoreKind::Category, &categories),
+                    (IgnoreKind::Channel, &channels),
+                ] {
+                    let rules = ignores.rules_mut(kind);
+                    for name in names {
+                        let rule = IgnoreRule {
+                            name: name.to_string(),
+                            id: None,
+                            content: None,
+                        };
+                        if !rules.contains(&rule) {
+                            rules.push(rule);
+                        }
+                    }
+                }
+            }
 
-        self.save()?;
-        Ok(is_ignored)
+            config.categories.clear();
+            config.channels.clear();
+            Ok(true)
+        })
     }
 
-    pub fn toggle_channel(&mut self, channel: &str) -> Result<bool> {
-        let is_ignored = if self.channels.contains(channel) {
-            self.channels.remove(channel);
-            false
-        } else {
-            self.channels.insert(channel.to_string());
-            true
-        };
+    /// Hide or show a category or channel on one provider, matching it by ID
+    ///
+    /// Any rule that currently hides the item is removed when showing it again, including
+    /// name-only rules. Returns true if the item is now ignored.
+    pub fn toggle(
+        &mut self,
+        provider_hash: &str,
+        provider_name: &str,
+        kind: IgnoreKind,
+        content: &str,
+        id: &str,
+        name: &str,
+    ) -> Result<bool> {
+        self.update(|config| {
+            let is_ignored = if config.is_ignored(provider_hash, kind, content, id, name) {
+                config.global_names_mut(kind).remove(name);
+                if let Some(ignores) = config.providers.get_mut(provider_hash) {
+                    ignores
+                        .rules_mut(kind)
+                        .retain(|rule| !rule.matches(content, id, name));
+                }
+                false
+            } else {
+                let ignores = config
+                    .providers
+                    .entry(provider_hash.to_string())
+                    .or_default();
+                ignores.provider_name = Some(provider_name.to_string());
+                ignores.rules_mut(kind).push(IgnoreRule {
+                    name: name.to_string(),
+                    id: Some(id.to_string()),
+                    content: Some(content.to_string()),
+                });
+                true
+            };
+
+            Ok(is_ignored)
+        })
+    }
+
+    /// Hide a category or channel on one provider by ID, unless a rule already hides it
+    ///
+    /// Returns false if it was already hidden.
+    pub fn hide(
+        &mut self,
+        provider_hash: &str,
+        provider_name: &str,
+        kind: IgnoreKind,
+        content: &str,
+        id: &str,
+        name: &str,
+    ) -> Result<bool> {
+        self.update(|config| {
+            if config.is_ignored(provider_hash, kind, content, id, name) {
+                return Ok(false);
+            }
+            let ignores = config
+                .providers
+                .entry(provider_hash.to_string())
+                .or_default();
+            ignores.provider_name = Some(provider_name.to_string());
+            ignores.rules_mut(kind).push(IgnoreRule {
+                name: name.to_string(),
+                id: Some(id.to_string()),
+                content: Some(content.to_string()),
+            });
+            Ok(true)
+        })
+    }
+
+    /// Remove a single rule, as listed by `rules`
+    pub fn remove_rule(
+        &mut self,
+        provider_hash: &str,
+        kind: IgnoreKind,
+        rule: &IgnoreRule,
+    ) -> Result<()> {
+        self.update(|config| {
+            if let Some(ignores) = config.providers.get_mut(provider_hash) {
+                ignores.rules_mut(kind).retain(|r| r != rule);
+            }
+            if rule.id.is_none() && rule.content.is_none() {
+                config.global_names_mut(kind).remove(&rule.name);
+            }
+            Ok(())
+        })
+    }
+
+    /// Rules that apply to a provider, including global rules that are not yet migrated
+    pub fn rules(&self, provider_hash: &str, kind: IgnoreKind) -> Vec<IgnoreRule> {
+        let mut rules: Vec<IgnoreRule> = self
+            .providers
+            .get(provider_hash)
+            .map(|ignores| ignores.rules(kind).to_vec())
+            .unwrap_or_default();
+
+        let mut global: Vec<&String> = self.global_names(kind).iter().collect();
+        global.sort();
+        for name in global {
+            let rule = IgnoreRule {
+                name: name.clone(),
+                id: None,
+                content: None,
+            };
+            if !rules.contains(&rule) {
+                rules.push(rule);
+            }
+        }
 
-        self.save()?;
-        Ok(is_ignored)
+        rules
     }
 
-    pub fn is_category_ignored(&self, category: &str) -> bool {
-        self.categories.contains(category)
+    pub fn is_ignored(
+        &self,
+        provider_hash: &str,
+        kind: IgnoreKind,
+        content: &str,
+        id: &str,
+        name: &str,
+    ) -> bool {
+        self.global_names(kind).contains(name)
+            || self.providers.get(provider_hash).is_some_and(|ignores| {
+                ignores
+                    .rules(kind)
+                    .iter()
+                    .any(|rule| rule.matches(content, id, name))
+            })
     }
 
-    pub fn is_channel_ignored(&self, channel: &str) -> bool {
-        self.channels.contains(channel)
+    pub fn is_category_ignored(
+        &self,
+        provider_hash: &str,
+        content: &str,
+        category_id: &str,
+        category: &str,
+    ) -> bool {
+        self.is_ignored(
+            provider_hash,
+            IgnoreKind::Category,
+            content,
+            category_id,
+            category,
+        )
     }
 
-    pub fn get_ignored_categories(&self) -> &HashSet<String> {
-        &self.categories
+    pub fn is_channel_ignored(
+        &self,
+        provider_hash: &str,
+        content: &str,
+        stream_id: u32,
+        channel: &str,
+    ) -> bool {
+        self.is_ignored(
+            provider_hash,
+            IgnoreKind::Channel,
+            content,
+            &stream_id.to_string(),
+            channel,
+        )
     }
 
-    pub fn get_ignored_channels(&self) -> &HashSet<String> {
-        &self.channels
+    /// Add a pattern rule for one provider, or for all of them when `provider` is None
+    ///
+    /// The provider is given as (hash, name). Fails if the pattern doesn't compile.
+    pub fn add_pattern(&mut self, provider: Option<(&str, &str)>, rule: PatternRule) -> Result<()> {
+        self.update(|config| {
+            rule.compile()?;
+            let patterns = match provider {
+                Some((provider_hash, provider_name)) => {
+                    let ignores = config
+                        .providers
+                        .entry(provider_hash.to_string())
+                        .or_default();
+                    ignores.provider_name = Some(provider_name.to_string());
+                    &mut ignores.patterns
+                }
+                None => &mut config.patterns,
+            };
+            if !patterns.contains(&rule) {
+                patterns.push(rule);
+            }
+            Ok(())
+        })
+    }
+
+    /// Remove a pattern rule from one provider, or from the rules for all providers when
+    /// `provider_hash` is None. Returns false if there was no such rule.
+    pub fn remove_pattern(
+        &mut self,
+        provider_hash: Option<&str>,
+        rule: &PatternRule,
+    ) -> Result<bool> {
+        self.update(|config| {
+            let patterns = match provider_hash {
+                Some(provider_hash) => match config.providers.get_mut(provider_hash) {
+                    Some(ignores) => &mut ignores.patterns,
+                    None => return Ok(false),
+                },
+                None => &mut config.patterns,
+            };
+            let len = patterns.len();
+            patterns.retain(|r| r != rule);
+            if patterns.len() == len {
+                return Ok(false);
+            }
+            Ok(true)
+        })
+    }
+
+    /// Pattern rules that apply to a provider, global ones first
+    pub fn patterns(&self, provider_hash: &str) -> Vec<PatternRule> {
+        let mut patterns = self.patterns.clone();
+        if let Some(ignores) = self.providers.get(provider_hash) {
+            patterns.extend(ignores.patterns.iter().cloned());
+        }
+        patterns
+    }
+
+    /// Compile every rule that applies to a provider, for filtering many names at once
+    ///
+    /// Patterns that fail to compile are skipped; they are checked when added.
+    pub fn matcher(&self, provider_hash: &str) -> IgnoreMatcher {
+        IgnoreMatcher::new(
+            [IgnoreKind::Category, IgnoreKind::Channel]
+                .into_iter()
+                .flat_map(|kind| {
+                    self.rules(provider_hash, kind)
+                        .into_iter()
+                        .map(move |rule| (kind, rule))
+                })
+                .collect(),
+            &self.patterns(provider_hash),
+        )
+    }
+
+    fn global_names(&self, kind: IgnoreKind) -> &HashSet<String> {
+        match kind {
+            IgnoreKind::Category => &self.categories,
+            IgnoreKind::Channel => &self.channels,
+        }
+    }
+
+    fn global_names_mut(&mut self, kind: IgnoreKind) -> &mut HashSet<String> {
+        match kind {
+            IgnoreKind::Category => &mut self.categories,
+            IgnoreKind::Channel => &mut self.channels,
+        }
     }
 
     fn config_path() -> Result<PathBuf> {
@@ -81,3 +519,175 @@ impl IgnoreConfig {
         Ok(config_dir.join("iptv").join("ignore.toml"))
     }
 }
+
+/// Every rule for one provider, compiled once
+#[derive(Debug, Default)]
+pub struct IgnoreMatcher {
+    rules: Vec<(IgnoreKind, IgnoreRule)>,
+    ignore: Vec<(PatternRule, Regex)>,
+    allow: Vec<(PatternRule, Regex)>,
+    parental: Option<ParentalFilter>,
+    // Category names by content type and category ID, for `hides_favourite`
+    category_names: HashMap<(String, String), String>,
+    // Adult-flagged stream IDs by content type, for `hides_favourite`
+    adult_streams: HashSet<(String, u32)>,
+}
+
+impl IgnoreMatcher {
+    /// Build a matcher from exact rules and patterns, skipping patterns that don't compile
+    pub fn new(rules: Vec<(IgnoreKind, IgnoreRule)>, patterns: &[PatternRule]) -> Self {
+        let mut matcher = Self {
+            rules,
+            ..Default::default()
+        };
+        for pattern in patterns {
+            if let Ok(regex) = pattern.compile() {
+                match pattern.mode {
+                    PatternMode::Ignore => matcher.ignore.push((pattern.clone(), regex)),
+                    PatternMode::Allow => matcher.allow.push((pattern.clone(), regex)),
+                }
+            }
+        }
+        matcher
+    }
+
+    /// Also hide adult content, unless parental controls are off or unlocked
+    pub fn with_parental(mut self, parental: Option<ParentalFilter>) -> Self {
+        self.parental = parental;
+        self
+    }
+
+    /// Whether a category or channel is hidden by an exact rule, an ignore pattern, or by
+    /// not matching any allow pattern of its kind
+    pub fn hides(&self, kind: IgnoreKind, content: &str, id: &str, name: &str) -> bool {
+        if self
+            .rules
+            .iter()
+            .any(|(k, rule)| *k == kind && rule.matches(content, id, name))
+        {
+            return true;
+        }
+
+        if self
+            .ignore
+            .iter()
+            .any(|(rule, regex)| rule.applies_to(kind, content) && regex.is_match(name))
+        {
+            return true;
+        }
+
+        let mut allow = self
+            .allow
+            .iter()
+            .filter(|(rule, _)| rule.applies_to(kind, content))
+            .peekable();
+        allow.peek().is_some() && !allow.any(|(_, regex)| regex.is_match(name))
+    }
+
+    pub fn hides_category(&self, content: &str, category: &Category) -> bool {
+        self.hides(
+            IgnoreKind::Category,
+            content,
+            &category.category_id,
+            &category.category_name,
+        ) || self
+            .parental
+            .as_ref()
+            .is_some_and(|parental| parental.hides_category(category))
+    }
+
+    /// Whether a stream is hidden, either itself or through its category
+    ///
+    /// `is_adult` is the provider's flag, which only matters under parental controls.
+    pub fn hides_stream(
+        &self,
+        content: &str,
+        stream_id: u32,
+        name: &str,
+        category: Option<&Category>,
+        is_adult: bool,
+    ) -> bool {
+        (is_adult && self.parental.is_some())
+            || self.hides(IgnoreKind::Channel, content, &stream_id.to_string(), name)
+            || category.is_some_and(|category| self.hides_category(content, category))
+    }
+
+    /// Load the provider's cached category names so `hides_favourite` can check categories,
+    /// and under parental controls the cached stream lists for their adult flags
+    pub async fn load_cached_categories(&mut self, cache: &CacheManager, provider_hash: &str) {
+        for (content, cache_type) in [
+            ("live", "live_categories"),
+            ("movie", "vod_categories"),
+            ("series", "series_categories"),
+        ] {
+            if let Ok(Some(categories)) = cache
+                .get_cached::<Vec<Category>>(provider_hash, cache_type, None)
+                .await
+            {
+                for category in categories {
+                    self.category_names.insert(
+                        (content.to_string(), category.category_id),
+                        category.category_name,
+                    );
+                }
+            }
+        }
+
+        if self.parental.is_none() {
+            return;
+        }
+        for (content, cache_type) in [("live", "live_streams"), ("movie", "vod_streams")] {
+            if let Ok(Some(streams)) = cache
+                .get_cached::<Vec<Stream>>(provider_hash, cache_type, None)
+                .await
+            {
+                self.adult_streams.extend(
+                    streams
+                        .into_iter()
+                        .filter(|s| parental::is_adult(s.is_adult.as_ref()))
+                        .map(|s| (content.to_string(), s.stream_id)),
+                );
+            }
+        }
+        if let Ok(Some(series)) = cache
+            .get_cached::<Vec<SeriesInfo>>(provider_hash, "series", None)
+            .await
+        {
+            self.adult_streams.extend(
+                series
+                    .into_iter()
+                    .filter(|s| parental::is_adult(s.is_adult.as_ref()))
+                    .map(|s| ("series".to_string(), s.series_id)),
+            );
+        }
+    }
+
+    /// Whether a favourite is hidden by its name or category
+    ///
+    /// Episodes are checked as part of their series: its category and adult flag.
+    pub fn hides_favourite(&self, favourite: &crate::xtream::FavouriteStream) -> bool {
+        let (content, adult_id) = match favourite.series_id {
+            Some(series_id) if favourite.stream_type == "episode" => ("series", series_id),
+            _ => (favourite.stream_type.as_str(), favourite.stream_id),
+        };
+        // Without its cached name a category would fail every category allow pattern
+        let category = favourite.category_id.as_ref().and_then(|id| {
+            let name = self
+                .category_names
+                .get(&(content.to_string(), id.clone()))?;
+            Some(Category {
+                category_id: id.clone(),
+                category_name: name.clone(),
+                parent_id: None,
+            })
+        });
+        self.hides_stream(
+            content,
+            favourite.stream_id,
+            &favourite.name,
+            category.as_ref(),
+            self.adult_streams
+                .contains(&(content.to_string(), adult_id)),
+        )
+    }
+}
@@ -0,0 +1,280 @@
+use anyhow::{Context, Result};
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+/// Program that shows the launcher's menus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuBackend {
+    Rofi,
+    Dmenu,
+    Wofi,
+    Fuzzel,
+    Fzf,
+}
+
+impl MenuBackend {
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "rofi" => Ok(Self::Rofi),
+            "dmenu" => Ok(Self::Dmenu),
+            "wofi" => Ok(Self::Wofi),
+            "fuzzel" => Ok(Self::Fuzzel),
+            "fzf" => Ok(Self::Fzf),
+            _ => anyhow::bail!(
+                "Invalid menu: {}. Use 'rofi', 'dmenu', 'wofi', 'fuzzel' or 'fzf'",
+                s
+            ),
+        }
+    }
+
+    /// Pick the first installed backend that suits the session: fzf in a terminal, fuzzel
+    /// or wofi on Wayland, otherwise rofi or dmenu
+    pub fn detect() -> Result<Self> {
+        let in_terminal = std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+        let on_wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+
+        let candidates: &[Self] = if in_terminal {
+            &[Self::Fzf, Self::Rofi, Self::Fuzzel, Self::Wofi, Self::Dmenu]
+        } else if on_wayland {
+            &[Self::Fuzzel, Self::Wofi, Self::Rofi, Self::Dmenu]
+        } else {
+            &[Self::Rofi, Self::Dmenu, Self::Fuzzel, Self::Wofi]
+        };
+
+        candidates
+            .iter()
+            .copied()
+            .find(|backend| backend.is_installed())
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No menu program found. Please install rofi, dmenu, wofi, fuzzel or fzf."
+                )
+            })
+    }
+
+    pub fn binary(self) -> &'static str {
+        match self {
+            Self::Rofi => "rofi",
+            Self::Dmenu => "dmenu",
+            Self::Wofi => "wofi",
+            Self::Fuzzel => "fuzzel",
+            Self::Fzf => "fzf",
+        }
+    }
+
+    pub fn is_installed(self) -> bool {
+        std::env::var_os("PATH").is_some_and(|path| {
+            std::env::split_paths(&path).any(|dir| dir.join(self.binary()).is_file())
+        })
+    }
+
+    /// Show a menu and return the chosen entry, or the typed text if nothing matched
+    pub fn select(self, prompt: &str, entries: &[String]) -> Result<Option<String>> {
+        let command = self.menu_command(prompt);
+        let Some(output) = self.run(command, Some(entries))? else {
+            return Ok(None);
+        };
+        let lines: Vec<&str> = output.lines().collect();
+        let selected = match self {
+            // fzf prints the query, then the selection if something matched
+            Self::Fzf => lines.get(1).or(lines.first()),
+            _ => lines.first(),
+        };
+        Ok(selected
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty()))
+    }
+
+    /// Show a menu and return the index of the chosen entry, so entries with the same label
+    /// can be told apart; None if it was cancelled or nothing matched
+    pub fn select_index(self, prompt: &str, entries: &[String]) -> Result<Option<usize>> {
+        let mut command = self.menu_command(prompt);
+        // rofi and fuzzel can print the index themselves; fzf is given it as a hidden
+        // field, and dmenu and wofi as invisible characters after the label
+        let tagged: Vec<String> = match self {
+            Self::Rofi => {
+                command.arg("-format").arg("i");
+                entries.to_vec()
+            }
+            Self::Fuzzel => {
+                command.arg("--index");
+                entries.to_vec()
+            }
+            Self::Fzf => {
+                command
+                    .arg("--delimiter")
+                    .arg("\t")
+                    .arg("--with-nth")
+                    .arg("2..");
+                entries
+                    .iter()
+                    .enumerate()
+                    .map(|(index, entry)| format!("{}\t{}", index, entry))
+                    .collect()
+            }
+            Self::Dmenu | Self::Wofi => entries
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| format!("{}{}", entry, hidden_index(index)))
+                .collect(),
+        };
+
+        let Some(output) = self.run(command, Some(&tagged))? else {
+            return Ok(None);
+        };
+        let lines: Vec<&str> = output.lines().collect();
+        let index = match self {
+            Self::Rofi | Self::Fuzzel => lines.first().and_then(|line| line.trim().parse().ok()),
+            // fzf prints the query, then the selection if something matched
+            Self::Fzf => lines
+                .get(1)
+                .and_then(|line| line.split_once('\t'))
+                .and_then(|(index, _)| index.parse().ok()),
+            Self::Dmenu | Self::Wofi => lines.first().and_then(|line| read_hidden_index(line)),
+        };
+        Ok(index.filter(|&index| index < entries.len()))
+    }
+
+    fn menu_command(self, prompt: &str) -> Command {
+        let mut command = Command::new(self.binary());
+        match self {
+            Self::Rofi => {
+                command
+                    .arg("-dmenu")
+                    .arg("-hover-select")
+                    .arg("-me-select-entry")
+                    .arg("")
+                    .arg("-me-accept-entry")
+                    .arg("MousePrimary")
+                    .arg("-i") // case insensitive
+                    .arg("-p")
+                    .arg(prompt);
+            }
+            Self::Dmenu => {
+                command.arg("-i").arg("-l").arg("20").arg("-p").arg(prompt);
+            }
+            Self::Wofi => {
+                command.arg("--dmenu").arg("-i").arg("-p").arg(prompt);
+            }
+            Self::Fuzzel => {
+                command
+                    .arg("--dmenu")
+                    .arg("-p")
+                    .arg(format!("{}: ", prompt));
+            }
+            Self::Fzf => {
+                // The query is printed first so typed text can be used when nothing matches
+                command
+                    .arg("--print-query")
+                    .arg("--no-sort")
+                    .arg("--prompt")
+                    .arg(format!("{}> ", prompt));
+            }
+        }
+        command
+    }
+
+    /// Ask for a secret such as a PIN without echoing it where the backend allows
+    pub fn password(self, prompt: &str) -> Result<Option<String>> {
+        let mut command = Command::new(self.binary());
+        match self {
+            Self::Rofi => {
+                command.arg("-dmenu").arg("-password").arg("-p").arg(prompt);
+            }
+            Self::Dmenu => {
+                // dmenu has no password mode; draw the typed text in the background colour
+                command
+                    .arg("-p")
+                    .arg(prompt)
+                    .arg("-nf")
+                    .arg("#222222")
+                    .arg("-nb")
+                    .arg("#222222");
+            }
+            Self::Wofi => {
+                command
+                    .arg("--dmenu")
+                    .arg("--password")
+                    .arg("-p")
+                    .arg(prompt);
+            }
+            Self::Fuzzel => {
+                command
+                    .arg("--dmenu")
+                    .arg("--password")
+                    .arg("-p")
+                    .arg(format!("{}: ", prompt));
+            }
+            Self::Fzf => {
+                return match inquire::Password::new(&format!("{}:", prompt))
+                    .without_confirmation()
+                    .prompt()
+                {
+                    Ok(pin) => Ok(Some(pin)),
+                    Err(inquire::InquireError::OperationCanceled)
+                    | Err(inquire::InquireError::OperationInterrupted) => Ok(None),
+                    Err(e) => Err(e.into()),
+                };
+            }
+        }
+
+        Ok(self
+            .run(command, None)?
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty()))
+    }
+
+    /// Run the menu program with the entries on stdin; None if it was cancelled
+    fn run(self, mut command: Command, entries: Option<&[String]>) -> Result<Option<String>> {
+        command.stdin(Stdio::piped()).stdout(Stdio::piped());
+        let mut process = command
+            .spawn()
+            .with_context(|| format!("Failed to start '{}'", self.binary()))?;
+
+        // Send the entries to the menu's stdin; dropping it closes the pipe
+        if let Some(mut stdin) = process.stdin.take() {
+            stdin.write_all(entries.unwrap_or_default().join("\n").as_bytes())?;
+        }
+
+        let output = process.wait_with_output()?;
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+
+        // fzf exits with 1 when nothing matched but still prints the typed query
+        let typed_query =
+            self == Self::Fzf && output.status.code() == Some(1) && !stdout.trim().is_empty();
+        if !output.status.success() && !typed_query {
+            // User cancelled or error
+            return Ok(None);
+        }
+        Ok(Some(stdout))
+    }
+}
+
+/// Marks the start of an index hidden after a label
+const HIDDEN_INDEX_MARK: char = '\u{2060}';
+
+/// An entry's index written as zero-width characters, one per binary digit
+fn hidden_index(index: usize) -> String {
+    let digits = format!("{:b}", index);
+    std::iter::once(HIDDEN_INDEX_MARK)
+        .chain(
+            digits
+                .chars()
+                .map(|digit| if digit == '1' { '\u{200C}' } else { '\u{200B}' }),
+        )
+        .collect()
+}
+
+/// The index hidden after a chosen label by `hidden_index`
+fn read_hidden_index(line: &str) -> Option<usize> {
+    let (_, digits) = line.rsplit_once(HIDDEN_INDEX_MARK)?;
+    let digits: String = digits
+        .chars()
+        .map(|c| match c {
+            '\u{200B}' => Some('0'),
+            '\u{200C}' => Some('1'),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    usize::from_str_radix(&digits, 2).ok()
+}
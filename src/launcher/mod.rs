@@ -0,0 +1,637 @@
+pub mod menu;
+
+pub use menu::MenuBackend;
+
+use crate::cli::provider_api;
+use anyhow::Result;
+use iptv::Player;
+use iptv::config::{ParentalSettings, ProviderConfig};
+use iptv::favourites::{FavouriteGroup, FavouriteKey, FavouritesManager};
+use iptv::ignore::{IgnoreConfig, IgnoreMatcher};
+use iptv::parental::{self, ParentalFilter};
+use iptv::recents::RecentsManager;
+use iptv::xtream::{ApiEpisode, Category, FavouriteStream, XTreamAPI};
+use std::collections::HashMap;
+
+/// Top level entry that opens favourite group management
+const MANAGE_GROUPS: &str = "⚙ Manage groups";
+
+/// Prefix of the series menu entry that plays the next unwatched episode
+const NEXT_UNWATCHED: &str = "▶ Next unwatched: ";
+
+/// Prefix of recently played entries in the top level menu
+const RECENT: &str = "↺ ";
+
+/// Prefix of the entries that browse a provider's categories
+const BROWSE: &str = "☰ ";
+
+/// Number of recently played items shown in the top level menu
+const MAX_RECENTS_SHOWN: usize = 10;
+
+const CONTENT_TYPES: [(&str, &str); 3] = [
+    ("live", "Live TV"),
+    ("movie", "Movies"),
+    ("series", "Series"),
+];
+
+/// What a top level menu entry does
+enum TopEntry {
+    Play(FavouriteStream),
+    Group(FavouriteGroup),
+    Browse(usize, Option<&'static str>),
+    ManageGroups,
+}
+
+struct LauncherProvider {
+    config: ProviderConfig,
+    api: XTreamAPI,
+    matcher: IgnoreMatcher,
+}
+
+/// Drill-down menus for favourites, recents and every provider's categories and streams
+pub struct Launcher {
+    menu: MenuBackend,
+    player: Player,
+    providers: Vec<LauncherProvider>,
+    favourites_manager: FavouritesManager,
+    recents: RecentsManager,
+}
+
+impl Launcher {
+    pub async fn new(
+        menu: MenuBackend,
+        providers: Vec<ProviderConfig>,
+        player: Player,
+        parental: Option<ParentalFilter>,
+    ) -> Result<Self> {
+        let ignore = IgnoreConfig::load().unwrap_or_default();
+
+        let mut launcher_providers = Vec::new();
+        for config in providers {
+            tracing::debug!(
+                "Connecting to provider: {}",
+                config.name.as_ref().unwrap_or(&config.url)
+            );
+            let api = provider_api(&config)?;
+
+            // Ignore rules and parental controls apply to every menu
+            let mut matcher = ignore
+                .matcher(&api.provider_hash)
+                .with_parental(parental.clone());
+            matcher
+                .load_cached_categories(&api.cache_manager, &api.provider_hash)
+                .await;
+
+            launcher_providers.push(LauncherProvider {
+                config,
+                api,
+                matcher,
+            });
+        }
+
+        Ok(Self {
+            menu,
+            player,
+            providers: launcher_providers,
+            favourites_manager: FavouritesManager::new()?,
+            recents: RecentsManager::new()?,
+        })
+    }
+
+    /// Show the top level menu; cancelling a sub-menu goes back a level
+    pub async fn run(&mut self) -> Result<()> {
+        if self.providers.is_empty() {
+            eprintln!("No providers configured. Please check your config file.");
+            return Ok(());
+        }
+
+        loop {
+            let entries = self.top_entries()?;
+            let Some(entry) = self.choose("IPTV", entries)? else {
+                return Ok(());
+            };
+
+            let played = match entry {
+                TopEntry::Play(item) => self.play_item(item).await?,
+                TopEntry::Group(group) => self.open_group(&group).await?,
+                TopEntry::Browse(provider, Some(content)) => {
+                    self.browse_content(provider, content).await?
+                }
+                TopEntry::Browse(provider, None) => self.browse_provider(provider).await?,
+                TopEntry::ManageGroups => {
+                    let favourites: Vec<(FavouriteKey, String)> = self
+                        .visible_favourites()?
+                        .iter()
+                        .map(|f| (FavouriteKey::of(f), self.display_name(f)))
+                        .collect();
+                    return self.manage_groups(&favourites);
+                }
+            };
+            if played {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Favourites (or their groups), recents, browse entries and group management
+    fn top_entries(&self) -> Result<Vec<(String, TopEntry)>> {
+        let mut entries = Vec::new();
+
+        // With a single group list its favourites, otherwise its name
+        let favourites = self.visible_favourites()?;
+        let groups = self.favourites_manager.get_groups()?;
+        if groups.len() > 1 {
+            for group in groups {
+                let count = self.group_members(&group, &favourites).len();
+                entries.push((
+                    format!("★ {} ({})", group.name, count),
+                    TopEntry::Group(group),
+                ));
+            }
+        } else if let Some(group) = groups.first() {
+            for favourite in self.group_members(group, &favourites) {
+                entries.push((
+                    self.display_name(favourite),
+                    TopEntry::Play(favourite.clone()),
+                ));
+            }
+        }
+
+        for recent in self
+            .recents
+            .get_recents()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|r| !self.is_hidden(r))
+            .take(MAX_RECENTS_SHOWN)
+        {
+            entries.push((
+                format!("{}{}", RECENT, self.display_name(&recent)),
+                TopEntry::Play(recent),
+            ));
+        }
+
+        // A single provider's content types are shown directly
+        if self.providers.len() == 1 {
+            for (content, label) in CONTENT_TYPES {
+                entries.push((
+                    format!("{}{}", BROWSE, label),
+                    TopEntry::Browse(0, Some(content)),
+                ));
+            }
+        } else {
+            for (index, provider) in self.providers.iter().enumerate() {
+                entries.push((
+                    format!("{}Browse {}", BROWSE, provider.config.label()),
+                    TopEntry::Browse(index, None),
+                ));
+            }
+        }
+
+        entries.push((MANAGE_GROUPS.to_string(), TopEntry::ManageGroups));
+        Ok(entries)
+    }
+
+    /// Favourites of configured providers that aren't hidden
+    fn visible_favourites(&self) -> Result<Vec<FavouriteStream>> {
+        let mut favourites = Vec::new();
+        for provider in &self.providers {
+            favourites.extend(
+                self.favourites_manager
+                    .get_favourites(&provider.api.provider_hash)?
+                    .into_iter()
+                    .filter(|f| !provider.matcher.hides_favourite(f)),
+            );
+        }
+        Ok(favourites)
+    }
+
+    /// Favourites of a group in group order
+    fn group_members<'a>(
+        &self,
+        group: &FavouriteGroup,
+        favourites: &'a [FavouriteStream],
+    ) -> Vec<&'a FavouriteStream> {
+        group
+            .members
+            .iter()
+            .filter_map(|key| favourites.iter().find(|f| FavouriteKey::of(f) == *key))
+            .collect()
+    }
+
+    fn provider_index(&self, provider_hash: &str) -> Option<usize> {
+        self.providers
+            .iter()
+            .position(|p| p.api.provider_hash == provider_hash)
+    }
+
+    /// Hidden by ignore rules or parental controls, or from a provider no longer configured
+    fn is_hidden(&self, item: &FavouriteStream) -> bool {
+        self.provider_index(&item.provider_hash)
+            .is_none_or(|index| self.providers[index].matcher.hides_favourite(item))
+    }
+
+    /// Name with the provider name when there are several providers
+    fn display_name(&self, item: &FavouriteStream) -> String {
+        let provider_name = self
+            .provider_index(&item.provider_hash)
+            .filter(|_| self.providers.len() > 1)
+            .and_then(|index| self.providers[index].config.name.as_ref())
+            .map(|name| format!(" [{}]", name))
+            .unwrap_or_default();
+        format!("{}{}", item.name, provider_name)
+    }
+
+    /// Show labelled entries and return the value of the chosen one
+    fn choose<T>(&self, prompt: &str, entries: Vec<(String, T)>) -> Result<Option<T>> {
+        let labels: Vec<String> = entries.iter().map(|(label, _)| label.clone()).collect();
+        let Some(index) = self.menu.select_index(prompt, &labels)? else {
+            return Ok(None);
+        };
+        Ok(entries.into_iter().nth(index).map(|(_, value)| value))
+    }
+
+    async fn open_group(&mut self, group: &FavouriteGroup) -> Result<bool> {
+        let favourites = self.visible_favourites()?;
+        let entries = self
+            .group_members(group, &favourites)
+            .into_iter()
+            .map(|f| (self.display_name(f), f.clone()))
+            .collect();
+        match self.choose(&group.name, entries)? {
+            Some(item) => self.play_item(item).await,
+            None => Ok(false),
+        }
+    }
+
+    async fn browse_provider(&mut self, provider: usize) -> Result<bool> {
+        let name = self.providers[provider].config.label();
+        loop {
+            let entries = CONTENT_TYPES
+                .iter()
+                .map(|(content, label)| (label.to_string(), *content))
+                .collect();
+            let Some(content) = self.choose(&name, entries)? else {
+                return Ok(false);
+            };
+            if self.browse_content(provider, content).await? {
+                return Ok(true);
+            }
+        }
+    }
+
+    async fn browse_content(&mut self, provider: usize, content: &str) -> Result<bool> {
+        let LauncherProvider { api, matcher, .. } = &mut self.providers[provider];
+        let categories = match content {
+            "live" => api.get_live_categories().await?,
+            "movie" => api.get_vod_categories().await?,
+            _ => api.get_series_categories().await?,
+        };
+        let mut entries: Vec<(String, Category)> = vec![(
+            "All".to_string(),
+            Category {
+                category_id: "all".to_string(),
+                category_name: "All".to_string(),
+                parent_id: None,
+            },
+        )];
+        // Streams listed under "All" are still filtered by their own category
+        let by_id: HashMap<String, Category> = categories
+            .iter()
+            .map(|c| (c.category_id.clone(), c.clone()))
+            .collect();
+        entries.extend(
+            categories
+                .into_iter()
+                .filter(|c| !matcher.hides_category(content, c))
+                .map(|c| (c.category_name.clone(), c)),
+        );
+
+        let label = CONTENT_TYPES
+            .iter()
+            .find(|(c, _)| *c == content)
+            .map_or(content, |(_, label)| label);
+        loop {
+            let Some(category) = self.choose(label, entries.clone())? else {
+                return Ok(false);
+            };
+            if self
+                .browse_category(provider, content, &category, &by_id)
+                .await?
+            {
+                return Ok(true);
+            }
+        }
+    }
+
+    async fn browse_category(
+        &mut self,
+        provider: usize,
+        content: &str,
+        category: &Category,
+        categories: &HashMap<String, Category>,
+    ) -> Result<bool> {
+        let LauncherProvider { api, matcher, .. } = &mut self.providers[provider];
+        let category_id = Some(category.category_id.as_str()).filter(|id| *id != "all");
+        let category_of = |id: Option<&str>| {
+            id.and_then(|id| categories.get(id))
+                .or(category_id.map(|_| category))
+        };
+        let provider_hash = api.provider_hash.clone();
+
+        // Streams and series as playable items; series are picked from further down
+        let items: Vec<FavouriteStream> = if content == "series" {
+            api.get_series(category_id)
+                .await?
+                .into_iter()
+                .filter(|s| {
+                    !matcher.hides_stream(
+                        content,
+                        s.series_id,
+                        &s.name,
+                        category_of(s.category_id.as_deref()),
+                        parental::is_adult(s.is_adult.as_ref()),
+                    )
+                })
+                .map(|s| {
+                    FavouriteStream::new(
+                        &provider_hash,
+                        s.series_id,
+                        s.name,
+                        content,
+                        s.category_id,
+                    )
+                })
+                .collect()
+        } else {
+            let streams = if content == "live" {
+                api.get_live_streams(category_id).await?
+            } else {
+                api.get_vod_streams(category_id).await?
+            };
+            streams
+                .into_iter()
+                .filter(|s| {
+                    !matcher.hides_stream(
+                        content,
+                        s.stream_id,
+                        &s.name,
+                        category_of(s.category_id.as_deref()),
+                        parental::is_adult(s.is_adult.as_ref()),
+                    )
+                })
+                .map(|s| {
+                    let mut stream = FavouriteStream::new(
+                        &provider_hash,
+                        s.stream_id,
+                        s.name,
+                        content,
+                        s.category_id,
+                    );
+                    stream.container_extension = s.container_extension;
+                    stream
+                })
+                .collect()
+        };
+
+        // Favourites are starred
+        let favourites = self.favourites_manager.index(&provider_hash)?;
+        let entries: Vec<(String, FavouriteStream)> = items
+            .into_iter()
+            .map(|i| {
+                let mark = if favourites.contains(&i.stream_type, i.stream_id) {
+                    "★ "
+                } else {
+                    ""
+                };
+                (format!("{}{}", mark, i.name), i)
+            })
+            .collect();
+        loop {
+            let Some(selected) = self.choose(&category.category_name, entries.clone())? else {
+                return Ok(false);
+            };
+            if self.play_item(selected).await? {
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Play an item; series first go through the season and episode menus
+    ///
+    /// Returns false if the episode menus were cancelled.
+    async fn play_item(&mut self, item: FavouriteStream) -> Result<bool> {
+        let Some(provider) = self.provider_index(&item.provider_hash) else {
+            eprintln!("The provider of '{}' is no longer configured", item.name);
+            return Ok(false);
+        };
+
+        let item = if item.stream_type == "series" {
+            let watched = self.watched_episodes(&item);
+            let Some(episode) = self.pick_episode(provider, &item, &watched).await? else {
+                return Ok(false);
+            };
+            match FavouriteStream::from_episode(
+                &item.provider_hash,
+                item.stream_id,
+                &item.name,
+                item.category_id.clone(),
+                &episode,
+            ) {
+                Some(episode) => episode,
+                None => {
+                    eprintln!("Unsupported episode ID: {}", episode.id);
+                    return Ok(false);
+                }
+            }
+        } else {
+            item
+        };
+
+        // Movies keep their container; live streams play as HLS
+        let extension = (item.stream_type == "movie")
+            .then_some(item.container_extension.as_deref())
+            .flatten();
+        let url = self.providers[provider]
+            .api
+            .get_favourite_url(&item, extension);
+
+        tracing::info!("Starting playback of: {}", item.name);
+
+        // Play in detached mode so the menu can exit cleanly
+        self.player.play_detached(&url).await?;
+
+        if let Some(series_id) = item.series_id {
+            self.favourites_manager.mark_episode_watched(
+                &item.provider_hash,
+                series_id,
+                &item.stream_id.to_string(),
+            )?;
+        }
+        if let Err(e) = self.recents.record(item) {
+            tracing::warn!("Failed to record recently played item: {}", e);
+        }
+        Ok(true)
+    }
+
+    /// Episodes played from the series' favourite, if it is one
+    fn watched_episodes(&self, series: &FavouriteStream) -> Vec<String> {
+        self.favourites_manager
+            .get_favourites(&series.provider_hash)
+            .unwrap_or_default()
+            .into_iter()
+            .find(|f| f.stream_type == "series" && f.stream_id == series.stream_id)
+            .map(|f| f.watched_episodes)
+            .unwrap_or_default()
+    }
+
+    /// Pick an episode: the next unwatched one, or one from a season menu
+    async fn pick_episode(
+        &mut self,
+        provider: usize,
+        series: &FavouriteStream,
+        watched: &[String],
+    ) -> Result<Option<ApiEpisode>> {
+        let info = self.providers[provider]
+            .api
+            .get_series_info(series.stream_id)
+            .await?;
+        let episodes = info.sorted_episodes();
+        if episodes.is_empty() {
+            eprintln!("No episodes found for {}", series.name);
+            return Ok(None);
+        }
+
+        let mut seasons: Vec<u32> = episodes.iter().map(|e| e.season).collect();
+        seasons.dedup();
+        let next = info.next_unwatched(watched);
+
+        loop {
+            let mut entries: Vec<(String, Option<u32>)> = Vec::new();
+            if let Some(next) = next {
+                entries.push((format!("{}{}", NEXT_UNWATCHED, next.label()), None));
+            }
+            for season in &seasons {
+                let count = episodes.iter().filter(|e| e.season == *season).count();
+                entries.push((
+                    format!("Season {} ({} episodes)", season, count),
+                    Some(*season),
+                ));
+            }
+
+            let season = match self.choose(&series.name, entries)? {
+                None => return Ok(None),
+                Some(None) => return Ok(next.cloned()),
+                Some(Some(season)) => season,
+            };
+
+            // Played episodes are ticked
+            let entries: Vec<(String, &ApiEpisode)> = episodes
+                .iter()
+                .filter(|e| e.season == season)
+                .map(|episode| {
+                    let mark = if watched.contains(&episode.id) {
+                        "✓ "
+                    } else {
+                        ""
+                    };
+                    (format!("{}{}", mark, episode.label()), *episode)
+                })
+                .collect();
+            let prompt = format!("{} - Season {}", series.name, season);
+            if let Some(episode) = self.choose(&prompt, entries)? {
+                return Ok(Some(episode.clone()));
+            }
+        }
+    }
+
+    /// Create, rename and delete favourite groups and move favourites between and within them
+    fn manage_groups(&self, favourites: &[(FavouriteKey, String)]) -> Result<()> {
+        const NEW: &str = "New group";
+        const RENAME: &str = "Rename group";
+        const DELETE: &str = "Delete group";
+        const MOVE_TO: &str = "Move favourite to group";
+        const MOVE_UP: &str = "Move favourite up";
+        const MOVE_DOWN: &str = "Move favourite down";
+
+        let favourites_manager = &self.favourites_manager;
+        let actions: Vec<String> = [NEW, RENAME, DELETE, MOVE_TO, MOVE_UP, MOVE_DOWN]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        let Some(action) = self.menu.select("Manage groups", &actions)? else {
+            return Ok(());
+        };
+
+        let group_names: Vec<String> = favourites_manager
+            .get_groups()?
+            .into_iter()
+            .map(|g| g.name)
+            .collect();
+        let favourite_names: Vec<String> =
+            favourites.iter().map(|(_, name)| name.clone()).collect();
+        let pick_favourite = |prompt: &str| -> Result<Option<&FavouriteKey>> {
+            Ok(self
+                .menu
+                .select_index(prompt, &favourite_names)?
+                .and_then(|index| favourites.get(index))
+                .map(|(key, _)| key))
+        };
+
+        match action.as_str() {
+            NEW => {
+                if let Some(name) = self.menu.select("New group name", &[])? {
+                    favourites_manager.create_group(&name)?;
+                }
+            }
+            RENAME => {
+                if let Some(old_name) = self.menu.select("Rename group", &group_names)?
+                    && let Some(new_name) = self
+                        .menu
+                        .select(&format!("Rename '{}' to", old_name), &[])?
+                {
+                    favourites_manager.rename_group(&old_name, &new_name)?;
+                }
+            }
+            DELETE => {
+                if let Some(name) = self.menu.select("Delete group", &group_names)? {
+                    favourites_manager.delete_group(&name)?;
+                }
+            }
+            MOVE_TO => {
+                if let Some(key) = pick_favourite("Move favourite")?
+                    && let Some(group) = self
+                        .menu
+                        .select("To group (type a new name to create)", &group_names)?
+                {
+                    favourites_manager.move_to_group(key, &group)?;
+                }
+            }
+            MOVE_UP | MOVE_DOWN => {
+                if let Some(key) = pick_favourite(&action)? {
+                    favourites_manager.move_within_group(key, action == MOVE_UP)?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// Ask for the parental PIN; false if cancelled or wrong
+pub fn check_pin(menu: MenuBackend, settings: &ParentalSettings) -> Result<bool> {
+    if settings.pin_hash.is_none() {
+        return Ok(true);
+    }
+
+    let Some(pin) = menu.password("Parental PIN")? else {
+        return Ok(false);
+    };
+    if parental::verify_pin(settings, &pin) {
+        Ok(true)
+    } else {
+        eprintln!("Wrong PIN");
+        Ok(false)
+    }
+}
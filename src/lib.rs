@@ -1,12 +1,16 @@
 // SPDX-License-Identifier: MIT
 // SPDX-FileCopyrightText: (C) 2025 Cranky Kernel <crankykernel@proton.me>
 
+pub mod account;
 pub mod cache;
 pub mod config;
 pub mod favourites;
 pub mod ignore;
+pub mod parental;
 pub mod player;
+pub mod recents;
 pub mod setup;
+pub mod storage;
 pub mod tui;
 pub mod xtream;
 
@@ -18,7 +22,12 @@ pub use xtream::XTreamAPI;
 
 use anyhow::Result;
 
-/// Run the TUI application
-pub async fn run_tui(config: Config, player: Player, provider: Option<String>) -> Result<()> {
-    tui::run_tui(config, player, provider).await
+/// Run the TUI application, reopening the last session if `restore_session` is set
+pub async fn run_tui(
+    config: Config,
+    player: Player,
+    provider: Option<String>,
+    restore_session: bool,
+) -> Result<()> {
+    tui::run_tui(config, player, provider, restore_session).await
 }
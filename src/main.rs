@@ -1,21 +1,27 @@
 // SPDX-License-Identifier: MIT
 // SPDX-FileCopyrightText: (C) 2025 Cranky Kernel <crankykernel@proton.me>
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use clap::builder::styling::{AnsiColor, Effects, Styles};
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use std::fs::File;
 use std::path::PathBuf;
-use std::process::{Command, Stdio};
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::prelude::*;
 
-use iptv::config::ProviderConfig;
+use iptv::parental::ParentalFilter;
 use iptv::xtream::XTreamAPI;
 use iptv::{Config, Player};
 
 mod cli;
-use cli::{CacheCommand, CommandContext, ContentType, OutputFormat, SearchCommand};
+mod launcher;
+use cli::{
+    AccountCommand, CacheCommand, CommandContext, ConflictPolicy, ContentType, DoctorCommand,
+    EpisodeRef, FavouritesCommand, IgnoreCommand, InfoCommand, ListCommand, ListTarget, MoveTo,
+    OutputFormat, ParentalCommand, PatternArgs, PlayCommand, PlayTarget, PlaybackMode,
+    ProviderCommand, SearchCommand, SearchField, StreamFormat,
+};
+use launcher::{Launcher, MenuBackend};
 
 fn cargo_style() -> Styles {
     Styles::styled()
@@ -43,6 +49,10 @@ struct Cli {
     #[arg(short, long, global = true)]
     provider: Option<String>,
 
+    /// Start the TUI fresh instead of reopening the last session
+    #[arg(long, global = true)]
+    fresh: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -56,14 +66,96 @@ enum Commands {
         provider: Option<String>,
     },
 
-    /// Launch rofi menu with favourites
-    Rofi,
+    /// Launch a menu of favourites, recently played and provider categories
+    #[command(visible_alias = "launch")]
+    Rofi {
+        /// Menu program: rofi, dmenu, wofi, fuzzel or fzf (detected if not given)
+        #[arg(long)]
+        menu: Option<String>,
+
+        /// Show adult content hidden by parental controls (asks for the PIN)
+        #[arg(long)]
+        unlock: bool,
+    },
 
     /// Command-line interface for scriptable operations
     Cli(CliCommands),
 
     /// Execute raw API calls
     Api(ApiCommands),
+
+    /// Manage the configuration
+    #[command(subcommand)]
+    Config(ConfigSubCommand),
+
+    /// Check mpv, rofi, the config, storage directories and each provider
+    Doctor {
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigSubCommand {
+    /// Add, remove, rename, test and list providers
+    #[command(subcommand)]
+    Provider(ProviderSubCommand),
+}
+
+#[derive(Subcommand)]
+enum ProviderSubCommand {
+    /// List configured providers
+    List {
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+    /// Add a provider, asking for anything not given, after testing the connection
+    Add {
+        /// Provider name
+        #[arg(short, long)]
+        name: Option<String>,
+        /// Server URL, e.g. http://example.com:8080, or a get.php or player_api.php link
+        /// with the username and password in it
+        #[arg(long)]
+        url: Option<String>,
+        /// Username
+        #[arg(short, long)]
+        username: Option<String>,
+        /// Password
+        #[arg(long)]
+        password: Option<String>,
+        /// Save without testing the connection
+        #[arg(long)]
+        no_test: bool,
+        /// Output format of the connection test (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+    /// Remove a provider
+    Remove {
+        /// Provider name, ID or URL
+        name: String,
+        /// Remove without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Rename a provider, keeping its favourites, cache and ignore rules
+    Rename {
+        /// Provider name, ID or URL
+        name: String,
+        /// New name
+        new_name: String,
+    },
+    /// Check provider URLs and credentials and show account info (all providers unless given)
+    Test {
+        /// Provider name, ID or URL
+        name: Option<String>,
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
 }
 
 #[derive(Parser)]
@@ -73,6 +165,10 @@ struct CliCommands {
     #[arg(short, long)]
     provider: Option<String>,
 
+    /// Show adult content hidden by parental controls (asks for the PIN)
+    #[arg(long)]
+    unlock: bool,
+
     #[command(subcommand)]
     command: CliSubcommands,
 }
@@ -86,7 +182,100 @@ enum CliSubcommands {
         /// Content type to search (live, movie, series)
         #[arg(short = 't', long)]
         r#type: Option<String>,
-        /// Output format (text, json, m3u)
+        /// Only search the category with this name or ID
+        #[arg(long)]
+        category: Option<String>,
+        /// Only match items added since a date (YYYY-MM-DD) or age (12h, 7d, 2w)
+        #[arg(long)]
+        added_since: Option<String>,
+        /// Only match items rated at least this, out of 10
+        #[arg(long)]
+        min_rating: Option<f64>,
+        /// Only match live channels with a catch-up archive
+        #[arg(long)]
+        archive: bool,
+        /// Treat the query as a case-insensitive regular expression
+        #[arg(long)]
+        regex: bool,
+        /// Also match these fields (plot, cast, genre); movies need their info cached
+        #[arg(long = "in", value_delimiter = ',')]
+        fields: Vec<String>,
+        /// Show each channel or film once with the providers that carry it
+        #[arg(long)]
+        group_by_title: bool,
+        /// Output format (text, json, m3u, tsv)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// List categories, channels, movies, series or episodes, hiding ignored ones
+    #[command(subcommand)]
+    List(ListSubCommand),
+
+    /// Play a channel, movie, series episode or favourite
+    ///
+    /// Names match fuzzily unless --exact; "S02E05 of <series>" picks an episode.
+    Play {
+        /// Channel, movie or series name
+        #[arg(required_unless_present_any = ["id", "favourite"])]
+        name: Option<String>,
+        /// Provider ID of the channel, movie or series instead of a name
+        #[arg(long, conflicts_with_all = ["name", "favourite"])]
+        id: Option<u32>,
+        /// Content type to look in (live, movie, series); live for --id
+        #[arg(short = 't', long)]
+        r#type: Option<String>,
+        /// Name of a favourite to play
+        #[arg(long, conflicts_with = "name")]
+        favourite: Option<String>,
+        /// Only accept a name that matches exactly (ignoring case)
+        #[arg(long)]
+        exact: bool,
+        /// Episode of a series, e.g. S02E05 or 2x05 (next unwatched if not given)
+        #[arg(short, long)]
+        episode: Option<String>,
+        /// Live stream format (ts, hls)
+        #[arg(long)]
+        stream_format: Option<String>,
+        /// Playback mode (detached, blocking, terminal, independent)
+        #[arg(short, long, default_value = "detached")]
+        mode: String,
+        /// Print the stream URL instead of playing it
+        #[arg(long)]
+        print_url: bool,
+    },
+
+    /// Show the plot, cast, rating, seasons and stream URLs of a movie or series
+    Info {
+        /// Movie or series name
+        #[arg(required_unless_present = "id")]
+        name: Option<String>,
+        /// Provider ID of the movie or series instead of a name
+        #[arg(long, conflicts_with = "name")]
+        id: Option<u32>,
+        /// Content type to look in (movie, series); movie for --id
+        #[arg(short = 't', long)]
+        r#type: Option<String>,
+        /// Only accept a name that matches exactly (ignoring case)
+        #[arg(long)]
+        exact: bool,
+        /// List every episode of a series with its stream URL
+        #[arg(short, long)]
+        episodes: bool,
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Show subscription status, expiry and connection usage (all providers unless --provider)
+    Account {
+        /// Warn about subscriptions expiring within this many days (default from config)
+        #[arg(long)]
+        warn_days: Option<u32>,
+        /// Exit with an error if any subscription needs attention
+        #[arg(long)]
+        check: bool,
+        /// Output format (text, json)
         #[arg(short, long, default_value = "text")]
         format: String,
     },
@@ -94,6 +283,293 @@ enum CliSubcommands {
     /// Manage cache
     #[command(subcommand)]
     Cache(CacheSubCommand),
+
+    /// Manage favourites
+    #[command(subcommand)]
+    Favourites(FavouritesSubCommand),
+
+    /// Manage ignore rules (for all providers unless --provider)
+    #[command(subcommand)]
+    Ignore(IgnoreSubCommand),
+
+    /// Manage parental controls
+    #[command(subcommand)]
+    Parental(ParentalSubCommand),
+}
+
+#[derive(Subcommand)]
+enum ListSubCommand {
+    /// List categories (of all content types unless --type)
+    Categories {
+        /// Content type (live, movie, series)
+        #[arg(short = 't', long)]
+        r#type: Option<String>,
+        #[command(flatten)]
+        options: ListOptions,
+    },
+    /// List channels and movies (both unless --type)
+    Streams {
+        /// Content type (live, movie)
+        #[arg(short = 't', long)]
+        r#type: Option<String>,
+        /// Category ID or name (case-insensitive)
+        #[arg(short, long)]
+        category: Option<String>,
+        #[command(flatten)]
+        options: ListOptions,
+    },
+    /// List series
+    Series {
+        /// Category ID or name (case-insensitive)
+        #[arg(short, long)]
+        category: Option<String>,
+        #[command(flatten)]
+        options: ListOptions,
+    },
+    /// List the episodes of a series
+    Episodes {
+        /// Series ID or name (case-insensitive)
+        series: String,
+        /// Only list this season
+        #[arg(short, long)]
+        season: Option<u32>,
+        #[command(flatten)]
+        options: ListOptions,
+    },
+}
+
+#[derive(Args)]
+struct ListOptions {
+    /// Only list items added since a date (YYYY-MM-DD) or age (12h, 7d, 2w)
+    #[arg(long)]
+    added_since: Option<String>,
+    /// Output format (text, json, tsv, m3u)
+    #[arg(short, long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Subcommand)]
+enum ParentalSubCommand {
+    /// Show whether parental controls are on and which keywords they use
+    Status,
+    /// Hide adult content everywhere, setting a PIN first if there is none
+    Enable,
+    /// Stop hiding adult content (asks for the PIN)
+    Disable,
+    /// Change the PIN (asks for the current PIN)
+    SetPin,
+    /// Change the keywords that hide matching categories (asks for the PIN)
+    Keywords {
+        /// Keyword to add
+        #[arg(long)]
+        add: Vec<String>,
+        /// Keyword to remove
+        #[arg(long)]
+        remove: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum IgnoreSubCommand {
+    /// List ignore rules (all providers unless --provider)
+    List {
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+    /// Show what a pattern rule would hide without saving it
+    Preview {
+        #[command(flatten)]
+        rule: PatternRuleArgs,
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+    /// Show what a pattern rule would hide, then save it
+    Add {
+        #[command(flatten)]
+        rule: PatternRuleArgs,
+        /// Save without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Remove a pattern rule, or with --exact the rules hiding a name or ID
+    Remove {
+        #[command(flatten)]
+        rule: PatternRuleArgs,
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+}
+
+#[derive(Args)]
+struct PatternRuleArgs {
+    /// Glob pattern matched against the whole name, e.g. "AR |*" (case-insensitive)
+    pattern: String,
+    /// Treat the pattern as a regular expression matched anywhere in the name
+    #[arg(long)]
+    regex: bool,
+    /// Allow-list: hide everything that doesn't match this or another allow rule
+    #[arg(long)]
+    allow: bool,
+    /// Match channel and stream names instead of category names
+    #[arg(long)]
+    channels: bool,
+    /// Only apply to one content type (live, movie, series)
+    #[arg(short = 't', long)]
+    r#type: Option<String>,
+    /// Match one category or channel by its exact name or ID instead of a pattern
+    #[arg(long, conflicts_with_all = ["regex", "allow"])]
+    exact: bool,
+}
+
+impl PatternRuleArgs {
+    fn into_pattern_args(self) -> Result<PatternArgs> {
+        Ok(PatternArgs {
+            pattern: self.pattern,
+            regex: self.regex,
+            allow: self.allow,
+            channels: self.channels,
+            exact: self.exact,
+            content_type: self
+                .r#type
+                .as_deref()
+                .map(ContentType::from_str)
+                .transpose()?,
+        })
+    }
+}
+
+#[derive(Args)]
+struct FavouriteTargetArgs {
+    /// Channel, movie or series name
+    #[arg(required_unless_present = "id")]
+    name: Option<String>,
+    /// Provider ID instead of a name
+    #[arg(long, conflicts_with = "name")]
+    id: Option<u32>,
+    /// Content type (live, movie, series); live for --id
+    #[arg(short = 't', long)]
+    r#type: Option<String>,
+    /// Only accept a name that matches exactly (ignoring case)
+    #[arg(long)]
+    exact: bool,
+}
+
+impl FavouriteTargetArgs {
+    /// Names are looked up in the streams to add a favourite, and in the favourites
+    /// themselves to change one
+    fn into_target(self, favourite: bool) -> Result<PlayTarget> {
+        let content_type = self
+            .r#type
+            .as_deref()
+            .map(ContentType::from_str)
+            .transpose()?;
+        Ok(match (self.id, self.name) {
+            (Some(id), _) => PlayTarget::Id {
+                id,
+                content_type: content_type.unwrap_or(ContentType::Live),
+            },
+            (None, Some(name)) if favourite => PlayTarget::Favourite {
+                name,
+                exact: self.exact,
+            },
+            (None, Some(name)) => PlayTarget::Name {
+                name,
+                content_type,
+                exact: self.exact,
+            },
+            (None, None) => anyhow::bail!("Give a name or --id"),
+        })
+    }
+}
+
+#[derive(Subcommand)]
+enum FavouritesSubCommand {
+    /// List favourites in group order (all providers unless --provider)
+    List {
+        /// Only list this group
+        #[arg(short, long)]
+        group: Option<String>,
+        /// Output format (text, json, tsv)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+    /// Add a channel, movie or series as a favourite
+    Add {
+        #[command(flatten)]
+        target: FavouriteTargetArgs,
+        /// Group to add it to, created if it doesn't exist
+        #[arg(short, long)]
+        group: Option<String>,
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+    /// Remove a favourite
+    Remove {
+        #[command(flatten)]
+        target: FavouriteTargetArgs,
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+    /// Move a favourite within its group or to another group
+    Move {
+        #[command(flatten)]
+        target: FavouriteTargetArgs,
+        /// Move one place up
+        #[arg(long, conflicts_with_all = ["down", "position", "group"])]
+        up: bool,
+        /// Move one place down
+        #[arg(long, conflicts_with_all = ["position", "group"])]
+        down: bool,
+        /// Position in the group, counting from 1
+        #[arg(long)]
+        position: Option<usize>,
+        /// Move to the end of this group (or to --position in it), creating it if needed
+        #[arg(short, long)]
+        group: Option<String>,
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+    /// Export favourites keyed by provider and stream name (all providers unless --provider)
+    Export {
+        /// Output format (json, m3u)
+        #[arg(short, long, default_value = "json")]
+        format: String,
+        /// File to write instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Merge favourites from an exported JSON or M3U file
+    Import {
+        /// File to import
+        file: PathBuf,
+        /// What to do when a favourite already exists (keep, replace)
+        #[arg(long, default_value = "keep")]
+        on_conflict: String,
+    },
+    /// Check favourites against the cached stream lists and find replacements for dead ones
+    Verify {
+        /// Re-match, rename or remove problem favourites
+        #[arg(long)]
+        fix: bool,
+        /// With --fix, take the best match without asking
+        #[arg(long, requires = "fix")]
+        auto: bool,
+        /// Minimum similarity (0.0-1.0) for a stream to be offered as a match
+        #[arg(long, default_value_t = 0.6)]
+        min_score: f64,
+        /// Also look for matches on the other configured providers
+        #[arg(long)]
+        other_providers: bool,
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -111,6 +587,16 @@ struct ApiCommands {
     #[arg(short, long)]
     provider: Option<String>,
 
+    /// Also write the response to the fixtures directory, with the username and password
+    /// redacted, as <provider>/<action>__<key>-<value>....json with one __<key>-<value> per
+    /// parameter, or .txt if the response isn't JSON
+    #[arg(long)]
+    save: bool,
+
+    /// Directory --save writes to
+    #[arg(long, value_name = "DIR", default_value = "fixtures")]
+    fixtures_dir: PathBuf,
+
     #[command(subcommand)]
     command: ApiSubcommand,
 }
@@ -144,240 +630,124 @@ enum ApiSubcommand {
     SeriesInfo { id: u32 },
     /// Get VOD info
     VodInfo { id: u32 },
+    /// Call any API action, e.g. get_short_epg or get_simple_data_table
+    ///
+    /// The response is printed as it is, pretty-printed if it's JSON.
+    Call {
+        /// Action name as passed to player_api.php
+        action: String,
+        /// Extra query parameter as KEY=VALUE, e.g. --param stream_id=123 (repeatable)
+        #[arg(long = "param", value_name = "KEY=VALUE")]
+        params: Vec<String>,
+    },
 }
 
-async fn run_rofi_menu(providers: Vec<ProviderConfig>, player: Player) -> Result<()> {
-    use iptv::favourites::FavouritesManager;
-    use iptv::xtream::FavouriteStream;
-    use std::io::Write;
-
-    if providers.is_empty() {
-        eprintln!("No providers configured. Please check your config file.");
-        return Ok(());
-    }
-
-    // Check if rofi is available
-    if !Command::new("rofi")
-        .arg("--help")
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status()
-        .map(|status| status.success())
-        .unwrap_or(false)
-    {
-        eprintln!("Error: 'rofi' command not found or not working. Please install rofi.");
-        return Ok(());
-    }
-
-    // Collect favourites from all providers with provider info
-    #[derive(Clone)]
-    struct FavouriteWithProvider {
-        favourite: FavouriteStream,
-        provider_name: Option<String>,
-        provider_config: ProviderConfig,
-    }
-
-    let mut all_favourites = Vec::new();
-
-    tracing::debug!("Loading favourites from {} provider(s)...", providers.len());
-
-    for provider in &providers {
-        tracing::debug!(
-            "Connecting to provider: {}",
-            provider.name.as_ref().unwrap_or(&provider.url)
-        );
-
-        let api = XTreamAPI::new_with_id(
-            provider.url.clone(),
-            provider.username.clone(),
-            provider.password.clone(),
-            provider.name.clone(),
-            provider.id.clone(),
-        )?;
-
-        // Get favourites from this provider using the provider hash from the API
-        let favourites_manager = FavouritesManager::new()?;
-
-        let provider_favourites =
-            match tokio::time::timeout(std::time::Duration::from_secs(5), async {
-                favourites_manager.get_favourites(&api.provider_hash)
-            })
-            .await
-            {
-                Ok(Ok(favs)) => {
-                    if !favs.is_empty() {
-                        tracing::debug!(
-                            "Loaded {} favourites from {}",
-                            favs.len(),
-                            provider.name.as_ref().unwrap_or(&provider.url)
-                        );
+async fn run_api_command(
+    provider: &str,
+    api: &mut XTreamAPI,
+    cmd: ApiSubcommand,
+    save: Option<PathBuf>,
+) -> Result<()> {
+    let category = |category: Option<String>| {
+        category
+            .map(|id| vec![("category_id".to_string(), id)])
+            .unwrap_or_default()
+    };
+    let (action, params) = match cmd {
+        ApiSubcommand::UserInfo => ("get_user_info".to_string(), Vec::new()),
+        ApiSubcommand::LiveCategories => ("get_live_categories".to_string(), Vec::new()),
+        ApiSubcommand::VodCategories => ("get_vod_categories".to_string(), Vec::new()),
+        ApiSubcommand::SeriesCategories => ("get_series_categories".to_string(), Vec::new()),
+        ApiSubcommand::LiveStreams { category: c } => ("get_live_streams".to_string(), category(c)),
+        ApiSubcommand::VodStreams { category: c } => ("get_vod_streams".to_string(), category(c)),
+        ApiSubcommand::Series { category: c } => ("get_series".to_string(), category(c)),
+        ApiSubcommand::SeriesInfo { id } => (
+            "get_series_info".to_string(),
+            vec![("series_id".to_string(), id.to_string())],
+        ),
+        ApiSubcommand::VodInfo { id } => (
+            "get_vod_info".to_string(),
+            vec![("vod_id".to_string(), id.to_string())],
+        ),
+        ApiSubcommand::Call { action, params } => {
+            let params = params
+                .iter()
+                .map(|param| {
+                    let (key, value) = param.split_once('=').ok_or_else(|| {
+                        anyhow::anyhow!("Invalid parameter: {}. Use KEY=VALUE", param)
+                    })?;
+                    let key = key.trim();
+                    if matches!(key, "username" | "password" | "action") {
+                        anyhow::bail!("'{}' is set from the provider config", key);
                     }
-                    favs
-                }
-                Ok(Err(e)) => {
-                    tracing::warn!(
-                        "Error loading favourites from {}: {}",
-                        provider.name.as_ref().unwrap_or(&provider.url),
-                        e
-                    );
-                    Vec::new()
-                }
-                Err(_) => {
-                    tracing::warn!(
-                        "Timeout loading favourites from {}",
-                        provider.name.as_ref().unwrap_or(&provider.url)
-                    );
-                    Vec::new()
-                }
-            };
-
-        // Store favourites with their provider info
-        for favourite in provider_favourites {
-            all_favourites.push(FavouriteWithProvider {
-                favourite,
-                provider_name: provider.name.clone(),
-                provider_config: provider.clone(),
-            });
+                    Ok((key.to_string(), value.to_string()))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            (action, params)
         }
-    }
-
-    let favourites = all_favourites;
-
-    if favourites.is_empty() {
-        println!("No favourites found. Use the TUI to add favourites first.");
-        return Ok(());
-    }
-
-    // Prepare rofi input: format favourites for display with provider names
-    let mut rofi_input = String::new();
-    for fav_with_provider in &favourites {
-        // Include provider name for clarity when multiple providers have favourites
-        let provider_name = fav_with_provider
-            .provider_name
-            .as_ref()
-            .map(|name| format!(" [{}]", name))
-            .unwrap_or_default();
-        rofi_input.push_str(&format!(
-            "{}{}\n",
-            fav_with_provider.favourite.name, provider_name
-        ));
-    }
-
-    // Launch rofi to select a favourite
-    let mut rofi_cmd = Command::new("rofi");
-    rofi_cmd
-        .arg("-dmenu")
-        .arg("-hover-select")
-        .arg("-me-select-entry")
-        .arg("")
-        .arg("-me-accept-entry")
-        .arg("MousePrimary")
-        .arg("-i") // case insensitive
-        .arg("-p")
-        .arg("IPTV Favourites")
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped());
-
-    let mut rofi_process = rofi_cmd.spawn()?;
-
-    // Send the favourites to rofi's stdin
-    if let Some(stdin) = rofi_process.stdin.as_mut() {
-        stdin.write_all(rofi_input.as_bytes())?;
-    }
-
-    // Get the selection from rofi
-    let output = rofi_process.wait_with_output()?;
-
-    if !output.status.success() {
-        // User cancelled or error
-        return Ok(());
-    }
-
-    let selected = String::from_utf8_lossy(&output.stdout);
-    let selected = selected.trim();
+    };
 
-    if selected.is_empty() {
-        return Ok(());
+    // Return raw responses without any interpretation or deserialization
+    let body = api.make_request_raw(&action, &params).await?;
+    let json = serde_json::from_str::<serde_json::Value>(&body).ok();
+    match &json {
+        Some(json) => println!("{}", serde_json::to_string_pretty(json)?),
+        None => {
+            eprintln!("Response is not JSON");
+            println!("{}", body);
+        }
     }
 
-    // Find the selected favourite (match the display name with provider suffix)
-    let selected_fav = favourites.iter().find(|fav_with_provider| {
-        let provider_name = fav_with_provider
-            .provider_name
-            .as_ref()
-            .map(|name| format!(" [{}]", name))
-            .unwrap_or_default();
-        let display_name = format!("{}{}", fav_with_provider.favourite.name, provider_name);
-        display_name == selected
-    });
-
-    if let Some(fav_with_provider) = selected_fav {
-        // Create API for the selected provider
-        let api = XTreamAPI::new_with_id(
-            fav_with_provider.provider_config.url.clone(),
-            fav_with_provider.provider_config.username.clone(),
-            fav_with_provider.provider_config.password.clone(),
-            fav_with_provider.provider_config.name.clone(),
-            fav_with_provider.provider_config.id.clone(),
-        )?;
-
-        // Get the stream URL based on stream type
-        let url = match fav_with_provider.favourite.stream_type.as_str() {
-            "live" => api.get_stream_url(fav_with_provider.favourite.stream_id, "live", None),
-            "movie" => api.get_stream_url(fav_with_provider.favourite.stream_id, "movie", None),
-            "series" => {
-                // For series, we'd need to handle episode selection, but for simplicity,
-                // we'll just show an error
-                eprintln!("Series playback not supported in rofi mode. Use TUI mode instead.");
-                return Ok(());
-            }
-            _ => {
-                eprintln!(
-                    "Unknown stream type: {}",
-                    fav_with_provider.favourite.stream_type
-                );
-                return Ok(());
-            }
-        };
-
-        tracing::info!("Starting playback of: {}", fav_with_provider.favourite.name);
-
-        // Play in detached mode so rofi can exit cleanly
-        player.play_detached(&url).await?;
+    if let Some(dir) = save {
+        let path = save_fixture(&dir, provider, &action, &params, api, json, &body)?;
+        eprintln!("Saved response to {}", path.display());
     }
-
     Ok(())
 }
 
-async fn run_api_command(_provider: &str, api: &mut XTreamAPI, cmd: ApiSubcommand) -> Result<()> {
-    // Return raw JSON responses without any interpretation or deserialization
-    let result = match cmd {
-        ApiSubcommand::UserInfo => api.make_request_raw("get_user_info", None).await?,
-        ApiSubcommand::LiveCategories => api.make_request_raw("get_live_categories", None).await?,
-        ApiSubcommand::VodCategories => api.make_request_raw("get_vod_categories", None).await?,
-        ApiSubcommand::SeriesCategories => {
-            api.make_request_raw("get_series_categories", None).await?
-        }
-        ApiSubcommand::LiveStreams { category } => {
-            api.make_request_raw("get_live_streams", category.as_deref())
-                .await?
-        }
-        ApiSubcommand::VodStreams { category } => {
-            api.make_request_raw("get_vod_streams", category.as_deref())
-                .await?
-        }
-        ApiSubcommand::Series { category } => {
-            api.make_request_raw("get_series", category.as_deref())
-                .await?
-        }
-        ApiSubcommand::SeriesInfo { id } => {
-            api.make_info_request_raw("get_series_info", id).await?
+/// Write an API response under `dir`, named after the provider, action and parameters
+fn save_fixture(
+    dir: &std::path::Path,
+    provider: &str,
+    action: &str,
+    params: &[(String, String)],
+    api: &XTreamAPI,
+    json: Option<serde_json::Value>,
+    body: &str,
+) -> Result<PathBuf> {
+    let file_name_part = |s: &str| -> String {
+        s.chars()
+            .map(|c| {
+                if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    };
+    let mut name = file_name_part(action);
+    for (key, value) in params {
+        name.push_str(&format!(
+            "__{}-{}",
+            file_name_part(key),
+            file_name_part(value)
+        ));
+    }
+    let (contents, extension) = match json {
+        Some(mut json) => {
+            api.redact_credentials(&mut json);
+            (serde_json::to_string_pretty(&json)?, "json")
         }
-        ApiSubcommand::VodInfo { id } => api.make_info_request_raw("get_vod_info", id).await?,
+        None => (api.redact_text(body), "txt"),
     };
 
-    println!("{}", serde_json::to_string_pretty(&result)?);
-    Ok(())
+    let dir = dir.join(file_name_part(provider));
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let path = dir.join(format!("{}.{}", name, extension));
+    std::fs::write(&path, contents + "\n")
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
 }
 
 #[tokio::main]
@@ -425,6 +795,14 @@ async fn main() -> Result<()> {
         .map(|p| p.join("iptv").join("config.toml"))
         .unwrap_or_else(|| PathBuf::from("config.toml"));
 
+    // Diagnose the config rather than failing to load it
+    if let Some(Commands::Doctor { format }) = &cli.command {
+        let cmd = DoctorCommand {
+            format: OutputFormat::from_str(format)?,
+        };
+        return cmd.execute(config_path).await;
+    }
+
     let mut config = if config_path.exists() {
         Config::load(&config_path)?
     } else {
@@ -440,6 +818,8 @@ async fn main() -> Result<()> {
                 // Reload the config after setup
                 config = Config::load(&config_path)?;
             }
+            // Providers can be added from here instead
+            Some(Commands::Config(_)) => {}
             _ => {
                 // For other commands, just warn about missing providers
                 if config.providers.is_empty() {
@@ -460,11 +840,11 @@ async fn main() -> Result<()> {
         Some(Commands::Tui { provider }) => {
             // Launch TUI with provider from subcommand or global option
             let provider_to_use = provider.or(cli.provider.clone());
-            iptv::run_tui(config, player, provider_to_use).await?;
+            iptv::run_tui(config, player, provider_to_use, !cli.fresh).await?;
         }
         None => {
             // No command given, launch TUI with global provider option if specified
-            iptv::run_tui(config, player, cli.provider.clone()).await?;
+            iptv::run_tui(config, player, cli.provider.clone(), !cli.fresh).await?;
         }
 
         Some(Commands::Cli(cli_args)) => {
@@ -474,12 +854,22 @@ async fn main() -> Result<()> {
                 .or_else(|| std::env::var("IPTV_PROVIDER").ok());
 
             // Create command context
-            let context = CommandContext::new(config.providers.clone(), selected_provider, false);
+            let parental =
+                cli::parental::session_filter(&config.settings.parental, cli_args.unlock)?;
+            let context = CommandContext::new(config.providers.clone(), selected_provider, false)
+                .with_parental(parental);
 
             match cli_args.command {
                 CliSubcommands::Search {
                     query,
                     r#type,
+                    category,
+                    added_since,
+                    min_rating,
+                    archive,
+                    regex,
+                    fields,
+                    group_by_title,
                     format,
                 } => {
                     let content_type = r#type.map(|t| ContentType::from_str(&t)).transpose()?;
@@ -487,11 +877,179 @@ async fn main() -> Result<()> {
                     let cmd = SearchCommand {
                         query,
                         content_type,
+                        category,
+                        added_since: added_since
+                            .as_deref()
+                            .map(cli::list::parse_added_since)
+                            .transpose()?,
+                        min_rating,
+                        archive,
+                        regex,
+                        fields: fields
+                            .iter()
+                            .map(|f| SearchField::from_str(f))
+                            .collect::<Result<_>>()?,
+                        group_by_title,
                         format: output_format,
                     };
                     cmd.execute(context).await?;
                 }
 
+                CliSubcommands::List(list_cmd) => {
+                    let (target, options) = match list_cmd {
+                        ListSubCommand::Categories { r#type, options } => (
+                            ListTarget::Categories {
+                                content_type: r#type
+                                    .map(|t| ContentType::from_str(&t))
+                                    .transpose()?,
+                            },
+                            options,
+                        ),
+                        ListSubCommand::Streams {
+                            r#type,
+                            category,
+                            options,
+                        } => {
+                            let content_types = match r#type {
+                                Some(t) => match ContentType::from_str(&t)? {
+                                    ContentType::Series => {
+                                        anyhow::bail!("Use 'iptv cli list series' to list series")
+                                    }
+                                    content_type => vec![content_type],
+                                },
+                                None => vec![ContentType::Live, ContentType::Movie],
+                            };
+                            (
+                                ListTarget::Streams {
+                                    content_types,
+                                    category,
+                                },
+                                options,
+                            )
+                        }
+                        ListSubCommand::Series { category, options } => (
+                            ListTarget::Streams {
+                                content_types: vec![ContentType::Series],
+                                category,
+                            },
+                            options,
+                        ),
+                        ListSubCommand::Episodes {
+                            series,
+                            season,
+                            options,
+                        } => (ListTarget::Episodes { series, season }, options),
+                    };
+                    let cmd = ListCommand {
+                        target,
+                        added_since: options
+                            .added_since
+                            .as_deref()
+                            .map(cli::list::parse_added_since)
+                            .transpose()?,
+                        format: OutputFormat::from_str(&options.format)?,
+                    };
+                    cmd.execute(context).await?;
+                }
+
+                CliSubcommands::Play {
+                    name,
+                    id,
+                    r#type,
+                    favourite,
+                    exact,
+                    episode,
+                    stream_format,
+                    mode,
+                    print_url,
+                } => {
+                    let content_type = r#type.map(|t| ContentType::from_str(&t)).transpose()?;
+                    let mut episode = episode.map(|e| EpisodeRef::from_str(&e)).transpose()?;
+                    let target = match (id, favourite, name) {
+                        (Some(id), _, _) => PlayTarget::Id {
+                            id,
+                            content_type: content_type.unwrap_or(ContentType::Live),
+                        },
+                        (None, Some(name), _) => PlayTarget::Favourite { name, exact },
+                        (None, None, Some(name)) => {
+                            // "S02E05 of <series>" names the episode in the query
+                            let phrase = EpisodeRef::split_phrase(&name)
+                                .filter(|_| episode.is_none())
+                                .filter(|_| {
+                                    matches!(content_type, None | Some(ContentType::Series))
+                                });
+                            match phrase {
+                                Some((phrase_episode, series)) => {
+                                    episode = Some(phrase_episode);
+                                    PlayTarget::Name {
+                                        name: series,
+                                        content_type: Some(ContentType::Series),
+                                        exact,
+                                    }
+                                }
+                                None => PlayTarget::Name {
+                                    name,
+                                    content_type,
+                                    exact,
+                                },
+                            }
+                        }
+                        (None, None, None) => anyhow::bail!("Give a name, --id or --favourite"),
+                    };
+                    let cmd = PlayCommand {
+                        target,
+                        episode,
+                        format: stream_format
+                            .map(|f| StreamFormat::from_str(&f))
+                            .transpose()?,
+                        mode: PlaybackMode::from_str(&mode)?,
+                        print_url,
+                    };
+                    cmd.execute(context, player).await?;
+                }
+
+                CliSubcommands::Info {
+                    name,
+                    id,
+                    r#type,
+                    exact,
+                    episodes,
+                    format,
+                } => {
+                    let content_type = r#type.map(|t| ContentType::from_str(&t)).transpose()?;
+                    let target = match (id, name) {
+                        (Some(id), _) => PlayTarget::Id {
+                            id,
+                            content_type: content_type.unwrap_or(ContentType::Movie),
+                        },
+                        (None, Some(name)) => PlayTarget::Name {
+                            name,
+                            content_type,
+                            exact,
+                        },
+                        (None, None) => anyhow::bail!("Give a name or --id"),
+                    };
+                    let cmd = InfoCommand {
+                        target,
+                        episodes,
+                        format: OutputFormat::from_str(&format)?,
+                    };
+                    cmd.execute(context).await?;
+                }
+
+                CliSubcommands::Account {
+                    warn_days,
+                    check,
+                    format,
+                } => {
+                    let cmd = AccountCommand {
+                        warning_days: warn_days.unwrap_or(config.settings.expiry_warning_days),
+                        check,
+                        format: OutputFormat::from_str(&format)?,
+                    };
+                    cmd.execute(context).await?;
+                }
+
                 CliSubcommands::Cache(cache_cmd) => {
                     let cmd = match cache_cmd {
                         CacheSubCommand::Refresh => CacheCommand::Refresh,
@@ -499,11 +1057,166 @@ async fn main() -> Result<()> {
                     };
                     cmd.execute(context).await?;
                 }
+
+                CliSubcommands::Favourites(favourites_cmd) => {
+                    let cmd = match favourites_cmd {
+                        FavouritesSubCommand::List { group, format } => FavouritesCommand::List {
+                            group,
+                            format: OutputFormat::from_str(&format)?,
+                        },
+                        FavouritesSubCommand::Add {
+                            target,
+                            group,
+                            format,
+                        } => FavouritesCommand::Add {
+                            target: target.into_target(false)?,
+                            group,
+                            format: OutputFormat::from_str(&format)?,
+                        },
+                        FavouritesSubCommand::Remove { target, format } => {
+                            FavouritesCommand::Remove {
+                                target: target.into_target(true)?,
+                                format: OutputFormat::from_str(&format)?,
+                            }
+                        }
+                        FavouritesSubCommand::Move {
+                            target,
+                            up,
+                            down,
+                            position,
+                            group,
+                            format,
+                        } => FavouritesCommand::Move {
+                            target: target.into_target(true)?,
+                            to: match (up, down, position, group) {
+                                (true, ..) => MoveTo::Up,
+                                (_, true, ..) => MoveTo::Down,
+                                (_, _, Some(position), group) => {
+                                    MoveTo::Position { position, group }
+                                }
+                                (_, _, None, Some(group)) => MoveTo::Group(group),
+                                _ => anyhow::bail!("Give --up, --down, --position or --group"),
+                            },
+                            format: OutputFormat::from_str(&format)?,
+                        },
+                        FavouritesSubCommand::Export { format, output } => {
+                            FavouritesCommand::Export {
+                                format: OutputFormat::from_str(&format)?,
+                                output,
+                            }
+                        }
+                        FavouritesSubCommand::Import { file, on_conflict } => {
+                            FavouritesCommand::Import {
+                                input: file,
+                                on_conflict: ConflictPolicy::from_str(&on_conflict)?,
+                            }
+                        }
+                        FavouritesSubCommand::Verify {
+                            fix,
+                            auto,
+                            min_score,
+                            other_providers,
+                            format,
+                        } => FavouritesCommand::Verify {
+                            fix,
+                            auto,
+                            min_score,
+                            other_providers,
+                            format: OutputFormat::from_str(&format)?,
+                        },
+                    };
+                    cmd.execute(context).await?;
+                }
+
+                CliSubcommands::Ignore(ignore_cmd) => {
+                    let cmd = match ignore_cmd {
+                        IgnoreSubCommand::List { format } => IgnoreCommand::List {
+                            format: OutputFormat::from_str(&format)?,
+                        },
+                        IgnoreSubCommand::Preview { rule, format } => IgnoreCommand::Preview {
+                            rule: rule.into_pattern_args()?,
+                            format: OutputFormat::from_str(&format)?,
+                        },
+                        IgnoreSubCommand::Add { rule, yes } => IgnoreCommand::Add {
+                            rule: rule.into_pattern_args()?,
+                            yes,
+                        },
+                        IgnoreSubCommand::Remove { rule, format } => IgnoreCommand::Remove {
+                            rule: rule.into_pattern_args()?,
+                            format: OutputFormat::from_str(&format)?,
+                        },
+                    };
+                    cmd.execute(context).await?;
+                }
+
+                CliSubcommands::Parental(parental_cmd) => {
+                    let cmd = match parental_cmd {
+                        ParentalSubCommand::Status => ParentalCommand::Status,
+                        ParentalSubCommand::Enable => ParentalCommand::Enable,
+                        ParentalSubCommand::Disable => ParentalCommand::Disable,
+                        ParentalSubCommand::SetPin => ParentalCommand::SetPin,
+                        ParentalSubCommand::Keywords { add, remove } => {
+                            ParentalCommand::Keywords { add, remove }
+                        }
+                    };
+                    cmd.execute(config, config_path)?;
+                }
             }
         }
 
-        Some(Commands::Rofi) => {
-            run_rofi_menu(config.providers, player).await?;
+        Some(Commands::Rofi { menu, unlock }) => {
+            let backend = match menu {
+                Some(menu) => MenuBackend::from_str(&menu)?,
+                None => MenuBackend::detect()?,
+            };
+            if !backend.is_installed() {
+                anyhow::bail!("'{}' not found. Please install it.", backend.binary());
+            }
+
+            let settings = &config.settings.parental;
+            if unlock && settings.enabled && !launcher::check_pin(backend, settings)? {
+                return Ok(());
+            }
+            let parental = ParentalFilter::from_settings(settings, unlock);
+            Launcher::new(backend, config.providers, player, parental)
+                .await?
+                .run()
+                .await?;
+        }
+
+        // Run before the config is loaded
+        Some(Commands::Doctor { .. }) => {}
+
+        Some(Commands::Config(ConfigSubCommand::Provider(provider_cmd))) => {
+            let cmd = match provider_cmd {
+                ProviderSubCommand::List { format } => ProviderCommand::List {
+                    format: OutputFormat::from_str(&format)?,
+                },
+                ProviderSubCommand::Add {
+                    name,
+                    url,
+                    username,
+                    password,
+                    no_test,
+                    format,
+                } => ProviderCommand::Add {
+                    name,
+                    url,
+                    username,
+                    password,
+                    no_test,
+                    format: OutputFormat::from_str(&format)?,
+                },
+                ProviderSubCommand::Remove { name, yes } => ProviderCommand::Remove { name, yes },
+                ProviderSubCommand::Rename { name, new_name } => {
+                    ProviderCommand::Rename { name, new_name }
+                }
+                ProviderSubCommand::Test { name, format } => ProviderCommand::Test {
+                    name,
+                    format: OutputFormat::from_str(&format)?,
+                },
+            };
+            cmd.execute(config, config_path).await?;
         }
 
         Some(Commands::Api(api_cmds)) => {
@@ -515,7 +1228,13 @@ async fn main() -> Result<()> {
 
             let (mut api, provider_name) = context.get_single_provider().await?;
             eprintln!("Using provider: {}", provider_name);
-            run_api_command(&provider_name, &mut api, api_cmds.command).await?;
+            run_api_command(
+                &provider_name,
+                &mut api,
+                api_cmds.command,
+                api_cmds.save.then_some(api_cmds.fixtures_dir),
+            )
+            .await?;
         }
     }
 
@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: (C) 2025 Cranky Kernel <crankykernel@proton.me>
+
+use crate::config::ParentalSettings;
+use crate::xtream::Category;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Shortest PIN accepted when setting one
+pub const MIN_PIN_LENGTH: usize = 4;
+
+/// Hides adult-flagged streams and categories whose names contain one of the keywords
+#[derive(Debug, Clone, Default)]
+pub struct ParentalFilter {
+    keywords: Vec<String>,
+}
+
+impl ParentalFilter {
+    /// The filter to apply, or None when parental controls are off or unlocked for this session
+    pub fn from_settings(settings: &ParentalSettings, unlocked: bool) -> Option<Self> {
+        if !settings.enabled || unlocked {
+            return None;
+        }
+        Some(Self {
+            keywords: settings
+                .keywords
+                .iter()
+                .map(|k| k.trim().to_lowercase())
+                .filter(|k| !k.is_empty())
+                .collect(),
+        })
+    }
+
+    pub fn hides_category(&self, category: &Category) -> bool {
+        let name = category.category_name.to_lowercase();
+        self.keywords
+            .iter()
+            .any(|keyword| contains_word(&name, keyword))
+    }
+}
+
+/// Whether `word` occurs in `text` with no letter or digit directly on either side
+fn contains_word(text: &str, word: &str) -> bool {
+    text.match_indices(word).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + word.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Whether a provider's `is_adult` value marks the item as adult content
+pub fn is_adult(value: Option<&Value>) -> bool {
+    match value {
+        Some(Value::Bool(b)) => *b,
+        Some(Value::Number(n)) => n.as_i64().is_some_and(|n| n != 0),
+        Some(Value::String(s)) => matches!(s.trim(), "1" | "true" | "yes"),
+        _ => false,
+    }
+}
+
+/// Salted SHA-256 of a PIN, stored as `sha256$<salt>$<hash>`
+pub fn hash_pin(pin: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos()
+            .to_le_bytes(),
+    );
+    hasher.update(std::process::id().to_le_bytes());
+    let salt = format!("{:x}", hasher.finalize())[..16].to_string();
+    format!("sha256${}${}", salt, digest(&salt, pin))
+}
+
+/// Check a PIN against the stored hash; with no PIN set nothing is locked
+pub fn verify_pin(settings: &ParentalSettings, pin: &str) -> bool {
+    let Some(stored) = &settings.pin_hash else {
+        return true;
+    };
+    match stored.split('$').collect::<Vec<_>>().as_slice() {
+        ["sha256", salt, hash] => digest(salt, pin) == *hash,
+        _ => false,
+    }
+}
+
+fn digest(salt: &str, pin: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(pin.trim().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
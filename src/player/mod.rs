@@ -58,6 +58,12 @@ impl Player {
             .unwrap_or(false)
     }
 
+    /// MPV IPC sockets left in the state directory, with whether an MPV instance still
+    /// answers on each
+    pub fn mpv_sockets() -> Vec<(std::path::PathBuf, bool)> {
+        MpvPlayer::sockets()
+    }
+
     pub fn is_available(&self) -> bool {
         self.use_mpv
     }
@@ -139,6 +139,33 @@ impl MpvPlayer {
         iptv_dir.join(socket_name)
     }
 
+    /// IPC sockets in the socket directory, and whether an MPV instance still answers on
+    /// each; ones nobody answers on are left over from MPV instances that didn't exit cleanly
+    pub(super) fn sockets() -> Vec<(PathBuf, bool)> {
+        let shared = Self::get_socket_path(false);
+        let Some(dir) = shared.parent() else {
+            return Vec::new();
+        };
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut sockets: Vec<(PathBuf, bool)> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("mpv") && name.ends_with(".sock"))
+            })
+            .map(|path| {
+                let live = UnixStream::connect(&path).is_ok();
+                (path, live)
+            })
+            .collect();
+        sockets.sort();
+        sockets
+    }
+
     /// Try to connect to an existing MPV instance
     pub(super) async fn try_connect_existing() -> Option<Self> {
         let socket_path = Self::get_socket_path(false);
@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: (C) 2025 Cranky Kernel <crankykernel@proton.me>
+
+use crate::config::Config;
+use crate::favourites::FavouriteKey;
+use crate::storage;
+use crate::xtream::FavouriteStream;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Number of recently played items kept
+pub const MAX_RECENTS: usize = 20;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RecentsData {
+    recents: Vec<FavouriteStream>,
+}
+
+/// Recently played streams and episodes, newest first
+///
+/// Items are stored in the same shape as favourites so they can be played the same way;
+/// `added_date` is when the item was last played.
+#[derive(Debug)]
+pub struct RecentsManager {
+    path: PathBuf,
+}
+
+impl RecentsManager {
+    pub fn new() -> Result<Self> {
+        let config_dir = Config::ensure_config_dir()?;
+        Ok(Self {
+            path: config_dir.join("recents.json"),
+        })
+    }
+
+    pub fn get_recents(&self) -> Result<Vec<FavouriteStream>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read recents file: {}", self.path.display()))?;
+        let data: RecentsData =
+            serde_json::from_str(&content).with_context(|| "Failed to parse recents JSON")?;
+        Ok(data.recents)
+    }
+
+    /// Move an item to the front of the list, dropping the oldest beyond `MAX_RECENTS`
+    pub fn record(&self, mut item: FavouriteStream) -> Result<()> {
+        let _lock = storage::lock(&self.path)?;
+
+        let key = FavouriteKey::of(&item);
+        let mut recents = self.get_recents().unwrap_or_default();
+        recents.retain(|r| FavouriteKey::of(r) != key);
+        item.added_date = chrono::Utc::now();
+        item.watched_episodes.clear();
+        recents.insert(0, item);
+        recents.truncate(MAX_RECENTS);
+
+        let content = serde_json::to_string_pretty(&RecentsData { recents })
+            .with_context(|| "Failed to serialize recents")?;
+        storage::write_atomic(&self.path, content)
+            .with_context(|| format!("Failed to write recents file: {}", self.path.display()))
+    }
+}
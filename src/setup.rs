@@ -1,9 +1,12 @@
 use anyhow::Result;
+use chrono::{DateTime, Local, Utc};
 use inquire::validator::Validation;
 use inquire::{Confirm, Text};
 use std::path::Path;
 
+use crate::account::AccountStatus;
 use crate::config::{Config, ProviderConfig, Settings};
+use crate::xtream::{UserInfoResponse, XTreamAPI};
 
 pub async fn interactive_provider_setup() -> Result<()> {
     println!("\n🚀 Welcome to IPTV! Let's set up your first provider.\n");
@@ -62,6 +65,40 @@ pub async fn interactive_provider_setup() -> Result<()> {
 }
 
 async fn prompt_for_provider() -> Result<ProviderConfig> {
+    loop {
+        let provider = prompt_for_provider_details()?;
+
+        println!("\nTesting connection...");
+        match check_provider(&provider).await {
+            Ok(response) => {
+                println!("✅ Connection successful!\n");
+                for line in account_lines(&response, Settings::default().expiry_warning_days) {
+                    println!("  {}", line);
+                }
+                println!();
+                if Confirm::new("Save this provider?")
+                    .with_default(true)
+                    .prompt()?
+                {
+                    return Ok(provider);
+                }
+            }
+            Err(e) => {
+                println!("⚠️  Warning: Could not verify connection: {:#}", e);
+                if Confirm::new("Save this provider anyway?")
+                    .with_help_message("You may need to check your credentials")
+                    .with_default(false)
+                    .prompt()?
+                {
+                    return Ok(provider);
+                }
+            }
+        }
+        println!("\nLet's try again.");
+    }
+}
+
+fn prompt_for_provider_details() -> Result<ProviderConfig> {
     println!("\n📝 Provider Configuration");
     println!("━━━━━━━━━━━━━━━━━━━━━━━━");
 
@@ -69,77 +106,234 @@ async fn prompt_for_provider() -> Result<ProviderConfig> {
         .with_help_message("A friendly name for this provider")
         .prompt_skippable()?;
 
-    let url = Text::new("Server URL:")
-        .with_help_message("e.g., https://your-server.com:port/player_api.php")
-        .with_validator(|input: &str| {
-            if input.is_empty() {
-                Ok(Validation::Invalid("Server URL is required".into()))
-            } else if !input.starts_with("http://") && !input.starts_with("https://") {
-                Ok(Validation::Invalid(
-                    "URL must start with http:// or https://".into(),
-                ))
-            } else {
-                Ok(Validation::Valid)
-            }
-        })
-        .prompt()?;
-
-    let username = Text::new("Username:")
-        .with_validator(|input: &str| {
-            if input.is_empty() {
-                Ok(Validation::Invalid("Username is required".into()))
-            } else {
-                Ok(Validation::Valid)
-            }
-        })
-        .prompt()?;
-
-    let password = Text::new("Password:")
-        .with_validator(|input: &str| {
-            if input.is_empty() {
-                Ok(Validation::Invalid("Password is required".into()))
-            } else {
-                Ok(Validation::Valid)
-            }
+    let url = Text::new("Server URL or link:")
+        .with_help_message(
+            "e.g., https://your-server.com:port, or the get.php or player_api.php link from your provider",
+        )
+        .with_validator(|input: &str| match parse_provider_link(input.trim()) {
+            Ok(_) => Ok(Validation::Valid),
+            Err(e) => Ok(Validation::Invalid(e.to_string().into())),
         })
         .prompt()?;
-
-    println!("\nTesting connection...");
-
-    if let Err(e) = test_provider_connection(&url, &username, &password).await {
-        println!("⚠️  Warning: Could not verify connection: {}", e);
+    let link = parse_provider_link(url.trim())?;
+    if let Some(username) = &link.username {
         println!(
-            "    The provider will be saved anyway, but you may need to check your credentials."
+            "Found the server {} and user {} in the link",
+            link.server, username
         );
-    } else {
-        println!("✅ Connection successful!");
     }
 
+    let username = match link.username {
+        Some(username) => username,
+        None => Text::new("Username:")
+            .with_validator(|input: &str| {
+                if input.is_empty() {
+                    Ok(Validation::Invalid("Username is required".into()))
+                } else {
+                    Ok(Validation::Valid)
+                }
+            })
+            .prompt()?,
+    };
+
+    let password = match link.password {
+        Some(password) => password,
+        None => Text::new("Password:")
+            .with_validator(|input: &str| {
+                if input.is_empty() {
+                    Ok(Validation::Invalid("Password is required".into()))
+                } else {
+                    Ok(Validation::Valid)
+                }
+            })
+            .prompt()?,
+    };
+
     Ok(ProviderConfig {
         id: None,
         name,
-        url,
+        url: link.server,
         username,
         password,
     })
 }
 
-async fn test_provider_connection(url: &str, username: &str, password: &str) -> Result<()> {
-    use crate::xtream::XTreamAPI;
+/// Server URL and credentials taken from what was pasted as the server URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderLink {
+    pub server: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
 
-    let mut api = XTreamAPI::new(
-        url.to_string(),
-        username.to_string(),
-        password.to_string(),
-        None,
+/// Split a link such as `http://host:8080/get.php?username=u&password=p&type=m3u_plus` or a
+/// `player_api.php` URL with credentials into the server and the credentials. Plain server
+/// URLs are returned as they are, without credentials.
+pub fn parse_provider_link(input: &str) -> Result<ProviderLink> {
+    validate_url(input).map_err(anyhow::Error::msg)?;
+    let url = reqwest::Url::parse(input)?;
+
+    let query = |key: &str| {
+        url.query_pairs()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.into_owned())
+            .filter(|v| !v.is_empty())
+    };
+    let username = query("username");
+    let password = query("password");
+    if username.is_none() && password.is_none() {
+        return Ok(ProviderLink {
+            server: input.to_string(),
+            username: None,
+            password: None,
+        });
+    }
+
+    // The API client only uses the scheme, host and port
+    let host = url.host_str().unwrap_or("localhost");
+    let server = match url.port() {
+        Some(port) => format!("{}://{}:{}", url.scheme(), host, port),
+        None => format!("{}://{}", url.scheme(), host),
+    };
+    Ok(ProviderLink {
+        server,
+        username,
+        password,
+    })
+}
+
+/// Lines describing a provider's account and server, as shown after testing a connection
+pub fn account_lines(response: &UserInfoResponse, warning_days: u32) -> Vec<String> {
+    let now = Utc::now();
+    let user_info = &response.user_info;
+    let server_info = &response.server_info;
+    let account = AccountStatus::from_user_info(user_info, now);
+    let date = |time: DateTime<Utc>| time.with_timezone(&Local).format("%Y-%m-%d").to_string();
+
+    let mut status = account.status.clone();
+    if account.is_trial {
+        status.push_str(" (trial)");
+    }
+    let mut lines = vec![format!("Status:      {}", status)];
+    lines.push(match (account.expires_at, account.days_left) {
+        (Some(expires), Some(days)) if days >= 0 => {
+            format!("Expires:     {} ({} days left)", date(expires), days)
+        }
+        (Some(expires), _) => format!("Expires:     {}", date(expires)),
+        _ => "Expires:     Never".to_string(),
+    });
+    lines.push(
+        match (account.active_connections, account.max_connections) {
+            (Some(active), Some(max)) => format!("Connections: {} of {} in use", active, max),
+            (Some(active), None) => format!("Connections: {} in use, no limit", active),
+            _ => "Connections: unknown".to_string(),
+        },
+    );
+    if let Some(created) = user_info
+        .created_at
+        .as_deref()
+        .and_then(|c| c.trim().parse::<i64>().ok())
+        .and_then(|c| DateTime::from_timestamp(c, 0))
+    {
+        lines.push(format!("Created:     {}", date(created)));
+    }
+    if !user_info.message.trim().is_empty() {
+        lines.push(format!("Message:     {}", user_info.message.trim()));
+    }
+
+    let port = if server_info.server_protocol == "https" {
+        &server_info.https_port
+    } else {
+        &server_info.port
+    };
+    lines.push(format!(
+        "Server:      {}://{}:{}",
+        server_info.server_protocol, server_info.url, port
+    ));
+    lines.push(format!(
+        "Server time: {} ({})",
+        server_info.time_now, server_info.timezone
+    ));
+
+    for alert in account.alerts(warning_days, now) {
+        lines.push(format!("⚠ {}", alert));
+    }
+    lines
+}
+
+/// Check a server URL is given and is http or https
+pub fn validate_url(url: &str) -> std::result::Result<(), String> {
+    if url.is_empty() {
+        Err("Server URL is required".to_string())
+    } else if !url.starts_with("http://") && !url.starts_with("https://") {
+        Err("URL must start with http:// or https://".to_string())
+    } else if reqwest::Url::parse(url).is_err() {
+        Err(format!("Invalid URL: {}", url))
+    } else {
+        Ok(())
+    }
+}
+
+/// Check a provider's settings before saving it alongside `others`, the other configured
+/// providers
+pub fn validate_provider(provider: &ProviderConfig, others: &[ProviderConfig]) -> Result<()> {
+    validate_url(&provider.url).map_err(anyhow::Error::msg)?;
+    if provider.username.is_empty() {
+        anyhow::bail!("Username is required");
+    }
+    if provider.password.is_empty() {
+        anyhow::bail!("Password is required");
+    }
+    if let Some(name) = &provider.name {
+        if name.trim().is_empty() {
+            anyhow::bail!("Provider name cannot be blank");
+        }
+        if others.iter().any(|p| {
+            p.name
+                .as_ref()
+                .is_some_and(|n| n.eq_ignore_ascii_case(name))
+        }) {
+            anyhow::bail!("A provider named '{}' already exists", name);
+        }
+    }
+    let identifier = provider.identifier();
+    if others.iter().any(|p| p.identifier() == identifier) {
+        anyhow::bail!(
+            "Another provider already uses '{}' for its favourites and cache; give this one a name",
+            identifier
+        );
+    }
+    Ok(())
+}
+
+/// Check a provider's URL and credentials by fetching its account and server info
+pub async fn check_provider(provider: &ProviderConfig) -> Result<UserInfoResponse> {
+    let mut api = XTreamAPI::new_with_id(
+        provider.url.clone(),
+        provider.username.clone(),
+        provider.password.clone(),
+        provider.name.clone(),
+        provider.id.clone(),
     )?;
+    api.disable_progress();
 
-    // Try to refresh cache - this will fetch categories which most providers support
-    match tokio::time::timeout(std::time::Duration::from_secs(10), api.refresh_cache()).await {
-        Ok(Ok(_)) => Ok(()),
-        Ok(Err(e)) => Err(anyhow::anyhow!("Failed to connect: {}", e)),
-        Err(_) => Err(anyhow::anyhow!("Connection timeout")),
+    let response =
+        match tokio::time::timeout(std::time::Duration::from_secs(10), api.fetch_account_info())
+            .await
+        {
+            Ok(Ok(response)) => response,
+            // The request URL in the error carries the credentials, and doctor's report is
+            // meant to be shared
+            Ok(Err(e)) => {
+                let error = api.redact_text(&format!("{:#}", e));
+                anyhow::bail!("Failed to connect: {}", error)
+            }
+            Err(_) => anyhow::bail!("Connection timeout"),
+        };
+    if response.user_info.auth == 0 {
+        anyhow::bail!("The provider rejected the username or password");
     }
+    Ok(response)
 }
 
 fn save_config(config: &Config) -> Result<()> {
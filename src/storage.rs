@@ -0,0 +1,106 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: (C) 2025 Cranky Kernel <crankykernel@proton.me>
+
+//! Crash- and concurrency-safe file writes
+//!
+//! The TUI, the launcher and CLI commands can run at the same time and all write the same
+//! favourites, ignore and cache files. Files are written to a temporary file and renamed over
+//! the original so readers never see a partial file, and read-modify-write updates hold an
+//! advisory lock on a `.lock` file next to the data file so concurrent updates aren't lost.
+
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// An exclusive advisory lock, released when dropped
+#[derive(Debug)]
+pub struct FileLock {
+    _file: File,
+}
+
+/// Lock `path` for a read-modify-write, waiting for other processes holding the lock
+///
+/// The lock is taken on `<path>.lock` rather than the file itself, as the file is replaced
+/// on every write.
+pub fn lock(path: &Path) -> Result<FileLock> {
+    let lock_path = sibling(path, ".lock");
+    if let Some(parent) = lock_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open lock file: {}", lock_path.display()))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::io::AsRawFd;
+        loop {
+            if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } == 0 {
+                break;
+            }
+            let error = std::io::Error::last_os_error();
+            if error.kind() != std::io::ErrorKind::Interrupted {
+                return Err(error)
+                    .with_context(|| format!("Failed to lock file: {}", lock_path.display()));
+            }
+        }
+    }
+
+    Ok(FileLock { _file: file })
+}
+
+/// Replace the contents of `path` by writing a temporary file and renaming it into place
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let temp_path = sibling(path, &format!(".{}.tmp", std::process::id()));
+    let result = (|| -> Result<()> {
+        let mut file = File::create(&temp_path)
+            .with_context(|| format!("Failed to create file: {}", temp_path.display()))?;
+        file.write_all(contents.as_ref())
+            .with_context(|| format!("Failed to write file: {}", temp_path.display()))?;
+        file.sync_all()
+            .with_context(|| format!("Failed to sync file: {}", temp_path.display()))?;
+        fs::rename(&temp_path, path).with_context(|| {
+            format!(
+                "Failed to rename {} to {}",
+                temp_path.display(),
+                path.display()
+            )
+        })
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Lock `path` and replace its contents atomically
+pub fn write_locked(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    let _lock = lock(path)?;
+    write_atomic(path, contents)
+}
+
+/// Keep a copy of a file that failed to parse before it is overwritten
+///
+/// Returns the path of the copy.
+pub fn preserve_corrupt(path: &Path) -> Result<PathBuf> {
+    let backup = sibling(
+        path,
+        &format!(".corrupt-{}", chrono::Utc::now().format("%Y%m%d%H%M%S")),
+    );
+    fs::copy(path, &backup)
+        .with_context(|| format!("Failed to copy {} to {}", path.display(), backup.display()))?;
+    Ok(backup)
+}
+
+/// `path` with a suffix appended to its file name
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
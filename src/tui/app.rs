@@ -2,14 +2,23 @@
 // SPDX-FileCopyrightText: (C) 2025 Cranky Kernel <crankykernel@proton.me>
 
 // ContentType moved to commands module, but we'll define it locally for now
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ContentType {
     Live,
     Movies,
     Series,
 }
 
-impl ContentType {}
+impl ContentType {
+    /// The provider's stream type for this content
+    pub fn stream_type(self) -> &'static str {
+        match self {
+            ContentType::Live => "live",
+            ContentType::Movies => "movie",
+            ContentType::Series => "series",
+        }
+    }
+}
 
 impl std::fmt::Display for ContentType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -20,14 +29,88 @@ impl std::fmt::Display for ContentType {
         }
     }
 }
+use super::session::SessionState;
+use super::widgets::get_help_lines;
+use crate::account::{AccountAlert, AccountStatus};
 use crate::config::ProviderConfig;
-use crate::ignore::IgnoreConfig;
+use crate::favourites::{FavouriteGroup, FavouriteKey, FavouritesIndex, FavouritesManager};
+use crate::ignore::{IgnoreConfig, IgnoreKind, IgnoreMatcher, IgnoreRule, PatternRule};
+use crate::parental::{self, ParentalFilter};
 use crate::player::{MpvPlaybackStatus, Player};
-use crate::xtream::{ApiEpisode, Category, FavouriteStream, Stream, VodInfoResponse, XTreamAPI};
+use crate::xtream::{
+    ApiEpisode, Category, FavouriteStream, Stream, UserInfo, UserInfoResponse, VodInfoResponse,
+    XTreamAPI,
+};
 use chrono::{DateTime, Local};
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use std::collections::HashMap;
-use std::time::Instant;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch};
+
+/// Prefix of favourites whose stream is no longer in the provider's stream list
+pub const DEAD_FAVOURITE_MARKER: &str = "✗ ";
+
+/// Result of a task run in the background so the UI doesn't wait on the network
+enum BackgroundResult {
+    /// Movie details for the preview pane
+    VodPreview {
+        provider_hash: String,
+        stream_id: u32,
+        result: Result<Box<VodInfoResponse>, String>,
+    },
+    /// Connection test of a provider from the configuration screen
+    ProviderTest {
+        provider: ProviderConfig,
+        /// Whether the provider was just saved, which the result message mentions
+        saved: bool,
+        result: Result<Box<UserInfoResponse>, String>,
+    },
+    /// Subscription status, checked on connecting to a provider
+    Account {
+        provider_hash: String,
+        result: Result<UserInfo, String>,
+    },
+}
+
+/// A rule listed on the hidden items screen
+#[derive(Debug, Clone)]
+enum IgnoredItem {
+    Rule(IgnoreKind, IgnoreRule),
+    /// A pattern rule, and whether it applies to every provider
+    Pattern(PatternRule, bool),
+}
+
+/// Maximum delay between two clicks on the same item to count as a double-click
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+/// Number of lines scrolled per mouse wheel step
+const MOUSE_SCROLL_LINES: usize = 3;
+
+/// How long the cursor must rest on a movie before its details are fetched for the preview
+const PREVIEW_FETCH_DELAY: Duration = Duration::from_millis(300);
+
+/// How long to wait for another digit before jumping to a typed channel number
+const CHANNEL_NUMBER_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Settings listed on the configuration screen, in order, before the providers
+#[derive(Debug, Clone, Copy)]
+enum ConfigSetting {
+    PlayMode,
+    UseTsForLive,
+    Preview,
+    ParentalControls,
+    ParentalPin,
+}
+
+const CONFIG_SETTINGS_ITEMS: [ConfigSetting; 5] = [
+    ConfigSetting::PlayMode,
+    ConfigSetting::UseTsForLive,
+    ConfigSetting::Preview,
+    ConfigSetting::ParentalControls,
+    ConfigSetting::ParentalPin,
+];
 
 #[derive(Debug, Clone)]
 pub enum LogDisplayMode {
@@ -42,6 +125,95 @@ pub struct TuiSeason {
     pub episode_count: usize,
 }
 
+/// What a line of text typed into the footer prompt is used for
+#[derive(Debug, Clone)]
+pub enum TextInputPurpose {
+    CreateGroup,
+    RenameGroup(String),
+    MoveToGroup(FavouriteKey),
+    UnlockParental,
+    DisableParental,
+    ChangeParentalPin,
+    SetParentalPin,
+    /// One field of a provider being added (index None) or edited in the configuration
+    EditProvider {
+        index: Option<usize>,
+        draft: ProviderConfig,
+        field: ProviderField,
+    },
+}
+
+impl TextInputPurpose {
+    /// Whether the typed value is a PIN or password and should not be echoed
+    pub fn is_secret(&self) -> bool {
+        matches!(
+            self,
+            Self::UnlockParental
+                | Self::DisableParental
+                | Self::ChangeParentalPin
+                | Self::SetParentalPin
+                | Self::EditProvider {
+                    field: ProviderField::Password,
+                    ..
+                }
+        )
+    }
+}
+
+/// Provider settings asked for in turn when adding or editing a provider
+#[derive(Debug, Clone, Copy)]
+pub enum ProviderField {
+    Name,
+    Url,
+    Username,
+    Password,
+}
+
+impl ProviderField {
+    fn prompt(self) -> &'static str {
+        match self {
+            Self::Name => "Provider name (optional)",
+            Self::Url => "Server URL",
+            Self::Username => "Username",
+            Self::Password => "Password",
+        }
+    }
+
+    fn next(self) -> Option<Self> {
+        match self {
+            Self::Name => Some(Self::Url),
+            Self::Url => Some(Self::Username),
+            Self::Username => Some(Self::Password),
+            Self::Password => None,
+        }
+    }
+
+    fn value(self, provider: &ProviderConfig) -> String {
+        match self {
+            Self::Name => provider.name.clone().unwrap_or_default(),
+            Self::Url => provider.url.clone(),
+            Self::Username => provider.username.clone(),
+            Self::Password => provider.password.clone(),
+        }
+    }
+}
+
+/// A single line prompt shown in the footer
+#[derive(Debug, Clone)]
+pub struct TextInput {
+    pub prompt: String,
+    pub value: String,
+    pub purpose: TextInputPurpose,
+}
+
+/// Live channels of the category playback was started from, used for zapping
+#[derive(Debug, Clone)]
+struct ChannelZap {
+    channels: Vec<Stream>,
+    current: usize,
+    last: Option<usize>,
+}
+
 #[derive(Debug, Clone)]
 pub struct VodInfoState {
     pub stream: Stream,
@@ -64,12 +236,55 @@ impl std::fmt::Display for PlayMode {
     }
 }
 
+/// Details of the highlighted item, shown in the preview pane
 #[derive(Debug, Clone, Default)]
+pub struct PreviewDetails {
+    pub title: String,
+    pub fields: Vec<(String, String)>,
+    pub plot: Option<String>,
+    pub cast: Option<String>,
+    pub loading: bool,
+}
+
+/// Order of entries in a stream list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StreamSort {
+    #[default]
+    Provider,
+    Name,
+    Newest,
+    Rating,
+}
+
+impl StreamSort {
+    pub fn next(self) -> Self {
+        match self {
+            StreamSort::Provider => StreamSort::Name,
+            StreamSort::Name => StreamSort::Newest,
+            StreamSort::Newest => StreamSort::Rating,
+            StreamSort::Rating => StreamSort::Provider,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            StreamSort::Provider => "Provider order",
+            StreamSort::Name => "Name",
+            StreamSort::Newest => "Newest",
+            StreamSort::Rating => "Rating",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct NavigationState {
     pub selected_index: usize,
     pub scroll_offset: usize,
     pub search_query: String,
+    #[serde(skip)]
     pub filtered_indices: Vec<usize>,
+    pub sort: StreamSort,
 }
 
 impl NavigationState {
@@ -87,8 +302,10 @@ pub enum AppState {
     VodInfo(VodInfoState),
     SeasonSelection(Stream),
     EpisodeSelection(Stream, TuiSeason),
+    FavouriteGroups,
     CrossProviderFavourites,
     PerProviderFavourites,
+    IgnoredItems,
     StreamAdvancedMenu(Stream, ContentType),
     Configuration,
     Loading(String),
@@ -123,17 +340,39 @@ pub struct App {
     pub log_selected_index: usize,
     pub log_scroll_offset: usize,
     pub visible_height: usize, // Dynamically calculated based on terminal size
+    pub list_area: Rect,       // Inner area of the main list, updated on each draw
+    pub help_area: Rect,       // Area of the help overlay, updated on each draw
     pub search_query: String,
     pub search_active: bool,
     pub filtered_indices: Vec<usize>,
     pub config_state: NavigationState,
     categories: Vec<Category>,
-    streams: Vec<Stream>,
+    pub streams: Vec<Stream>,
+    pub stream_sort: StreamSort,
     seasons: Vec<TuiSeason>,
     episodes: Vec<ApiEpisode>,
     cross_provider_favourites: Vec<(FavouriteStream, ProviderConfig)>,
+    favourite_groups: Vec<FavouriteGroup>,
+    pub current_favourite_group: Option<String>,
+    pub text_input: Option<TextInput>,
     per_provider_favourites: Vec<FavouriteStream>,
+    // Favourites that no longer point at the stream they were saved for
+    dead_favourites: HashSet<FavouriteKey>,
+    // Rules listed on the hidden items screen, in display order
+    ignored_items: Vec<IgnoredItem>,
     vod_info: Option<VodInfoResponse>,
+    // VOD info fetched for the preview pane by stream ID
+    vod_preview_cache: HashMap<u32, VodInfoResponse>,
+    // Movies whose preview details are being fetched
+    vod_preview_pending: HashSet<u32>,
+    // Movie whose preview fetch failed, not retried until the cursor moves
+    vod_preview_failed: Option<u32>,
+    // Selected index and when the cursor arrived there, for debouncing preview fetches
+    preview_cursor: (usize, Instant),
+    // Channel list and position for zapping while a live stream plays
+    channel_zap: Option<ChannelZap>,
+    // Channel number being typed and when the last digit arrived
+    channel_number_input: Option<(String, Instant)>,
     // Cache for categories by content type
     cached_categories: HashMap<ContentType, Vec<Category>>,
     // Cache for streams by content type and category ID
@@ -145,8 +384,11 @@ pub struct App {
     stream_selection_states: HashMap<(ContentType, String), NavigationState>,
     season_selection_state: NavigationState,
     cross_provider_favourites_state: NavigationState,
+    favourite_groups_state: NavigationState,
     per_provider_favourites_state: NavigationState,
     ignore_config: IgnoreConfig,
+    // Parental controls unlocked with the PIN for the rest of this session
+    parental_unlocked: bool,
     previous_state_before_menu: Option<Box<AppState>>,
     previous_items_before_menu: Vec<String>,
     previous_nav_before_menu: NavigationState,
@@ -154,7 +396,20 @@ pub struct App {
     navigation_stack: Vec<(AppState, Vec<String>, NavigationState)>,
     pub playback_status: Option<MpvPlaybackStatus>,
     last_status_update: Instant,
+    // Bumped whenever favourites change, to re-mark the current list
+    favourites_changes: watch::Receiver<u64>,
+    // Problems with the current provider's subscription, shown as a header badge
+    pub account_alerts: Vec<AccountAlert>,
+    // Provider of current_api, for making clients for background tasks
+    current_provider: Option<ProviderConfig>,
+    // Results of background tasks, collected in async_tick
+    background_tx: mpsc::UnboundedSender<BackgroundResult>,
+    background_rx: mpsc::UnboundedReceiver<BackgroundResult>,
     pub current_stream_name: Option<String>,
+    // Last left click (time and item index) for double-click detection
+    last_click: Option<(Instant, usize)>,
+    // Set while a favourite is being dragged to a new position
+    dragging_favourite: bool,
 }
 
 impl App {
@@ -171,6 +426,7 @@ impl App {
             scroll_offset: self.scroll_offset,
             search_query: self.search_query.clone(),
             filtered_indices: self.filtered_indices.clone(),
+            sort: self.stream_sort,
         };
         self.navigation_stack
             .push((self.state.clone(), self.items.clone(), current_nav));
@@ -219,6 +475,7 @@ impl App {
         };
 
         let filtered_indices = (0..items.len()).collect();
+        let (background_tx, background_rx) = mpsc::unbounded_channel();
 
         let mut app = Self {
             state,
@@ -238,17 +495,31 @@ impl App {
             log_selected_index: 0,
             log_scroll_offset: 0,
             visible_height: 20, // Will be updated on first render
+            list_area: Rect::default(),
+            help_area: Rect::default(),
             search_query: String::new(),
             search_active: false,
             filtered_indices,
             config_state: NavigationState::new(),
             categories: Vec::new(),
             streams: Vec::new(),
+            stream_sort: StreamSort::default(),
             seasons: Vec::new(),
             episodes: Vec::new(),
             cross_provider_favourites: Vec::new(),
+            favourite_groups: Vec::new(),
+            current_favourite_group: None,
+            text_input: None,
             per_provider_favourites: Vec::new(),
+            dead_favourites: HashSet::new(),
+            ignored_items: Vec::new(),
             vod_info: None,
+            vod_preview_cache: HashMap::new(),
+            vod_preview_pending: HashSet::new(),
+            vod_preview_failed: None,
+            preview_cursor: (0, Instant::now()),
+            channel_zap: None,
+            channel_number_input: None,
             cached_categories: HashMap::new(),
             cached_streams: HashMap::new(),
             provider_selection_state: NavigationState::new(),
@@ -257,17 +528,28 @@ impl App {
             stream_selection_states: HashMap::new(),
             season_selection_state: NavigationState::new(),
             cross_provider_favourites_state: NavigationState::new(),
+            favourite_groups_state: NavigationState::new(),
             per_provider_favourites_state: NavigationState::new(),
             ignore_config: IgnoreConfig::load().unwrap_or_default(),
+            parental_unlocked: false,
             previous_state_before_menu: None,
             previous_items_before_menu: Vec::new(),
             previous_nav_before_menu: NavigationState::new(),
             navigation_stack: Vec::new(),
             playback_status: None,
             last_status_update: Instant::now(),
+            favourites_changes: FavouritesManager::subscribe(),
+            account_alerts: Vec::new(),
+            current_provider: None,
+            background_tx,
+            background_rx,
             current_stream_name: None,
+            last_click: None,
+            dragging_favourite: false,
         };
 
+        app.migrate_ignore_rules();
+
         // If a provider was specified, try to connect to it directly
         if let Some(provider_name) = provider_name {
             // Find the provider by name (case-insensitive)
@@ -338,10 +620,45 @@ impl App {
                 if self.playback_status.is_some() {
                     self.playback_status = None;
                     self.current_stream_name = None;
+                    self.channel_zap = None;
                     needs_redraw = true; // Redraw to remove status bar
                 }
             }
             self.last_status_update = Instant::now();
+
+            // Notice favourites changed by another process
+            let _ = self.favourites_index();
+        }
+
+        if self.favourites_changes.has_changed().unwrap_or(false) {
+            self.favourites_changes.mark_unchanged();
+            self.refresh_favourite_marks();
+            needs_redraw = true;
+        }
+
+        // Jump to a typed channel number once no more digits arrive
+        if let Some((_, last_digit)) = &self.channel_number_input
+            && last_digit.elapsed() >= CHANNEL_NUMBER_TIMEOUT
+        {
+            self.commit_channel_number();
+            needs_redraw = true;
+        }
+
+        while let Ok(result) = self.background_rx.try_recv() {
+            self.apply_background_result(result);
+            needs_redraw = true;
+        }
+
+        // Lazily fetch movie details for the preview pane once the cursor settles
+        if self.config.settings.show_preview {
+            if self.preview_cursor.0 != self.selected_index {
+                self.preview_cursor = (self.selected_index, Instant::now());
+                self.vod_preview_failed = None;
+            } else if self.preview_cursor.1.elapsed() >= PREVIEW_FETCH_DELAY
+                && let Some(stream_id) = self.preview_vod_to_fetch()
+            {
+                self.spawn_vod_preview_fetch(stream_id);
+            }
         }
 
         // Removed auto-connect for single provider - now shows provider selection screen
@@ -459,6 +776,12 @@ impl App {
             }
         }
 
+        // A footer prompt takes all typing until confirmed or cancelled
+        if self.text_input.is_some() {
+            self.handle_text_input_key(key).await;
+            return None;
+        }
+
         // Start search on '/' key
         if key.code == KeyCode::Char('/')
             && !matches!(self.state, AppState::Loading(_) | AppState::Playing(_))
@@ -522,6 +845,17 @@ impl App {
             return None;
         }
 
+        if key.code == KeyCode::Char('p') && !matches!(self.state, AppState::Error(_)) {
+            self.toggle_preview();
+            return None;
+        }
+
+        // Channel zapping while a live stream plays
+        if self.channel_zap.is_some() && self.playback_status.is_some() && self.handle_zap_key(key)
+        {
+            return None;
+        }
+
         match self.state.clone() {
             AppState::Error(_) => {
                 if key.code == KeyCode::Enter || key.code == KeyCode::Esc {
@@ -553,7 +887,7 @@ impl App {
                         // Favourites selected
                         self.save_current_navigation_state();
                         self.push_navigation_state(); // Save to stack before transitioning
-                        self.load_all_favourites().await;
+                        self.load_favourite_groups().await;
                     } else if self.selected_index > 0
                         && self.selected_index <= self.config.providers.len()
                     {
@@ -610,18 +944,23 @@ impl App {
                     if let Some(category) = self.get_current_category() {
                         if category.category_name != "All" && category.category_id != "all" {
                             // Don't allow ignoring "All" category
-                            let _provider_name = self
-                                .current_provider_name
-                                .as_ref()
-                                .unwrap_or(&String::new())
-                                .clone();
+                            let provider_hash = self.current_provider_hash();
+                            let provider_name =
+                                self.current_provider_name.clone().unwrap_or_default();
 
                             self.add_log(format!(
                                 "Toggling ignore for category '{}'",
                                 category.category_name
                             ));
 
-                            match self.ignore_config.toggle_category(&category.category_name) {
+                            match self.ignore_config.toggle(
+                                &provider_hash,
+                                &provider_name,
+                                IgnoreKind::Category,
+                                content_type.stream_type(),
+                                &category.category_id,
+                                &category.category_name,
+                            ) {
                                 Ok(is_ignored) => {
                                     let msg = if is_ignored {
                                         format!(
@@ -842,7 +1181,7 @@ impl App {
                     // selected_index already points to the correct stream
                     if self.selected_index < self.streams.len() {
                         let stream = self.streams[self.selected_index].clone();
-                        self.toggle_favourite_stream(&stream).await;
+                        self.toggle_favourite_stream(&stream, content_type).await;
                     }
                 }
                 KeyCode::Char('i') => {
@@ -850,8 +1189,18 @@ impl App {
                     if content_type == ContentType::Live && self.selected_index < self.streams.len()
                     {
                         let stream_name = self.streams[self.selected_index].name.clone();
+                        let stream_id = self.streams[self.selected_index].stream_id;
+                        let provider_hash = self.current_provider_hash();
+                        let provider_name = self.current_provider_name.clone().unwrap_or_default();
                         self.add_log(format!("Toggling ignore for channel '{}'", stream_name));
-                        match self.ignore_config.toggle_channel(&stream_name) {
+                        match self.ignore_config.toggle(
+                            &provider_hash,
+                            &provider_name,
+                            IgnoreKind::Channel,
+                            "live",
+                            &stream_id.to_string(),
+                            &stream_name,
+                        ) {
                             Ok(is_ignored) => {
                                 let msg = if is_ignored {
                                     format!("Channel '{}' will be hidden", stream_name)
@@ -1035,12 +1384,16 @@ impl App {
                                 )
                                 .await;
                             }
-                            _ => {
+                            ContentType::Live => {
+                                self.start_zap(self.selected_index);
                                 self.play_stream(&stream);
                             }
                         }
                     }
                 }
+                KeyCode::Char('o') => {
+                    self.cycle_stream_sort(content_type, category).await;
+                }
                 KeyCode::Char('a') => {
                     // Show advanced menu for live streams
                     if content_type == ContentType::Live && self.selected_index < self.streams.len()
@@ -1298,6 +1651,20 @@ impl App {
                         self.load_episodes(series.clone(), season).await;
                     }
                 }
+                KeyCode::Esc | KeyCode::Char('b')
+                    if matches!(
+                        self.navigation_stack.last(),
+                        Some((
+                            AppState::PerProviderFavourites | AppState::CrossProviderFavourites,
+                            _,
+                            _
+                        ))
+                    ) =>
+                {
+                    // Opened from a series favourite
+                    self.save_current_navigation_state();
+                    self.pop_navigation_state();
+                }
                 KeyCode::Esc | KeyCode::Char('b') => {
                     // Go back to stream selection
                     self.save_current_navigation_state();
@@ -1336,6 +1703,12 @@ impl App {
                     if self.selected_index < self.episodes.len() {
                         let episode = self.episodes[self.selected_index].clone();
                         self.play_episode(&episode).await;
+                        self.mark_episode_watched(series.stream_id, &episode.id);
+                    }
+                }
+                KeyCode::Char('f') => {
+                    if let Some(episode) = self.episodes.get(self.selected_index).cloned() {
+                        self.toggle_favourite_episode(&series, &episode);
                     }
                 }
                 KeyCode::Esc | KeyCode::Char('b') => {
@@ -1345,125 +1718,87 @@ impl App {
                 }
                 _ => {}
             },
-            AppState::CrossProviderFavourites => match key.code {
+            AppState::FavouriteGroups => match key.code {
                 KeyCode::Up if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    // Move favourite up
-                    if self.selected_index < self.cross_provider_favourites.len() {
-                        let (favourite, provider) =
-                            &self.cross_provider_favourites[self.selected_index];
-
-                        // Store the item we're moving to track it after reload
-                        let moving_stream_id = favourite.stream_id;
-                        let moving_stream_type = favourite.stream_type.clone();
-                        let moving_provider_id = provider.id.clone();
-
-                        let favourites_manager = match crate::FavouritesManager::new() {
-                            Ok(fm) => fm,
-                            Err(e) => {
-                                self.add_log(format!("Failed to access favourites: {}", e));
-                                return None;
-                            }
-                        };
-
-                        let api = match crate::XTreamAPI::new_with_id(
-                            provider.url.clone(),
-                            provider.username.clone(),
-                            provider.password.clone(),
-                            provider.name.clone(),
-                            provider.id.clone(),
-                        ) {
-                            Ok(mut api) => {
-                                api.disable_progress();
-                                api
+                    self.move_selected_group(true).await;
+                }
+                KeyCode::Down if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.move_selected_group(false).await;
+                }
+                KeyCode::Up | KeyCode::Char('k') => self.move_selection_up(),
+                KeyCode::Down | KeyCode::Char('j') => self.move_selection_down(),
+                KeyCode::PageUp => self.move_selection_page_up(),
+                KeyCode::PageDown => self.move_selection_page_down(),
+                KeyCode::Home | KeyCode::Char('H') => self.move_selection_home(),
+                KeyCode::End | KeyCode::Char('G') => self.move_selection_end(),
+                KeyCode::Enter => {
+                    if let Some(group) = self.favourite_groups.get(self.selected_index) {
+                        self.current_favourite_group = Some(group.name.clone());
+                        self.save_current_navigation_state();
+                        self.push_navigation_state(); // Save to stack before opening the group
+                        self.cross_provider_favourites_state = NavigationState::new();
+                        self.load_all_favourites().await;
+                    }
+                }
+                KeyCode::Char('n') => {
+                    self.text_input = Some(TextInput {
+                        prompt: "New group name".to_string(),
+                        value: String::new(),
+                        purpose: TextInputPurpose::CreateGroup,
+                    });
+                }
+                KeyCode::Char('r') => {
+                    if let Some(group) = self.favourite_groups.get(self.selected_index) {
+                        self.text_input = Some(TextInput {
+                            prompt: format!("Rename '{}' to", group.name),
+                            value: group.name.clone(),
+                            purpose: TextInputPurpose::RenameGroup(group.name.clone()),
+                        });
+                    }
+                }
+                KeyCode::Char('d') => {
+                    if let Some(group) = self.favourite_groups.get(self.selected_index) {
+                        let name = group.name.clone();
+                        match crate::FavouritesManager::new().and_then(|fm| fm.delete_group(&name))
+                        {
+                            Ok(()) => {
+                                self.status_message = Some(format!(
+                                    "Deleted group '{}', its favourites moved to the first group",
+                                    name
+                                ));
+                                self.add_log(format!("Deleted favourite group {}", name));
+                                self.load_favourite_groups().await;
                             }
                             Err(e) => {
-                                self.add_log(format!("Failed to connect: {}", e));
-                                return None;
-                            }
-                        };
-
-                        if let Ok(true) = favourites_manager.move_favourite_up(
-                            &api.provider_hash,
-                            favourite.stream_id,
-                            &favourite.stream_type,
-                        ) {
-                            self.add_log(format!("Moved {} up", favourite.name));
-                            // Reload favourites
-                            self.load_all_favourites().await;
-
-                            // Find the moved item's new position and select it
-                            if let Some(new_index) =
-                                self.cross_provider_favourites.iter().position(|(f, p)| {
-                                    f.stream_id == moving_stream_id
-                                        && f.stream_type == moving_stream_type
-                                        && p.id == moving_provider_id
-                                })
-                            {
-                                self.selected_index = new_index;
-                                // Ensure the selection is visible
-                                self.ensure_selected_visible();
+                                self.status_message = Some(format!("{}", e));
                             }
                         }
                     }
                 }
+                KeyCode::Esc | KeyCode::Char('b') => {
+                    if !self.search_query.is_empty() {
+                        self.reset_filter();
+                    } else if !self.pop_navigation_state() {
+                        self.save_current_navigation_state();
+                        self.state = AppState::ProviderSelection;
+                        self.restore_navigation_state(&AppState::ProviderSelection);
+                        self.update_provider_items();
+                    }
+                }
+                _ => {}
+            },
+            AppState::CrossProviderFavourites => match key.code {
+                KeyCode::Up if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    self.move_selected_favourite_in_group(true).await;
+                }
                 KeyCode::Down if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    // Move favourite down
-                    if self.selected_index < self.cross_provider_favourites.len() {
-                        let (favourite, provider) =
-                            &self.cross_provider_favourites[self.selected_index];
-
-                        // Store the item we're moving to track it after reload
-                        let moving_stream_id = favourite.stream_id;
-                        let moving_stream_type = favourite.stream_type.clone();
-                        let moving_provider_id = provider.id.clone();
-
-                        let favourites_manager = match crate::FavouritesManager::new() {
-                            Ok(fm) => fm,
-                            Err(e) => {
-                                self.add_log(format!("Failed to access favourites: {}", e));
-                                return None;
-                            }
-                        };
-
-                        let api = match crate::XTreamAPI::new_with_id(
-                            provider.url.clone(),
-                            provider.username.clone(),
-                            provider.password.clone(),
-                            provider.name.clone(),
-                            provider.id.clone(),
-                        ) {
-                            Ok(mut api) => {
-                                api.disable_progress();
-                                api
-                            }
-                            Err(e) => {
-                                self.add_log(format!("Failed to connect: {}", e));
-                                return None;
-                            }
-                        };
-
-                        if let Ok(true) = favourites_manager.move_favourite_down(
-                            &api.provider_hash,
-                            favourite.stream_id,
-                            &favourite.stream_type,
-                        ) {
-                            self.add_log(format!("Moved {} down", favourite.name));
-                            // Reload favourites
-                            self.load_all_favourites().await;
-
-                            // Find the moved item's new position and select it
-                            if let Some(new_index) =
-                                self.cross_provider_favourites.iter().position(|(f, p)| {
-                                    f.stream_id == moving_stream_id
-                                        && f.stream_type == moving_stream_type
-                                        && p.id == moving_provider_id
-                                })
-                            {
-                                self.selected_index = new_index;
-                                // Ensure the selection is visible
-                                self.ensure_selected_visible();
-                            }
-                        }
+                    self.move_selected_favourite_in_group(false).await;
+                }
+                KeyCode::Char('m') => {
+                    if let Some((favourite, _)) =
+                        self.cross_provider_favourites.get(self.selected_index)
+                    {
+                        self.prompt_move_to_group(FavouriteKey::of(favourite));
                     }
                 }
                 KeyCode::Up | KeyCode::Char('k') => self.move_selection_up(),
@@ -1516,8 +1851,12 @@ impl App {
                             }
                         }
 
-                        // Play the favourite using TUI-specific method
-                        if let Some(api) = &self.current_api {
+                        // Series open their seasons, everything else plays
+                        if favourite.stream_type == "series" {
+                            self.current_provider_name =
+                                provider.name.clone().or_else(|| Some(provider.url.clone()));
+                            self.open_favourite_series(&favourite).await;
+                        } else if let Some(api) = &self.current_api {
                             // Store current stream name and provider
                             self.current_stream_name = Some(favourite.name.clone());
                             self.current_provider_name =
@@ -1536,11 +1875,7 @@ impl App {
                                 None
                             };
 
-                            let stream_url = api.get_stream_url(
-                                favourite.stream_id,
-                                &favourite.stream_type,
-                                extension,
-                            );
+                            let stream_url = api.get_favourite_url(&favourite, extension);
 
                             self.add_log(format!("Playing: {}", favourite.name));
 
@@ -1573,6 +1908,12 @@ impl App {
                                         self.add_log("Player started in terminal mode".to_string());
                                     }
                                 }
+                                if let Some(series_id) = favourite.series_id {
+                                    self.mark_episode_watched(
+                                        series_id,
+                                        &favourite.stream_id.to_string(),
+                                    );
+                                }
                                 // Stay in CrossProviderFavourites state
                             }
                         }
@@ -1798,7 +2139,9 @@ impl App {
                     if self.selected_index < self.per_provider_favourites.len() {
                         let favourite = self.per_provider_favourites[self.selected_index].clone();
 
-                        if let Some(api) = &self.current_api {
+                        if favourite.stream_type == "series" {
+                            self.open_favourite_series(&favourite).await;
+                        } else if let Some(api) = &self.current_api {
                             // Store current stream name
                             self.current_stream_name = Some(favourite.name.clone());
 
@@ -1815,11 +2158,7 @@ impl App {
                                 None
                             };
 
-                            let stream_url = api.get_stream_url(
-                                favourite.stream_id,
-                                &favourite.stream_type,
-                                extension,
-                            );
+                            let stream_url = api.get_favourite_url(&favourite, extension);
 
                             self.add_log(format!("Playing: {}", favourite.name));
                             self.add_log(format!("Stream URL: {}", stream_url));
@@ -1850,6 +2189,12 @@ impl App {
                                         self.add_log("Player started in terminal mode".to_string());
                                     }
                                 }
+                                if let Some(series_id) = favourite.series_id {
+                                    self.mark_episode_watched(
+                                        series_id,
+                                        &favourite.stream_id.to_string(),
+                                    );
+                                }
                                 // Stay in PerProviderFavourites state
                             }
                         }
@@ -1888,6 +2233,11 @@ impl App {
                         }
                     }
                 }
+                KeyCode::Char('m') => {
+                    if let Some(favourite) = self.per_provider_favourites.get(self.selected_index) {
+                        self.prompt_move_to_group(FavouriteKey::of(favourite));
+                    }
+                }
                 KeyCode::Char('d') => {
                     // Remove favourite
                     if self.selected_index < self.per_provider_favourites.len() {
@@ -1955,12 +2305,36 @@ impl App {
                 }
                 _ => {}
             },
+            AppState::IgnoredItems => match key.code {
+                KeyCode::Up | KeyCode::Char('k') => self.move_selection_up(),
+                KeyCode::Down | KeyCode::Char('j') => self.move_selection_down(),
+                KeyCode::PageUp => self.move_selection_page_up(),
+                KeyCode::PageDown => self.move_selection_page_down(),
+                KeyCode::Home | KeyCode::Char('H') => self.move_selection_home(),
+                KeyCode::End | KeyCode::Char('G') => self.move_selection_end(),
+                KeyCode::Enter | KeyCode::Char('i') | KeyCode::Char('u') => {
+                    self.unignore_selected();
+                }
+                KeyCode::Esc | KeyCode::Char('b') => {
+                    if !self.search_query.is_empty() {
+                        self.reset_filter();
+                    } else if !self.pop_navigation_state() {
+                        self.state = AppState::MainMenu;
+                        self.restore_navigation_state(&AppState::MainMenu);
+                        self.update_main_menu_items();
+                    }
+                }
+                _ => {}
+            },
             AppState::Configuration => match key.code {
                 KeyCode::Up | KeyCode::Char('k') => self.move_selection_up(),
                 KeyCode::Down | KeyCode::Char('j') => self.move_selection_down(),
                 KeyCode::Enter => {
                     self.handle_configuration_selection();
                 }
+                KeyCode::Char('t') => {
+                    self.test_selected_provider();
+                }
                 KeyCode::Esc | KeyCode::Char('b') => {
                     // Save configuration state before going back
                     self.save_current_navigation_state();
@@ -1996,11 +2370,500 @@ impl App {
         None
     }
 
-    fn move_selection_up(&mut self) {
-        let indices = self.filtered_indices.clone();
+    pub async fn handle_mouse_event(&mut self, mouse: MouseEvent) -> Option<Action> {
+        // Typing a search query takes precedence over the pointer
+        if self.search_active {
+            return None;
+        }
 
-        if indices.is_empty() {
-            return;
+        // In full log view the wheel moves the log selection
+        if matches!(self.log_display_mode, LogDisplayMode::Full) {
+            match mouse.kind {
+                MouseEventKind::ScrollUp => {
+                    self.log_selected_index =
+                        self.log_selected_index.saturating_sub(MOUSE_SCROLL_LINES);
+                    if self.log_selected_index < self.log_scroll_offset {
+                        self.log_scroll_offset = self.log_selected_index;
+                    }
+                }
+                MouseEventKind::ScrollDown => {
+                    let max_index = self.logs.len().saturating_sub(1);
+                    self.log_selected_index =
+                        (self.log_selected_index + MOUSE_SCROLL_LINES).min(max_index);
+                }
+                _ => {}
+            }
+            return None;
+        }
+
+        // The help overlay scrolls with the wheel and closes on click
+        if self.show_help {
+            match mouse.kind {
+                MouseEventKind::ScrollUp => {
+                    self.help_scroll_offset =
+                        self.help_scroll_offset.saturating_sub(MOUSE_SCROLL_LINES);
+                }
+                MouseEventKind::ScrollDown => {
+                    let visible_height = self.help_area.height.saturating_sub(2) as usize;
+                    let max_scroll = get_help_lines().len().saturating_sub(visible_height);
+                    self.help_scroll_offset =
+                        (self.help_scroll_offset + MOUSE_SCROLL_LINES).min(max_scroll);
+                }
+                MouseEventKind::Down(_) => {
+                    self.show_help = false;
+                    self.help_scroll_offset = 0;
+                }
+                _ => {}
+            }
+            return None;
+        }
+
+        if matches!(self.state, AppState::Error(_)) {
+            // A click dismisses the error just like Enter
+            if mouse.kind == MouseEventKind::Down(MouseButton::Left) {
+                return self
+                    .handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+                    .await;
+            }
+            return None;
+        }
+
+        if matches!(self.state, AppState::Loading(_) | AppState::Playing(_)) {
+            return None;
+        }
+
+        match mouse.kind {
+            MouseEventKind::ScrollUp => self.scroll_list_up(MOUSE_SCROLL_LINES),
+            MouseEventKind::ScrollDown => self.scroll_list_down(MOUSE_SCROLL_LINES),
+            MouseEventKind::Down(MouseButton::Left) => {
+                if self.is_on_scrollbar(mouse.column, mouse.row) {
+                    self.scroll_to_scrollbar_row(mouse.row);
+                    return None;
+                }
+
+                let index = self.item_index_at(mouse.column, mouse.row)?;
+
+                // Separators and VOD info text lines can't be selected
+                if self.items[index].is_empty()
+                    || (matches!(self.state, AppState::VodInfo(_))
+                        && !is_vod_menu_item(&self.items[index]))
+                {
+                    return None;
+                }
+
+                let is_double_click = self.last_click.is_some_and(|(time, last_index)| {
+                    last_index == index && time.elapsed() <= DOUBLE_CLICK_INTERVAL
+                });
+
+                self.selected_index = index;
+
+                if is_double_click {
+                    // Double-click activates the item the same way Enter does
+                    self.last_click = None;
+                    return self
+                        .handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+                        .await;
+                }
+
+                self.last_click = Some((Instant::now(), index));
+
+                // Reordering only makes sense on the unfiltered list
+                self.dragging_favourite = matches!(
+                    self.state,
+                    AppState::FavouriteGroups
+                        | AppState::CrossProviderFavourites
+                        | AppState::PerProviderFavourites
+                ) && self.search_query.is_empty();
+            }
+            MouseEventKind::Drag(MouseButton::Left) => {
+                if self.dragging_favourite {
+                    self.drag_favourite_to(mouse.column, mouse.row).await;
+                } else if self.is_on_scrollbar(mouse.column, mouse.row) {
+                    self.scroll_to_scrollbar_row(mouse.row);
+                }
+            }
+            MouseEventKind::Up(MouseButton::Left) => {
+                self.dragging_favourite = false;
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    /// Scroll offset of the main list for the current state
+    fn list_scroll_offset(&self) -> usize {
+        match &self.state {
+            AppState::VodInfo(vod_state) => vod_state.content_scroll,
+            _ => self.scroll_offset,
+        }
+    }
+
+    /// Map a terminal cell to the index of the list item drawn there
+    fn item_index_at(&self, column: u16, row: u16) -> Option<usize> {
+        if !self.list_area.contains(Position::new(column, row)) {
+            return None;
+        }
+
+        let position = self.list_scroll_offset() + (row - self.list_area.y) as usize;
+        self.filtered_indices
+            .get(position)
+            .copied()
+            .filter(|&index| index < self.items.len())
+    }
+
+    /// Check whether a terminal cell is on the scrollbar drawn beside the main list
+    fn is_on_scrollbar(&self, column: u16, row: u16) -> bool {
+        self.filtered_indices.len() > self.list_area.height as usize
+            && self.list_area.contains(Position::new(column, row))
+            && column == self.list_area.right() - 1
+    }
+
+    /// Jump the list so the scrollbar thumb lands on the clicked row
+    fn scroll_to_scrollbar_row(&mut self, row: u16) {
+        let total = self.filtered_indices.len();
+        let visible = (self.list_area.height as usize).max(1);
+        let row_offset = row.saturating_sub(self.list_area.y) as usize;
+        let offset = (row_offset * total / visible).min(total.saturating_sub(visible));
+        self.set_list_scroll(offset);
+    }
+
+    fn scroll_list_up(&mut self, lines: usize) {
+        let offset = self.list_scroll_offset().saturating_sub(lines);
+        self.set_list_scroll(offset);
+    }
+
+    fn scroll_list_down(&mut self, lines: usize) {
+        let max_scroll = self
+            .filtered_indices
+            .len()
+            .saturating_sub(self.list_area.height as usize);
+        let offset = (self.list_scroll_offset() + lines).min(max_scroll);
+        self.set_list_scroll(offset);
+    }
+
+    /// Set the list scroll position, keeping the selection within the visible rows
+    fn set_list_scroll(&mut self, offset: usize) {
+        if let AppState::VodInfo(vod_state) = &mut self.state {
+            // Only the content scrolls, the selected menu action stays put
+            vod_state.content_scroll = offset;
+            return;
+        }
+
+        self.scroll_offset = offset;
+
+        let visible = (self.list_area.height as usize).max(1);
+        if let Some(position) = self
+            .filtered_indices
+            .iter()
+            .position(|&i| i == self.selected_index)
+        {
+            let target = if position < offset {
+                offset
+            } else if position >= offset + visible {
+                offset + visible - 1
+            } else {
+                position
+            };
+            if let Some(&index) = self.filtered_indices.get(target) {
+                self.selected_index = index;
+            }
+        }
+    }
+
+    /// Move the dragged favourite one step at a time towards the row under the pointer
+    async fn drag_favourite_to(&mut self, column: u16, row: u16) {
+        let Some(target) = self.item_index_at(column, row) else {
+            return;
+        };
+
+        // Reuse the Ctrl+Up/Down handlers so the new order is saved the same way
+        while self.selected_index != target {
+            let previous = self.selected_index;
+            let code = if target < previous {
+                KeyCode::Up
+            } else {
+                KeyCode::Down
+            };
+            self.handle_key_event(KeyEvent::new(code, KeyModifiers::CONTROL))
+                .await;
+
+            // Stop when the favourite can't move any further (e.g. at a provider boundary)
+            if self.selected_index == previous {
+                break;
+            }
+        }
+    }
+
+    fn toggle_preview(&mut self) {
+        let show_preview = !self.config.settings.show_preview;
+        self.status_message = Some(if show_preview {
+            "Preview pane shown".to_string()
+        } else {
+            "Preview pane hidden".to_string()
+        });
+        if let Err(e) = self.update_config(|config| config.settings.show_preview = show_preview) {
+            self.add_log(format!("Failed to save settings: {}", e));
+        }
+    }
+
+    /// Stream ID of the highlighted movie if its preview details haven't been fetched yet
+    fn preview_vod_to_fetch(&self) -> Option<u32> {
+        match &self.state {
+            AppState::StreamSelection(ContentType::Movies, _) => {
+                let stream = self.streams.get(self.selected_index)?;
+                let id = stream.stream_id;
+                (!self.vod_preview_cache.contains_key(&id)
+                    && !self.vod_preview_pending.contains(&id)
+                    && self.vod_preview_failed != Some(id))
+                .then_some(id)
+            }
+            _ => None,
+        }
+    }
+
+    /// A client for the current provider that a background task can own
+    fn background_api(&self) -> Option<XTreamAPI> {
+        let provider = self.current_provider.as_ref()?;
+        let mut api = XTreamAPI::new_with_id(
+            provider.url.clone(),
+            provider.username.clone(),
+            provider.password.clone(),
+            provider.name.clone(),
+            provider.id.clone(),
+        )
+        .ok()?;
+        api.disable_progress();
+        Some(api)
+    }
+
+    /// Fetch a movie's details for the preview pane without holding up the UI
+    fn spawn_vod_preview_fetch(&mut self, stream_id: u32) {
+        let Some(mut api) = self.background_api() else {
+            return;
+        };
+        self.vod_preview_pending.insert(stream_id);
+        let sender = self.background_tx.clone();
+        tokio::spawn(async move {
+            let result = api
+                .get_vod_info(stream_id)
+                .await
+                .map(Box::new)
+                .map_err(|e| e.to_string());
+            let _ = sender.send(BackgroundResult::VodPreview {
+                provider_hash: api.provider_hash,
+                stream_id,
+                result,
+            });
+        });
+    }
+
+    fn apply_background_result(&mut self, result: BackgroundResult) {
+        let current_hash = self
+            .current_api
+            .as_ref()
+            .map(|api| api.provider_hash.clone());
+        match result {
+            BackgroundResult::VodPreview {
+                provider_hash,
+                stream_id,
+                result,
+            } => {
+                // Drop results for a provider that has since been switched away from
+                if current_hash.as_deref() != Some(provider_hash.as_str()) {
+                    return;
+                }
+                self.vod_preview_pending.remove(&stream_id);
+                match result {
+                    Ok(vod_info) => {
+                        self.vod_preview_cache.insert(stream_id, *vod_info);
+                    }
+                    Err(e) => {
+                        self.add_log(format!("Failed to load movie details: {}", e));
+                        self.vod_preview_failed = Some(stream_id);
+                    }
+                }
+            }
+            BackgroundResult::ProviderTest {
+                provider,
+                saved,
+                result,
+            } => self.apply_provider_test(&provider, saved, result),
+            BackgroundResult::Account {
+                provider_hash,
+                result,
+            } => {
+                if current_hash.as_deref() == Some(provider_hash.as_str()) {
+                    self.apply_account_status(result);
+                }
+            }
+        }
+    }
+
+    /// Details of the highlighted item for the preview pane, if the screen has one
+    pub fn preview_details(&self) -> Option<PreviewDetails> {
+        match &self.state {
+            AppState::StreamSelection(content_type, category) => {
+                let stream = self.streams.get(self.selected_index)?;
+                Some(self.stream_preview(stream, *content_type, category))
+            }
+            AppState::PerProviderFavourites => {
+                let favourite = self.per_provider_favourites.get(self.selected_index)?;
+                Some(self.favourite_preview(favourite, None))
+            }
+            AppState::CrossProviderFavourites => {
+                let (favourite, provider) =
+                    self.cross_provider_favourites.get(self.selected_index)?;
+                Some(self.favourite_preview(favourite, Some(provider)))
+            }
+            _ => None,
+        }
+    }
+
+    fn stream_preview(
+        &self,
+        stream: &Stream,
+        content_type: ContentType,
+        category: &Category,
+    ) -> PreviewDetails {
+        let mut details = PreviewDetails {
+            title: stream.name.clone(),
+            ..Default::default()
+        };
+
+        // The "All" category mixes streams, so look up each stream's own category
+        let category_name = if category.category_id == "all" {
+            stream.category_id.as_ref().and_then(|id| {
+                self.cached_categories
+                    .get(&content_type)?
+                    .iter()
+                    .find(|c| &c.category_id == id)
+                    .map(|c| c.category_name.clone())
+            })
+        } else {
+            Some(category.category_name.clone())
+        };
+
+        details
+            .fields
+            .push(("Type".to_string(), content_type.to_string()));
+        if let Some(name) = category_name {
+            details.fields.push(("Category".to_string(), name));
+        }
+        if stream.num > 0 {
+            details
+                .fields
+                .push(("Number".to_string(), stream.num.to_string()));
+        }
+        if let Some(added) = stream.added_at() {
+            details.fields.push((
+                "Added".to_string(),
+                added.with_timezone(&Local).format("%Y-%m-%d").to_string(),
+            ));
+        }
+        if let Some(rating) = stream.rating_value() {
+            details
+                .fields
+                .push(("Rating".to_string(), format!("{:.1}/10", rating)));
+        }
+        if content_type == ContentType::Live {
+            let archive = match stream.archive_days() {
+                Some(days) => format!("Yes ({} days)", days),
+                None if stream.has_archive() => "Yes".to_string(),
+                None => "No".to_string(),
+            };
+            details.fields.push(("Archive".to_string(), archive));
+        }
+
+        if content_type == ContentType::Movies {
+            match self.vod_preview_cache.get(&stream.stream_id) {
+                Some(vod_info) => {
+                    let info = &vod_info.info;
+                    let optional_fields = [
+                        ("Genre", &info.genre),
+                        ("Release", &info.releasedate),
+                        ("Duration", &info.duration),
+                        ("Director", &info.director),
+                    ];
+                    for (label, value) in optional_fields {
+                        if let Some(value) = value
+                            && !value.trim().is_empty()
+                        {
+                            details
+                                .fields
+                                .push((label.to_string(), value.trim().to_string()));
+                        }
+                    }
+                    details.plot = info
+                        .plot
+                        .as_ref()
+                        .map(|p| p.trim().to_string())
+                        .filter(|p| !p.is_empty());
+                    details.cast = info
+                        .cast
+                        .as_ref()
+                        .map(|c| c.trim().to_string())
+                        .filter(|c| !c.is_empty());
+                }
+                // Fetch failed, show what the list has
+                None if self.vod_preview_failed == Some(stream.stream_id) => {}
+                None => details.loading = true,
+            }
+        }
+
+        details
+    }
+
+    fn favourite_preview(
+        &self,
+        favourite: &FavouriteStream,
+        provider: Option<&ProviderConfig>,
+    ) -> PreviewDetails {
+        let mut details = PreviewDetails {
+            title: favourite.name.clone(),
+            ..Default::default()
+        };
+
+        details
+            .fields
+            .push(("Type".to_string(), favourite.stream_type.clone()));
+        if let Some(provider) = provider {
+            details.fields.push((
+                "Provider".to_string(),
+                provider
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| provider.url.clone()),
+            ));
+        }
+        if let Some(category_id) = &favourite.category_id {
+            details
+                .fields
+                .push(("Category ID".to_string(), category_id.clone()));
+        }
+        details.fields.push((
+            "Favourited".to_string(),
+            favourite
+                .added_date
+                .with_timezone(&Local)
+                .format("%Y-%m-%d")
+                .to_string(),
+        ));
+        if self.dead_favourites.contains(&FavouriteKey::of(favourite)) {
+            details.fields.push((
+                "Status".to_string(),
+                "Missing from provider stream list".to_string(),
+            ));
+        }
+
+        details
+    }
+
+    fn move_selection_up(&mut self) {
+        let indices = self.filtered_indices.clone();
+
+        if indices.is_empty() {
+            return;
         }
 
         if let Some(current_pos) = indices.iter().position(|&idx| idx == self.selected_index) {
@@ -2126,15 +2989,24 @@ impl App {
                 // Note: We can't actually pass a closure that captures self here due to lifetime issues
                 // Instead we'll just disable progress bars for now
                 self.current_api = Some(api);
+                self.current_provider = Some(provider.clone());
                 self.current_provider_name = Some(
                     provider
                         .name
                         .clone()
                         .unwrap_or_else(|| provider.url.clone()),
                 );
+                self.account_alerts.clear();
+                self.spawn_account_check();
+                // The zap list holds the previous provider's stream IDs
+                self.channel_zap = None;
+                self.channel_number_input = None;
                 // Clear caches when switching providers
                 self.cached_categories.clear();
                 self.cached_streams.clear();
+                self.vod_preview_cache.clear();
+                self.vod_preview_pending.clear();
+                self.vod_preview_failed = None;
 
                 // Clear navigation states to prevent index out of bounds with different provider
                 self.category_selection_states.clear();
@@ -2153,6 +3025,39 @@ impl App {
         }
     }
 
+    /// Fetch the current provider's subscription status in the background
+    fn spawn_account_check(&mut self) {
+        let Some(mut api) = self.background_api() else {
+            return;
+        };
+        let sender = self.background_tx.clone();
+        tokio::spawn(async move {
+            let result = api.fetch_user_info().await.map_err(|e| e.to_string());
+            let _ = sender.send(BackgroundResult::Account {
+                provider_hash: api.provider_hash,
+                result,
+            });
+        });
+    }
+
+    /// Warn about expiry or the connection limit from a fetched subscription status
+    fn apply_account_status(&mut self, result: Result<UserInfo, String>) {
+        let now = chrono::Utc::now();
+        match result {
+            Ok(user_info) => {
+                self.account_alerts = AccountStatus::from_user_info(&user_info, now)
+                    .alerts(self.config.settings.expiry_warning_days, now);
+                for alert in self.account_alerts.clone() {
+                    self.add_log(format!("⚠️ {}", alert));
+                }
+                if let Some(alert) = self.account_alerts.first() {
+                    self.status_message = Some(format!(" ⚠ {} ", alert));
+                }
+            }
+            Err(e) => self.add_log(format!("Failed to check account status: {}", e)),
+        }
+    }
+
     fn update_provider_items(&mut self) {
         let mut items = vec!["Favourites".to_string()];
         items.extend(
@@ -2179,7 +3084,15 @@ impl App {
         // Add separator and Configuration option
         menu_items.push("".to_string()); // Empty string acts as separator
         menu_items.push("Configuration".to_string());
+        menu_items.push("Hidden Items".to_string());
         menu_items.push("Refresh Cache".to_string());
+        if self.config.settings.parental.enabled {
+            menu_items.push(if self.parental_unlocked {
+                "Lock Adult Content".to_string()
+            } else {
+                "Unlock Adult Content".to_string()
+            });
+        }
 
         self.items = menu_items;
         self.reset_filter();
@@ -2218,11 +3131,123 @@ impl App {
                 self.show_configuration();
                 None
             }
+            "Hidden Items" => {
+                self.push_navigation_state(); // Save current state to stack
+                self.load_ignored_items();
+                None
+            }
             "Refresh Cache" => self.refresh_cache().await,
+            "Unlock Adult Content" => {
+                self.prompt_pin("Parental PIN", TextInputPurpose::UnlockParental);
+                None
+            }
+            "Lock Adult Content" => {
+                self.parental_unlocked = false;
+                self.status_message = Some("Adult content locked".to_string());
+                self.update_main_menu_items();
+                None
+            }
             _ => None,
         }
     }
 
+    /// List the current provider's ignore rules so they can be removed
+    fn load_ignored_items(&mut self) {
+        let provider_hash = self.current_provider_hash();
+        let mut ignored_items: Vec<IgnoredItem> = [IgnoreKind::Category, IgnoreKind::Channel]
+            .into_iter()
+            .flat_map(|kind| {
+                self.ignore_config
+                    .rules(&provider_hash, kind)
+                    .into_iter()
+                    .map(move |rule| IgnoredItem::Rule(kind, rule))
+            })
+            .collect();
+        ignored_items.extend(
+            self.ignore_config
+                .patterns
+                .iter()
+                .map(|rule| IgnoredItem::Pattern(rule.clone(), true)),
+        );
+        if let Some(ignores) = self.ignore_config.providers.get(&provider_hash) {
+            ignored_items.extend(
+                ignores
+                    .patterns
+                    .iter()
+                    .map(|rule| IgnoredItem::Pattern(rule.clone(), false)),
+            );
+        }
+        self.ignored_items = ignored_items;
+
+        self.items = self
+            .ignored_items
+            .iter()
+            .map(|item| match item {
+                IgnoredItem::Rule(kind, rule) => {
+                    let kind = match kind {
+                        IgnoreKind::Category => "category",
+                        IgnoreKind::Channel => "channel",
+                    };
+                    let scope = match &rule.content {
+                        Some(content) => format!("{}/{}", kind, content),
+                        None => kind.to_string(),
+                    };
+                    match &rule.id {
+                        Some(id) => format!("[{}] {} (ID {})", scope, rule.name, id),
+                        None => format!("[{}] {} (by name)", scope, rule.name),
+                    }
+                }
+                IgnoredItem::Pattern(rule, true) => {
+                    format!("[pattern] {} (all providers)", rule.describe())
+                }
+                IgnoredItem::Pattern(rule, false) => format!("[pattern] {}", rule.describe()),
+            })
+            .collect();
+
+        if self.items.is_empty() {
+            self.status_message = Some("Nothing is hidden on this provider".to_string());
+        }
+
+        self.reset_filter();
+        self.state = AppState::IgnoredItems;
+    }
+
+    /// Remove the selected ignore rule so its category or channel shows again
+    fn unignore_selected(&mut self) {
+        let Some(item) = self.ignored_items.get(self.selected_index).cloned() else {
+            return;
+        };
+
+        let provider_hash = self.current_provider_hash();
+        let result = match &item {
+            IgnoredItem::Rule(kind, rule) => self
+                .ignore_config
+                .remove_rule(&provider_hash, *kind, rule)
+                .map(|()| format!("'{}' will be shown", rule.name)),
+            IgnoredItem::Pattern(rule, global) => self
+                .ignore_config
+                .remove_pattern((!global).then_some(provider_hash.as_str()), rule)
+                .map(|_| format!("Removed rule: {}", rule.describe())),
+        };
+        match result {
+            Ok(msg) => {
+                self.add_log(msg.clone());
+                self.status_message = Some(msg);
+
+                let selected = self.selected_index;
+                self.load_ignored_items();
+                if !self.filtered_indices.is_empty() {
+                    self.selected_index = selected.min(self.items.len() - 1);
+                }
+            }
+            Err(e) => {
+                let msg = format!("Failed to un-ignore: {}", e);
+                self.add_log(msg.clone());
+                self.status_message = Some(msg);
+            }
+        }
+    }
+
     fn show_configuration(&mut self) {
         // Save current state for going back
         self.save_current_navigation_state();
@@ -2233,6 +3258,7 @@ impl App {
             scroll_offset: self.scroll_offset,
             search_query: self.search_query.clone(),
             filtered_indices: self.filtered_indices.clone(),
+            sort: self.stream_sort,
         };
 
         // Transition to Configuration state
@@ -2242,65 +3268,304 @@ impl App {
     }
 
     fn update_configuration_items(&mut self) {
-        self.items = vec![
-            format!("Play Mode: {}", self.config.settings.play_mode),
-            format!(
-                "Use .ts URL for live streams: {}",
-                if self.config.settings.use_ts_for_live {
-                    "Enabled"
-                } else {
-                    "Disabled"
+        let enabled = |on: bool| if on { "Enabled" } else { "Disabled" };
+        self.items = CONFIG_SETTINGS_ITEMS
+            .iter()
+            .map(|setting| match setting {
+                ConfigSetting::PlayMode => {
+                    format!("Play Mode: {}", self.config.settings.play_mode)
                 }
-            ),
-            "Back".to_string(),
-        ];
+                ConfigSetting::UseTsForLive => format!(
+                    "Use .ts URL for live streams: {}",
+                    enabled(self.config.settings.use_ts_for_live)
+                ),
+                ConfigSetting::Preview => format!(
+                    "Preview pane: {}",
+                    enabled(self.config.settings.show_preview)
+                ),
+                ConfigSetting::ParentalControls => format!(
+                    "Parental controls: {}",
+                    enabled(self.config.settings.parental.enabled)
+                ),
+                ConfigSetting::ParentalPin => "Change parental PIN".to_string(),
+            })
+            .collect();
+        self.items.extend(
+            self.config
+                .providers
+                .iter()
+                .map(|p| format!("Provider: {}", p.label())),
+        );
+        self.items.push("Add provider".to_string());
+        self.items.push("Back".to_string());
         self.reset_filter();
     }
 
+    /// Index of the provider selected on the configuration screen
+    fn selected_config_provider(&self) -> Option<usize> {
+        self.selected_index
+            .checked_sub(CONFIG_SETTINGS_ITEMS.len())
+            .filter(|&index| index < self.config.providers.len())
+    }
+
     fn handle_configuration_selection(&mut self) {
+        if let Some(&setting) = CONFIG_SETTINGS_ITEMS.get(self.selected_index) {
+            self.handle_config_setting(setting);
+            return;
+        }
+        let providers = CONFIG_SETTINGS_ITEMS.len() + self.config.providers.len();
         match self.selected_index {
-            0 => {
+            index if index < providers => {
+                let index = index - CONFIG_SETTINGS_ITEMS.len();
+                let provider = self.config.providers[index].clone();
+                self.prompt_provider_field(Some(index), provider, ProviderField::Name);
+            }
+            index if index == providers => {
+                let provider = ProviderConfig {
+                    id: None,
+                    name: None,
+                    url: String::new(),
+                    username: String::new(),
+                    password: String::new(),
+                };
+                self.prompt_provider_field(None, provider, ProviderField::Name);
+            }
+            _ => {
+                // Back
+                self.save_current_navigation_state();
+                self.state = AppState::MainMenu;
+                self.restore_navigation_state(&AppState::MainMenu);
+                self.update_main_menu_items();
+            }
+        }
+    }
+
+    fn handle_config_setting(&mut self, setting: ConfigSetting) {
+        match setting {
+            ConfigSetting::PlayMode => {
                 // Toggle play mode
-                self.config.settings.play_mode = match self.config.settings.play_mode {
+                let play_mode = match self.config.settings.play_mode {
                     PlayMode::Mpv => PlayMode::MpvInTerminal,
                     PlayMode::MpvInTerminal => PlayMode::Mpv,
                 };
-                // Save configuration
-                if let Some(path) = crate::config::Config::default_config_path() {
-                    if let Err(e) = self.config.save(&path) {
-                        self.add_log(format!("Failed to save settings: {}", e));
-                    } else {
-                        self.add_log("Settings saved".to_string());
-                    }
+                self.save_settings(|config| config.settings.play_mode = play_mode);
+            }
+            ConfigSetting::UseTsForLive => {
+                // Toggle .ts URL preference
+                let use_ts_for_live = !self.config.settings.use_ts_for_live;
+                self.save_settings(|config| config.settings.use_ts_for_live = use_ts_for_live);
+            }
+            ConfigSetting::Preview => {
+                self.toggle_preview();
+                self.update_configuration_items();
+            }
+            ConfigSetting::ParentalControls => {
+                // Turning parental controls off needs the PIN; turning them on sets one
+                if self.config.settings.parental.enabled {
+                    self.prompt_pin("Parental PIN", TextInputPurpose::DisableParental);
+                } else if self.config.settings.parental.pin_hash.is_some() {
+                    self.config.settings.parental.enabled = true;
+                    self.parental_unlocked = false;
+                    self.save_parental_settings("Parental controls enabled");
                 } else {
-                    self.add_log("Failed to determine config path".to_string());
+                    self.prompt_pin("New parental PIN", TextInputPurpose::SetParentalPin);
                 }
-                self.update_configuration_items();
             }
-            1 => {
-                // Toggle .ts URL preference
-                self.config.settings.use_ts_for_live = !self.config.settings.use_ts_for_live;
-                // Save configuration
-                if let Some(path) = crate::config::Config::default_config_path() {
-                    if let Err(e) = self.config.save(&path) {
-                        self.add_log(format!("Failed to save settings: {}", e));
-                    } else {
-                        self.add_log("Settings saved".to_string());
-                    }
+            ConfigSetting::ParentalPin => {
+                if self.config.settings.parental.pin_hash.is_some() {
+                    self.prompt_pin("Current parental PIN", TextInputPurpose::ChangeParentalPin);
                 } else {
-                    self.add_log("Failed to determine config path".to_string());
+                    self.prompt_pin("New parental PIN", TextInputPurpose::SetParentalPin);
                 }
-                self.update_configuration_items();
             }
-            2 => {
-                // Back
-                self.save_current_navigation_state();
-                self.state = AppState::MainMenu;
-                self.restore_navigation_state(&AppState::MainMenu);
-                self.update_main_menu_items();
+        }
+    }
+
+    fn prompt_provider_field(
+        &mut self,
+        index: Option<usize>,
+        draft: ProviderConfig,
+        field: ProviderField,
+    ) {
+        let verb = if index.is_some() { "Edit" } else { "New" };
+        self.text_input = Some(TextInput {
+            prompt: format!("{} provider - {}", verb, field.prompt()),
+            value: field.value(&draft),
+            purpose: TextInputPurpose::EditProvider {
+                index,
+                draft,
+                field,
+            },
+        });
+    }
+
+    /// Take one typed provider field, then ask for the next or save and test the provider
+    fn submit_provider_field(
+        &mut self,
+        index: Option<usize>,
+        mut draft: ProviderConfig,
+        field: ProviderField,
+        value: String,
+    ) {
+        // Passwords are kept exactly as typed, spaces included
+        let value = match field {
+            ProviderField::Password => value,
+            _ => value.trim().to_string(),
+        };
+        let invalid = match field {
+            ProviderField::Name => {
+                draft.name = Some(value).filter(|name| !name.is_empty());
+                None
+            }
+            // A pasted get.php or player_api.php link fills in the credentials too
+            ProviderField::Url => match crate::setup::parse_provider_link(&value) {
+                Ok(link) => {
+                    draft.url = link.server;
+                    draft.username = link.username.unwrap_or(draft.username);
+                    draft.password = link.password.unwrap_or(draft.password);
+                    None
+                }
+                Err(e) => {
+                    draft.url = value;
+                    Some(e.to_string())
+                }
+            },
+            ProviderField::Username => {
+                draft.username = value;
+                draft
+                    .username
+                    .is_empty()
+                    .then(|| "Username is required".to_string())
+            }
+            ProviderField::Password => {
+                draft.password = value;
+                draft
+                    .password
+                    .is_empty()
+                    .then(|| "Password is required".to_string())
+            }
+        };
+        if let Some(message) = invalid {
+            self.status_message = Some(message);
+            self.prompt_provider_field(index, draft, field);
+            return;
+        }
+        if let Some(next) = field.next() {
+            self.prompt_provider_field(index, draft, next);
+            return;
+        }
+
+        let mut others = self.config.providers.clone();
+        if let Some(index) = index {
+            let previous = others.remove(index);
+            draft.keep_identity(&previous);
+        }
+        if let Err(e) = crate::setup::validate_provider(&draft, &others) {
+            self.status_message = Some(format!("{}", e));
+            self.prompt_provider_field(index, draft, ProviderField::Name);
+            return;
+        }
+
+        // Saved before the test, as setup does, so a provider that is down can be added
+        let label = draft.label();
+        // Matched by identity rather than index, as the file may have changed meanwhile
+        let identifier = draft.identifier();
+        let saved = self.update_config(|config| {
+            match config
+                .providers
+                .iter()
+                .position(|p| p.identifier() == identifier)
+            {
+                Some(index) => config.providers[index] = draft.clone(),
+                None => config.providers.push(draft.clone()),
+            }
+        });
+        match saved {
+            Ok(()) => {
+                self.status_message = Some(format!("Saved {}, testing the connection...", label));
+                self.spawn_provider_test(draft, true);
+            }
+            Err(e) => self.add_log(format!("Failed to save settings: {}", e)),
+        }
+        self.update_configuration_items();
+    }
+
+    /// Check a provider's URL and credentials in the background
+    fn spawn_provider_test(&mut self, provider: ProviderConfig, saved: bool) {
+        let sender = self.background_tx.clone();
+        tokio::spawn(async move {
+            let result = crate::setup::check_provider(&provider)
+                .await
+                .map(Box::new)
+                .map_err(|e| format!("{:#}", e));
+            let _ = sender.send(BackgroundResult::ProviderTest {
+                provider,
+                saved,
+                result,
+            });
+        });
+    }
+
+    /// Report a provider's connection test, describing its account if it worked
+    fn apply_provider_test(
+        &mut self,
+        provider: &ProviderConfig,
+        saved: bool,
+        result: Result<Box<UserInfoResponse>, String>,
+    ) {
+        let label = provider.label();
+        let message = match (result, saved) {
+            (Ok(response), true) => {
+                format!(
+                    "Saved {}: {}",
+                    label,
+                    self.account_summary(provider, &response)
+                )
             }
-            _ => {}
+            (Ok(response), false) => {
+                format!("{}: {}", label, self.account_summary(provider, &response))
+            }
+            (Err(e), true) => format!("Saved {}, but the connection test failed: {}", label, e),
+            (Err(e), false) => format!("{}: connection failed: {}", label, e),
+        };
+        self.add_log(message.clone());
+        self.status_message = Some(message);
+    }
+
+    /// Status, expiry and connections of a provider's account, logging any alerts
+    fn account_summary(
+        &mut self,
+        provider: &ProviderConfig,
+        response: &UserInfoResponse,
+    ) -> String {
+        let now = chrono::Utc::now();
+        let account = AccountStatus::from_user_info(&response.user_info, now);
+
+        let mut details = vec![account.status.clone()];
+        details.push(match account.expires_at {
+            Some(expires) => format!(
+                "expires {}",
+                expires.with_timezone(&Local).format("%Y-%m-%d")
+            ),
+            None => "never expires".to_string(),
+        });
+        if let (Some(active), Some(max)) = (account.active_connections, account.max_connections) {
+            details.push(format!("{}/{} connections", active, max));
+        }
+        for alert in account.alerts(self.config.settings.expiry_warning_days, now) {
+            self.add_log(format!("⚠️ {}: {}", provider.label(), alert));
+            details.push(format!("⚠ {}", alert));
         }
+        details.join(", ")
+    }
+
+    fn test_selected_provider(&mut self) {
+        let Some(index) = self.selected_config_provider() else {
+            return;
+        };
+        let provider = self.config.providers[index].clone();
+        self.status_message = Some(format!("Testing {}...", provider.label()));
+        self.spawn_provider_test(provider, false);
     }
 
     async fn load_categories(&mut self, content_type: ContentType) {
@@ -2332,14 +3597,12 @@ impl App {
         if !force_refresh && let Some(cached) = self.cached_categories.get(&content_type) {
             let ct = content_type;
             // Filter out ignored categories from cache
-            let _provider_name = self
-                .current_provider_name
-                .as_ref()
-                .unwrap_or(&String::new())
-                .clone();
+            let matcher = self.matcher_for(&self.current_provider_hash());
             self.categories = cached
                 .iter()
-                .filter(|cat| !self.ignore_config.is_category_ignored(&cat.category_name))
+                .filter(|cat| {
+                    cat.category_id == "all" || !matcher.hides_category(ct.stream_type(), cat)
+                })
                 .cloned()
                 .collect();
             self.add_log(format!("Using cached {} categories", ct));
@@ -2381,14 +3644,13 @@ impl App {
                         .insert(content_type, categories.clone());
 
                     // Filter out ignored categories
-                    let _provider_name = self
-                        .current_provider_name
-                        .as_ref()
-                        .unwrap_or(&String::new())
-                        .clone();
+                    let matcher = self.matcher_for(&self.current_provider_hash());
                     self.categories = categories
                         .into_iter()
-                        .filter(|cat| !self.ignore_config.is_category_ignored(&cat.category_name))
+                        .filter(|cat| {
+                            cat.category_id == "all"
+                                || !matcher.hides_category(content_type.stream_type(), cat)
+                        })
                         .collect();
 
                     self.items = self
@@ -2447,36 +3709,12 @@ impl App {
             let cat_name = category.category_name.clone();
             self.streams = cached.clone();
 
-            // Filter out ignored channels for live TV
-            if content_type == ContentType::Live {
-                self.streams
-                    .retain(|s| !self.ignore_config.is_channel_ignored(&s.name));
-            }
+            self.filter_ignored_streams(content_type);
 
             self.add_log(format!("Using cached streams for {}", cat_name));
 
-            // Get list of favourites to mark them with a star
-            let favourites = if let Some(api) = &self.current_api {
-                api.favourites_manager
-                    .get_favourites(&api.provider_hash)
-                    .unwrap_or_default()
-            } else {
-                Vec::new()
-            };
-
-            // Create item list with stars for favourites
-            self.items = self
-                .streams
-                .iter()
-                .map(|s| {
-                    let is_favourite = favourites.iter().any(|f| f.stream_id == s.stream_id);
-                    if is_favourite {
-                        format!("⭐ {}", s.name)
-                    } else {
-                        s.name.clone()
-                    }
-                })
-                .collect();
+            self.items = self.stream_items(content_type);
+            self.apply_saved_stream_sort(&cache_key);
 
             self.reset_filter();
             self.state = AppState::StreamSelection(content_type, category.clone());
@@ -2563,7 +3801,7 @@ impl App {
                                         tv_archive: None,
                                         direct_source: None,
                                         tv_archive_duration: None,
-                                        is_adult: None,
+                                        is_adult: info.is_adult.clone(),
                                         container_extension: None,
                                         rating: None,
                                         rating_5based: None,
@@ -2590,7 +3828,7 @@ impl App {
                                     tv_archive: None,
                                     direct_source: None,
                                     tv_archive_duration: None,
-                                    is_adult: None,
+                                    is_adult: info.is_adult.clone(),
                                     container_extension: None,
                                     rating: None,
                                     rating_5based: None,
@@ -2604,39 +3842,15 @@ impl App {
             match result {
                 Ok(streams) => {
                     // Store in cache
-                    self.cached_streams.insert(cache_key, streams.clone());
+                    self.cached_streams
+                        .insert(cache_key.clone(), streams.clone());
 
                     self.streams = streams;
 
-                    // Filter out ignored channels for live TV
-                    if content_type == ContentType::Live {
-                        self.streams
-                            .retain(|s| !self.ignore_config.is_channel_ignored(&s.name));
-                    }
-
-                    // Get list of favourites to mark them with a star
-                    let favourites = if let Some(api) = &self.current_api {
-                        api.favourites_manager
-                            .get_favourites(&api.provider_hash)
-                            .unwrap_or_default()
-                    } else {
-                        Vec::new()
-                    };
+                    self.filter_ignored_streams(content_type);
 
-                    // Create item list with stars for favourites
-                    self.items = self
-                        .streams
-                        .iter()
-                        .map(|s| {
-                            let is_favourite =
-                                favourites.iter().any(|f| f.stream_id == s.stream_id);
-                            if is_favourite {
-                                format!("[FAV] {}", s.name)
-                            } else {
-                                s.name.clone()
-                            }
-                        })
-                        .collect();
+                    self.items = self.stream_items(content_type);
+                    self.apply_saved_stream_sort(&cache_key);
 
                     self.reset_filter();
                     self.state = AppState::StreamSelection(content_type, category.clone());
@@ -2656,6 +3870,134 @@ impl App {
         }
     }
 
+    /// The current provider's favourites, or none if it can't be read
+    fn favourites_index(&self) -> FavouritesIndex {
+        self.current_api
+            .as_ref()
+            .and_then(|api| api.favourites_manager.index(&api.provider_hash).ok())
+            .unwrap_or_default()
+    }
+
+    /// Display items for `self.streams`, with favourites marked
+    fn stream_items(&self, content_type: ContentType) -> Vec<String> {
+        let index = self.favourites_index();
+        self.streams
+            .iter()
+            .map(|s| {
+                if Self::favourite_stream_type(&index, s, content_type).is_some() {
+                    format!("[FAV] {}", s.name)
+                } else {
+                    s.name.clone()
+                }
+            })
+            .collect()
+    }
+
+    /// The stream type a stream is saved under as a favourite, if it is one
+    ///
+    /// Favourites saved elsewhere use the content type rather than the provider's.
+    fn favourite_stream_type<'a>(
+        index: &FavouritesIndex,
+        stream: &'a Stream,
+        content_type: ContentType,
+    ) -> Option<&'a str> {
+        [stream.stream_type.as_str(), content_type.stream_type()]
+            .into_iter()
+            .find(|stream_type| index.contains(stream_type, stream.stream_id))
+    }
+
+    /// Display items for `self.episodes`, marking favourite episodes and ones played from a
+    /// favourite series
+    fn episode_items(&self, series_id: u32) -> Vec<String> {
+        let index = self.favourites_index();
+        let watched = index
+            .get("series", series_id)
+            .map(|f| f.watched_episodes.as_slice())
+            .unwrap_or_default();
+        self.episodes
+            .iter()
+            .map(|e| {
+                let is_favourite = e.id.parse().is_ok_and(|id| index.contains("episode", id));
+                format!(
+                    "{}{}Episode {}: {}",
+                    if is_favourite { "[FAV] " } else { "" },
+                    if watched.contains(&e.id) { "✓ " } else { "" },
+                    e.episode_num,
+                    e.title
+                )
+            })
+            .collect()
+    }
+
+    /// Re-mark favourites in the current list after they changed, e.g. in another window
+    fn refresh_favourite_marks(&mut self) {
+        let items = match &self.state {
+            AppState::StreamSelection(content_type, _) => self.stream_items(*content_type),
+            AppState::EpisodeSelection(series, _) => self.episode_items(series.stream_id),
+            _ => return,
+        };
+        if items.len() == self.items.len() {
+            self.items = items;
+        }
+    }
+
+    /// Apply the sort remembered for a stream list to the freshly loaded streams and items
+    fn apply_saved_stream_sort(&mut self, key: &(ContentType, String)) {
+        self.stream_sort = self
+            .stream_selection_states
+            .get(key)
+            .map(|nav| nav.sort)
+            .unwrap_or_default();
+        self.sort_stream_items();
+    }
+
+    fn sort_stream_items(&mut self) {
+        if self.stream_sort == StreamSort::Provider {
+            return;
+        }
+
+        // Sort streams and their display items together so indices stay aligned
+        let mut rows: Vec<(Stream, String)> = std::mem::take(&mut self.streams)
+            .into_iter()
+            .zip(std::mem::take(&mut self.items))
+            .collect();
+        match self.stream_sort {
+            StreamSort::Provider => {}
+            StreamSort::Name => rows.sort_by_cached_key(|(s, _)| s.name.to_lowercase()),
+            StreamSort::Newest => rows.sort_by_key(|(s, _)| std::cmp::Reverse(s.added_at())),
+            StreamSort::Rating => rows.sort_by(|(a, _), (b, _)| {
+                b.rating_value()
+                    .partial_cmp(&a.rating_value())
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            }),
+        }
+        (self.streams, self.items) = rows.into_iter().unzip();
+    }
+
+    async fn cycle_stream_sort(&mut self, content_type: ContentType, category: Category) {
+        let selected_id = self.streams.get(self.selected_index).map(|s| s.stream_id);
+
+        self.stream_sort = self.stream_sort.next();
+        self.save_current_navigation_state();
+
+        // Reload from the cache in provider order, then sort with the new mode
+        self.load_streams_without_nav_restore(content_type, category)
+            .await;
+
+        if let Some(index) =
+            selected_id.and_then(|id| self.streams.iter().position(|s| s.stream_id == id))
+            && self.filtered_indices.contains(&index)
+        {
+            self.selected_index = index;
+        } else if let Some(&first) = self.filtered_indices.first() {
+            self.selected_index = first;
+        }
+        self.ensure_selected_visible();
+        self.save_current_navigation_state();
+
+        self.status_message = Some(format!("Sorted by: {}", self.stream_sort.label()));
+    }
+
     async fn load_seasons(&mut self, series: Stream) {
         self.state = AppState::Loading(format!("Loading seasons for {}...", series.name));
         self.add_log(format!("Loading seasons for: {}", series.name));
@@ -2712,11 +4054,7 @@ impl App {
                         if let Some(episodes) = episodes_map.get(&season.season_number.to_string())
                         {
                             self.episodes = episodes.clone();
-                            self.items = self
-                                .episodes
-                                .iter()
-                                .map(|e| format!("Episode {}: {}", e.episode_num, e.title))
-                                .collect();
+                            self.items = self.episode_items(series.stream_id);
                             self.reset_filter();
 
                             self.state = AppState::EpisodeSelection(series.clone(), season);
@@ -2740,116 +4078,464 @@ impl App {
         }
     }
 
-    async fn load_favourites_internal(&mut self, provider_filter: Option<String>) {
-        let is_per_provider = provider_filter.is_some();
-
-        if is_per_provider {
-            self.state = AppState::Loading("Loading provider favourites...".to_string());
-        } else {
-            self.state = AppState::Loading("Loading all favourites...".to_string());
-            self.current_provider_name = Some("Favourites".to_string());
+    async fn load_favourites_internal(&mut self, provider_filter: Option<String>) {
+        let is_per_provider = provider_filter.is_some();
+
+        if is_per_provider {
+            self.state = AppState::Loading("Loading provider favourites...".to_string());
+        } else {
+            self.state = AppState::Loading("Loading all favourites...".to_string());
+            self.current_provider_name = Some("Favourites".to_string());
+        }
+
+        let favourites_manager = match crate::FavouritesManager::new() {
+            Ok(fm) => fm,
+            Err(e) => {
+                self.state = AppState::Error(format!("Failed to access favourites: {}", e));
+                return;
+            }
+        };
+
+        let mut all_favourites = Vec::new();
+        let mut all_items = Vec::new();
+        let mut per_provider_favourites = Vec::new();
+
+        if let Some(provider_hash) = provider_filter {
+            // Load favourites for single provider
+            let provider_name = self
+                .current_provider_name
+                .clone()
+                .unwrap_or_else(|| "provider".to_string());
+            self.add_log(format!("Loading favourites for {}", provider_name));
+
+            match favourites_manager.get_favourites(&provider_hash) {
+                Ok(favs) => {
+                    let hidden = self.hidden_favourites(&favs).await;
+                    for fav in favs {
+                        if hidden.contains(&FavouriteKey::of(&fav)) {
+                            continue;
+                        }
+                        // Format consistently with cross-provider view
+                        let display_name = format!("[{}] {}", fav.stream_type, fav.name);
+                        all_items.push(display_name);
+                        per_provider_favourites.push(fav);
+                    }
+                }
+                Err(e) => {
+                    self.add_log(format!("Failed to load favourites: {}", e));
+                }
+            }
+
+            if per_provider_favourites.is_empty() {
+                self.state = AppState::Error("No favourites found for this provider".to_string());
+                return;
+            }
+
+            self.per_provider_favourites = per_provider_favourites;
+        } else {
+            // Load the favourites of the current group, which may span providers
+            let group_name = self
+                .current_favourite_group
+                .clone()
+                .unwrap_or_else(|| crate::favourites::DEFAULT_GROUP.to_string());
+            self.add_log(format!("Loading favourites in group {}", group_name));
+
+            // Map provider hashes back to the configured providers
+            let mut providers_by_hash = HashMap::new();
+            for provider in self.config.providers.clone() {
+                match crate::XTreamAPI::new_with_id(
+                    provider.url.clone(),
+                    provider.username.clone(),
+                    provider.password.clone(),
+                    provider.name.clone(),
+                    provider.id.clone(),
+                ) {
+                    Ok(api) => {
+                        providers_by_hash.insert(api.provider_hash.clone(), provider);
+                    }
+                    Err(e) => {
+                        self.add_log(format!("Failed to connect to provider: {}", e));
+                    }
+                }
+            }
+
+            match favourites_manager.get_group_favourites(&group_name) {
+                Ok(favs) => {
+                    let hidden = self.hidden_favourites(&favs).await;
+                    for fav in favs {
+                        if hidden.contains(&FavouriteKey::of(&fav)) {
+                            continue;
+                        }
+                        let Some(provider) = providers_by_hash.get(&fav.provider_hash) else {
+                            self.add_log(format!(
+                                "Skipping {}: its provider is no longer configured (re-match with 'iptv cli favourites verify --fix')",
+                                fav.name
+                            ));
+                            continue;
+                        };
+                        let provider_name = provider.name.as_ref().unwrap_or(&provider.url);
+                        all_items.push(format!(
+                            "[{}] {} [{}]",
+                            fav.stream_type, fav.name, provider_name
+                        ));
+                        all_favourites.push((fav, provider.clone()));
+                    }
+                }
+                Err(e) => {
+                    self.add_log(format!("Failed to load favourites: {}", e));
+                }
+            }
+
+            if all_favourites.is_empty() {
+                self.state = AppState::Error(format!("No favourites in group '{}'", group_name));
+                return;
+            }
+
+            self.cross_provider_favourites = all_favourites;
+        }
+
+        // Flag favourites whose stream has gone from the provider's cached list
+        let favourites: Vec<FavouriteStream> = if is_per_provider {
+            self.per_provider_favourites.clone()
+        } else {
+            self.cross_provider_favourites
+                .iter()
+                .map(|(favourite, _)| favourite.clone())
+                .collect()
+        };
+        self.dead_favourites = Self::find_dead_favourites(&favourites).await;
+        for (item, favourite) in all_items.iter_mut().zip(&favourites) {
+            if self.dead_favourites.contains(&FavouriteKey::of(favourite)) {
+                item.insert_str(0, DEAD_FAVOURITE_MARKER);
+            }
+        }
+        if !self.dead_favourites.is_empty() {
+            self.status_message = Some(format!(
+                "{} favourite(s) missing from the provider; run 'iptv cli favourites verify --fix' to re-match",
+                self.dead_favourites.len()
+            ));
+        }
+
+        self.items = all_items;
+        self.reset_filter();
+
+        if is_per_provider {
+            self.state = AppState::PerProviderFavourites;
+            self.restore_navigation_state(&AppState::PerProviderFavourites);
+            self.add_log(format!("Loaded {} provider favourites", self.items.len()));
+        } else {
+            self.state = AppState::CrossProviderFavourites;
+            self.restore_navigation_state(&AppState::CrossProviderFavourites);
+            self.add_log(format!("Loaded {} favourites", self.items.len()));
+        }
+    }
+
+    /// Favourites hidden by ignore rules or parental controls, checked against cached
+    /// categories and stream lists
+    async fn hidden_favourites(&self, favourites: &[FavouriteStream]) -> HashSet<FavouriteKey> {
+        let Ok(cache) = crate::CacheManager::new() else {
+            return HashSet::new();
+        };
+
+        let mut matchers: HashMap<&str, IgnoreMatcher> = HashMap::new();
+        let mut hidden = HashSet::new();
+        for favourite in favourites {
+            let hash = favourite.provider_hash.as_str();
+            if !matchers.contains_key(hash) {
+                let mut matcher = self.matcher_for(hash);
+                matcher.load_cached_categories(&cache, hash).await;
+                matchers.insert(hash, matcher);
+            }
+            if matchers[hash].hides_favourite(favourite) {
+                hidden.insert(FavouriteKey::of(favourite));
+            }
+        }
+        hidden
+    }
+
+    /// Check favourites against the cached stream lists, without fetching anything
+    async fn find_dead_favourites(favourites: &[FavouriteStream]) -> HashSet<FavouriteKey> {
+        let Ok(cache) = crate::CacheManager::new() else {
+            return HashSet::new();
+        };
+
+        let mut catalogue = crate::favourites::StreamCatalogue::new();
+        catalogue.load_for(&cache, favourites).await;
+        favourites
+            .iter()
+            .filter(|favourite| catalogue.check(favourite).is_dead())
+            .map(FavouriteKey::of)
+            .collect()
+    }
+
+    async fn load_favourite_groups(&mut self) {
+        self.current_provider_name = Some("Favourites".to_string());
+
+        let groups = match crate::FavouritesManager::new().and_then(|fm| fm.get_groups()) {
+            Ok(groups) => groups,
+            Err(e) => {
+                self.state = AppState::Error(format!("Failed to load favourite groups: {}", e));
+                return;
+            }
+        };
+
+        self.items = groups
+            .iter()
+            .map(|g| format!("{} ({})", g.name, g.members.len()))
+            .collect();
+        self.favourite_groups = groups;
+        self.reset_filter();
+        self.state = AppState::FavouriteGroups;
+        self.restore_navigation_state(&AppState::FavouriteGroups);
+    }
+
+    async fn move_selected_group(&mut self, up: bool) {
+        let Some(group) = self.favourite_groups.get(self.selected_index) else {
+            return;
+        };
+        let name = group.name.clone();
+
+        match crate::FavouritesManager::new().and_then(|fm| fm.move_group(&name, up)) {
+            Ok(true) => {
+                self.load_favourite_groups().await;
+                if let Some(index) = self.favourite_groups.iter().position(|g| g.name == name) {
+                    self.selected_index = index;
+                    self.ensure_selected_visible();
+                }
+            }
+            Ok(false) => {}
+            Err(e) => self.add_log(format!("Failed to move group: {}", e)),
+        }
+    }
+
+    async fn move_selected_favourite_in_group(&mut self, up: bool) {
+        let Some((favourite, _)) = self.cross_provider_favourites.get(self.selected_index) else {
+            return;
+        };
+        let key = FavouriteKey::of(favourite);
+        let name = favourite.name.clone();
+
+        match crate::FavouritesManager::new().and_then(|fm| fm.move_within_group(&key, up)) {
+            Ok(true) => {
+                self.add_log(format!("Moved {} {}", name, if up { "up" } else { "down" }));
+                self.load_all_favourites().await;
+
+                // Find the moved item's new position and select it
+                if let Some(index) = self
+                    .cross_provider_favourites
+                    .iter()
+                    .position(|(f, _)| FavouriteKey::of(f) == key)
+                {
+                    self.selected_index = index;
+                    self.ensure_selected_visible();
+                }
+            }
+            Ok(false) => {}
+            Err(e) => self.add_log(format!("Failed to move favourite: {}", e)),
+        }
+    }
+
+    fn prompt_move_to_group(&mut self, key: FavouriteKey) {
+        let groups = crate::FavouritesManager::new()
+            .and_then(|fm| fm.get_groups())
+            .map(|groups| {
+                groups
+                    .into_iter()
+                    .map(|g| g.name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_default();
+
+        self.text_input = Some(TextInput {
+            prompt: format!("Move to group ({})", groups),
+            value: String::new(),
+            purpose: TextInputPurpose::MoveToGroup(key),
+        });
+    }
+
+    async fn handle_text_input_key(&mut self, key: KeyEvent) {
+        let Some(input) = &mut self.text_input else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Esc => {
+                self.text_input = None;
+            }
+            KeyCode::Backspace => {
+                input.value.pop();
+            }
+            KeyCode::Char(c) => {
+                input.value.push(c);
+            }
+            KeyCode::Enter => {
+                if let Some(input) = self.text_input.take() {
+                    self.submit_text_input(input).await;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    async fn submit_text_input(&mut self, input: TextInput) {
+        if let TextInputPurpose::EditProvider {
+            index,
+            draft,
+            field,
+        } = input.purpose
+        {
+            self.submit_provider_field(index, draft, field, input.value);
+            return;
         }
-
+        let value = input.value.trim().to_string();
         let favourites_manager = match crate::FavouritesManager::new() {
             Ok(fm) => fm,
             Err(e) => {
-                self.state = AppState::Error(format!("Failed to access favourites: {}", e));
+                self.add_log(format!("Failed to access favourites: {}", e));
                 return;
             }
         };
 
-        let mut all_favourites = Vec::new();
-        let mut all_items = Vec::new();
-        let mut per_provider_favourites = Vec::new();
+        let (result, message) = match &input.purpose {
+            TextInputPurpose::CreateGroup => (
+                favourites_manager.create_group(&value),
+                format!("Created group '{}'", value),
+            ),
+            TextInputPurpose::RenameGroup(old_name) => (
+                favourites_manager.rename_group(old_name, &value),
+                format!("Renamed group '{}' to '{}'", old_name, value),
+            ),
+            TextInputPurpose::MoveToGroup(key) => (
+                favourites_manager.move_to_group(key, &value),
+                format!("Moved to group '{}'", value),
+            ),
+            TextInputPurpose::UnlockParental
+            | TextInputPurpose::DisableParental
+            | TextInputPurpose::ChangeParentalPin
+            | TextInputPurpose::SetParentalPin => {
+                self.submit_pin(&input);
+                return;
+            }
+            TextInputPurpose::EditProvider { .. } => return,
+        };
 
-        if let Some(provider_hash) = provider_filter {
-            // Load favourites for single provider
-            let provider_name = self
-                .current_provider_name
-                .clone()
-                .unwrap_or_else(|| "provider".to_string());
-            self.add_log(format!("Loading favourites for {}", provider_name));
+        match result {
+            Ok(()) => {
+                self.add_log(message.clone());
+                self.status_message = Some(message);
+            }
+            Err(e) => {
+                self.status_message = Some(format!("{}", e));
+                return;
+            }
+        }
 
-            match favourites_manager.get_favourites(&provider_hash) {
-                Ok(favs) => {
-                    for fav in favs {
-                        // Format consistently with cross-provider view
-                        let display_name = format!("[{}] {}", fav.stream_type, fav.name);
-                        all_items.push(display_name);
-                        per_provider_favourites.push(fav);
-                    }
+        // Refresh the screen the prompt was opened from
+        match self.state {
+            AppState::FavouriteGroups => {
+                self.load_favourite_groups().await;
+                if let Some(index) = self.favourite_groups.iter().position(|g| g.name == value) {
+                    self.selected_index = index;
+                    self.ensure_selected_visible();
                 }
-                Err(e) => {
-                    self.add_log(format!("Failed to load favourites: {}", e));
+            }
+            AppState::CrossProviderFavourites => {
+                let selected = self.selected_index;
+                self.load_all_favourites().await;
+                if matches!(self.state, AppState::CrossProviderFavourites) {
+                    self.selected_index = selected.min(self.items.len().saturating_sub(1));
+                    self.ensure_selected_visible();
                 }
             }
+            _ => {}
+        }
+    }
 
-            if per_provider_favourites.is_empty() {
-                self.state = AppState::Error("No favourites found for this provider".to_string());
+    fn prompt_pin(&mut self, prompt: &str, purpose: TextInputPurpose) {
+        self.text_input = Some(TextInput {
+            prompt: prompt.to_string(),
+            value: String::new(),
+            purpose,
+        });
+    }
+
+    fn submit_pin(&mut self, input: &TextInput) {
+        let pin = input.value.trim();
+        let settings = &self.config.settings.parental;
+
+        if matches!(input.purpose, TextInputPurpose::SetParentalPin) {
+            if pin.len() < parental::MIN_PIN_LENGTH {
+                self.status_message = Some(format!(
+                    "The PIN needs at least {} characters",
+                    parental::MIN_PIN_LENGTH
+                ));
                 return;
             }
+            self.config.settings.parental.pin_hash = Some(parental::hash_pin(pin));
+            self.config.settings.parental.enabled = true;
+            self.parental_unlocked = false;
+            self.save_parental_settings("Parental PIN set and parental controls enabled");
+            return;
+        }
 
-            self.per_provider_favourites = per_provider_favourites;
-        } else {
-            // Load favourites from all providers
-            self.add_log("Loading favourites from all providers".to_string());
-
-            let providers = self.config.providers.clone();
-            for provider in &providers {
-                let api = match crate::XTreamAPI::new_with_id(
-                    provider.url.clone(),
-                    provider.username.clone(),
-                    provider.password.clone(),
-                    provider.name.clone(),
-                    provider.id.clone(),
-                ) {
-                    Ok(mut api) => {
-                        api.disable_progress();
-                        api
-                    }
-                    Err(e) => {
-                        self.add_log(format!("Failed to connect to provider: {}", e));
-                        continue;
-                    }
-                };
+        if !parental::verify_pin(settings, pin) {
+            self.add_log("Wrong parental PIN entered".to_string());
+            self.status_message = Some("Wrong PIN".to_string());
+            return;
+        }
 
-                match favourites_manager.get_favourites(&api.provider_hash) {
-                    Ok(favs) => {
-                        for fav in favs {
-                            let provider_name = provider.name.as_ref().unwrap_or(&provider.url);
-                            all_items.push(format!(
-                                "[{}] {} [{}]",
-                                fav.stream_type, fav.name, provider_name
-                            ));
-                            all_favourites.push((fav, provider.clone()));
-                        }
-                    }
-                    Err(e) => {
-                        self.add_log(format!("Failed to load favourites: {}", e));
-                    }
-                }
+        match input.purpose {
+            TextInputPurpose::UnlockParental => {
+                self.parental_unlocked = true;
+                self.status_message = Some("Adult content unlocked for this session".to_string());
+                self.update_main_menu_items();
             }
-
-            if all_favourites.is_empty() {
-                self.state = AppState::Error("No favourites found across any provider".to_string());
-                return;
+            TextInputPurpose::DisableParental => {
+                self.config.settings.parental.enabled = false;
+                self.parental_unlocked = false;
+                self.save_parental_settings("Parental controls disabled");
             }
-
-            self.cross_provider_favourites = all_favourites;
+            TextInputPurpose::ChangeParentalPin => {
+                self.prompt_pin("New parental PIN", TextInputPurpose::SetParentalPin);
+            }
+            _ => {}
         }
+    }
 
-        self.items = all_items;
-        self.reset_filter();
+    fn save_parental_settings(&mut self, message: &str) {
+        let parental = self.config.settings.parental.clone();
+        match self.update_config(|config| config.settings.parental = parental.clone()) {
+            Ok(()) => {
+                self.add_log(message.to_string());
+                self.status_message = Some(message.to_string());
+            }
+            Err(e) => self.add_log(format!("Failed to save settings: {}", e)),
+        }
+        self.update_configuration_items();
+    }
 
-        if is_per_provider {
-            self.state = AppState::PerProviderFavourites;
-            self.restore_navigation_state(&AppState::PerProviderFavourites);
-            self.add_log(format!("Loaded {} provider favourites", self.items.len()));
-        } else {
-            self.state = AppState::CrossProviderFavourites;
-            self.restore_navigation_state(&AppState::CrossProviderFavourites);
-            self.add_log(format!("Loaded {} favourites", self.items.len()));
+    /// Change a setting from the configuration screen and save it
+    fn save_settings(&mut self, update: impl Fn(&mut crate::config::Config)) {
+        match self.update_config(update) {
+            Ok(()) => self.add_log("Settings saved".to_string()),
+            Err(e) => self.add_log(format!("Failed to save settings: {}", e)),
         }
+        self.update_configuration_items();
+    }
+
+    /// Apply a change to the config and save it
+    ///
+    /// The config file is re-read under its lock before the change is applied again, so
+    /// changes made by another process since it was loaded are kept.
+    fn update_config(&mut self, update: impl Fn(&mut crate::config::Config)) -> anyhow::Result<()> {
+        update(&mut self.config);
+        let path = crate::config::Config::default_config_path()
+            .ok_or_else(|| anyhow::anyhow!("Failed to determine config path"))?;
+        self.config.update(&path, |config| {
+            update(config);
+            Ok(())
+        })
     }
 
     async fn load_all_favourites(&mut self) {
@@ -2868,21 +4554,16 @@ impl App {
         self.load_favourites_internal(Some(api_hash)).await;
     }
 
-    async fn toggle_favourite_stream(&mut self, stream: &Stream) {
+    async fn toggle_favourite_stream(&mut self, stream: &Stream, content_type: ContentType) {
+        let index = self.favourites_index();
         if let Some(api) = &self.current_api {
-            // Check if this stream is already a favourite
-            let favourites = api
-                .favourites_manager
-                .get_favourites(&api.provider_hash)
-                .unwrap_or_default();
-            let is_favourite = favourites.iter().any(|f| f.stream_id == stream.stream_id);
-
-            if is_favourite {
+            // Check if this stream is already a favourite, under whichever type it was saved
+            if let Some(stream_type) = Self::favourite_stream_type(&index, stream, content_type) {
                 // Remove from favourites
                 let _ = api.favourites_manager.remove_favourite(
                     &api.provider_hash,
                     stream.stream_id,
-                    &stream.stream_type,
+                    stream_type,
                 );
                 self.add_log(format!("Removed {} from favourites", stream.name));
 
@@ -2897,14 +4578,13 @@ impl App {
                 }
             } else {
                 // Add to favourites
-                let favourite = crate::xtream::FavouriteStream {
-                    stream_id: stream.stream_id,
-                    name: stream.name.clone(),
-                    stream_type: stream.stream_type.clone(),
-                    provider_hash: api.provider_hash.clone(),
-                    added_date: chrono::Utc::now(),
-                    category_id: stream.category_id.clone(),
-                };
+                let favourite = crate::xtream::FavouriteStream::new(
+                    &api.provider_hash,
+                    stream.stream_id,
+                    stream.name.clone(),
+                    &stream.stream_type,
+                    stream.category_id.clone(),
+                );
 
                 let _ = api
                     .favourites_manager
@@ -2924,6 +4604,103 @@ impl App {
         }
     }
 
+    fn toggle_favourite_episode(&mut self, series: &Stream, episode: &ApiEpisode) {
+        let Some(api) = &self.current_api else {
+            return;
+        };
+        let Some(favourite) = FavouriteStream::from_episode(
+            &api.provider_hash,
+            series.stream_id,
+            &series.name,
+            series.category_id.clone(),
+            episode,
+        ) else {
+            self.status_message = Some(format!(
+                "Episode ID '{}' can't be saved as a favourite",
+                episode.id
+            ));
+            return;
+        };
+
+        let favourites_manager = &api.favourites_manager;
+        let is_favourite = favourites_manager
+            .is_favourite(&api.provider_hash, favourite.stream_id, "episode")
+            .unwrap_or(false);
+        let result = if is_favourite {
+            favourites_manager.remove_favourite(&api.provider_hash, favourite.stream_id, "episode")
+        } else {
+            favourites_manager.add_favourite(&api.provider_hash, favourite.clone())
+        };
+        if let Err(e) = result {
+            self.add_log(format!("Failed to update favourites: {}", e));
+            return;
+        }
+
+        // Clear cross-provider favourites cache to force reload
+        self.cross_provider_favourites.clear();
+
+        if let Some(item) = self.items.get_mut(self.selected_index) {
+            if is_favourite {
+                if let Some(rest) = item.strip_prefix("[FAV] ") {
+                    *item = rest.to_string();
+                }
+                self.add_log(format!("Removed {} from favourites", favourite.name));
+            } else {
+                *item = format!("[FAV] {}", item);
+                self.add_log(format!("Added {} to favourites", favourite.name));
+            }
+        }
+    }
+
+    /// Record a played episode on its series favourite, for "next unwatched" in rofi
+    fn mark_episode_watched(&mut self, series_id: u32, episode_id: &str) {
+        let Some(api) = &self.current_api else {
+            return;
+        };
+        match api
+            .favourites_manager
+            .mark_episode_watched(&api.provider_hash, series_id, episode_id)
+        {
+            Ok(true) => {
+                if matches!(self.state, AppState::EpisodeSelection(..))
+                    && let Some(item) = self.items.get_mut(self.selected_index)
+                    && !item.contains("✓ ")
+                {
+                    let at = if item.starts_with("[FAV] ") { 6 } else { 0 };
+                    item.insert_str(at, "✓ ");
+                }
+            }
+            Ok(false) => {}
+            Err(e) => self.add_log(format!("Failed to record watched episode: {}", e)),
+        }
+    }
+
+    /// Open a series favourite's seasons; going back returns to the favourites list
+    async fn open_favourite_series(&mut self, favourite: &FavouriteStream) {
+        let series = Stream {
+            num: 0,
+            name: favourite.name.clone(),
+            stream_type: "series".to_string(),
+            stream_id: favourite.stream_id,
+            stream_icon: None,
+            epg_channel_id: None,
+            added: None,
+            category_id: favourite.category_id.clone(),
+            category_ids: None,
+            custom_sid: None,
+            tv_archive: None,
+            direct_source: None,
+            tv_archive_duration: None,
+            is_adult: None,
+            rating: None,
+            rating_5based: None,
+            container_extension: None,
+        };
+        self.save_current_navigation_state();
+        self.push_navigation_state();
+        self.load_seasons(series).await;
+    }
+
     fn play_stream(&mut self, stream: &Stream) {
         // Store the current state to return to after starting playback
         let return_state = self.state.clone();
@@ -3129,6 +4906,7 @@ impl App {
             scroll_offset: self.scroll_offset,
             search_query: self.search_query.clone(),
             filtered_indices: self.filtered_indices.clone(),
+            sort: self.stream_sort,
         };
 
         // Create menu items
@@ -3389,6 +5167,8 @@ impl App {
             Ok(vod_info) => {
                 self.add_log("Successfully loaded movie information".to_string());
                 self.vod_info = Some(vod_info.clone());
+                self.vod_preview_cache
+                    .insert(stream.stream_id, vod_info.clone());
 
                 // Helper function to wrap text
                 let wrap_text = |text: &str, width: usize, indent: &str| -> Vec<String> {
@@ -3630,6 +5410,7 @@ impl App {
             scroll_offset: self.scroll_offset,
             search_query: self.search_query.clone(),
             filtered_indices: self.filtered_indices.clone(),
+            sort: self.stream_sort,
         };
 
         match self.state.clone() {
@@ -3650,6 +5431,9 @@ impl App {
             AppState::SeasonSelection(_) => {
                 self.season_selection_state = nav_state;
             }
+            AppState::FavouriteGroups => {
+                self.favourite_groups_state = nav_state;
+            }
             AppState::CrossProviderFavourites => {
                 self.cross_provider_favourites_state = nav_state;
             }
@@ -3678,6 +5462,7 @@ impl App {
                 .cloned()
                 .unwrap_or_else(NavigationState::new),
             AppState::SeasonSelection(_) => self.season_selection_state.clone(),
+            AppState::FavouriteGroups => self.favourite_groups_state.clone(),
             AppState::CrossProviderFavourites => self.cross_provider_favourites_state.clone(),
             AppState::PerProviderFavourites => self.per_provider_favourites_state.clone(),
             AppState::Configuration => self.config_state.clone(),
@@ -3695,6 +5480,14 @@ impl App {
         if nav_state.filtered_indices.is_empty() && nav_state.search_query.is_empty() {
             self.filtered_indices = (0..self.items.len()).collect();
             self.search_query = nav_state.search_query;
+        } else if nav_state.filtered_indices.is_empty() {
+            // A state restored from a saved session only has the query, so re-run the filter
+            self.search_query = nav_state.search_query;
+            self.apply_filter();
+            self.selected_index = nav_state
+                .selected_index
+                .min(self.items.len().saturating_sub(1));
+            self.scroll_offset = nav_state.scroll_offset;
         } else {
             // Validate that filtered indices are within bounds
             self.filtered_indices = nav_state
@@ -3718,6 +5511,246 @@ impl App {
         }
     }
 
+    /// Capture the screen path, selections and filters so the next launch can reopen them
+    pub fn session_state(&mut self) -> SessionState {
+        self.save_current_navigation_state();
+
+        // Each stacked screen records the item that was entered from it
+        let mut path: Vec<String> = self
+            .navigation_stack
+            .iter()
+            .filter_map(|(state, items, nav)| self.session_key(state, items, nav.selected_index))
+            .collect();
+
+        // Launched straight into a provider, so the provider selection step is missing
+        let starts_at_provider_selection = matches!(
+            self.navigation_stack.first(),
+            Some((AppState::ProviderSelection, _, _))
+        ) || (self.navigation_stack.is_empty()
+            && matches!(self.state, AppState::ProviderSelection));
+        if !starts_at_provider_selection
+            && self.current_api.is_some()
+            && let Some(provider) = &self.current_provider_name
+        {
+            path.insert(0, provider.clone());
+        }
+
+        SessionState {
+            provider: self
+                .current_api
+                .as_ref()
+                .and(self.current_provider_name.clone()),
+            path,
+            provider_selection: self.provider_selection_state.clone(),
+            main_menu: self.main_menu_state.clone(),
+            categories: self
+                .category_selection_states
+                .iter()
+                .map(|(content_type, nav)| (*content_type, nav.clone()))
+                .collect(),
+            streams: self
+                .stream_selection_states
+                .iter()
+                .map(|((content_type, category_id), nav)| {
+                    (*content_type, category_id.clone(), nav.clone())
+                })
+                .collect(),
+        }
+    }
+
+    /// Key saved for an item entered from a screen, which stays the same when labels with
+    /// counts change: the category ID, the favourite group name, otherwise the label
+    fn session_key(&self, state: &AppState, items: &[String], index: usize) -> Option<String> {
+        match state {
+            // Entering a category or group doesn't replace these lists
+            AppState::CategorySelection(_) => {
+                self.categories.get(index).map(|c| c.category_id.clone())
+            }
+            AppState::FavouriteGroups => self.favourite_groups.get(index).map(|g| g.name.clone()),
+            _ => items.get(index).cloned(),
+        }
+    }
+
+    /// Index of the item on the current screen with a saved session key
+    fn session_item_index(&self, key: &str) -> Option<usize> {
+        match self.state {
+            AppState::CategorySelection(_) => {
+                self.categories.iter().position(|c| c.category_id == key)
+            }
+            AppState::FavouriteGroups => self.favourite_groups.iter().position(|g| g.name == key),
+            _ => self.items.iter().position(|item| item == key),
+        }
+    }
+
+    /// Reopen the saved session by replaying its screen path from provider selection
+    pub async fn restore_session(&mut self) {
+        if !matches!(self.state, AppState::ProviderSelection) {
+            return;
+        }
+
+        let session = match SessionState::load() {
+            Ok(Some(session)) => session,
+            Ok(None) => return,
+            Err(e) => {
+                self.add_log(format!("Failed to load previous session: {}", e));
+                return;
+            }
+        };
+
+        self.provider_selection_state = session.provider_selection;
+        self.main_menu_state = session.main_menu;
+        self.restore_navigation_state(&AppState::ProviderSelection);
+
+        for key in &session.path {
+            // Only menus are replayed; entering a stream list item would start playback
+            let from_provider_selection = matches!(self.state, AppState::ProviderSelection);
+            if !from_provider_selection
+                && !matches!(
+                    self.state,
+                    AppState::MainMenu | AppState::CategorySelection(_) | AppState::FavouriteGroups
+                )
+            {
+                break;
+            }
+            let Some(index) = self.session_item_index(key) else {
+                break;
+            };
+
+            self.selected_index = index;
+            self.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE))
+                .await;
+
+            // Connecting clears per-provider selections, so bring the saved ones back after
+            if from_provider_selection
+                && matches!(self.state, AppState::MainMenu)
+                && self.current_provider_name == session.provider
+            {
+                self.category_selection_states = session.categories.iter().cloned().collect();
+                self.stream_selection_states = session
+                    .streams
+                    .iter()
+                    .map(|(content_type, category_id, nav)| {
+                        ((*content_type, category_id.clone()), nav.clone())
+                    })
+                    .collect();
+            }
+        }
+
+        if !session.path.is_empty() {
+            self.add_log("Restored previous session".to_string());
+        }
+    }
+
+    /// Remember the current stream list so playback can zap through it
+    fn start_zap(&mut self, index: usize) {
+        let previous = self
+            .channel_zap
+            .as_ref()
+            .map(|zap| zap.channels[zap.current].stream_id);
+        let channels = self.streams.clone();
+        let last = previous
+            .and_then(|id| channels.iter().position(|s| s.stream_id == id))
+            .filter(|&i| i != index);
+        self.channel_zap = Some(ChannelZap {
+            channels,
+            current: index,
+            last,
+        });
+        self.channel_number_input = None;
+    }
+
+    /// Handle channel up/down, last channel and number entry, returning true if the key was used
+    fn handle_zap_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char(c @ '0'..='9') => {
+                let mut digits = self
+                    .channel_number_input
+                    .take()
+                    .map(|(digits, _)| digits)
+                    .unwrap_or_default();
+                digits.push(c);
+                self.status_message = Some(format!("Channel: {}_", digits));
+                self.channel_number_input = Some((digits, Instant::now()));
+            }
+            KeyCode::Enter if self.channel_number_input.is_some() => {
+                self.commit_channel_number();
+            }
+            KeyCode::Esc if self.channel_number_input.is_some() => {
+                self.channel_number_input = None;
+                self.status_message = None;
+            }
+            KeyCode::Char('+') | KeyCode::Char('=') => self.zap_by(1),
+            KeyCode::Char('-') => self.zap_by(-1),
+            KeyCode::Backspace => {
+                if let Some(last) = self.channel_zap.as_ref().and_then(|zap| zap.last) {
+                    self.zap_to(last);
+                } else {
+                    self.status_message = Some("No previous channel".to_string());
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn zap_by(&mut self, delta: isize) {
+        if let Some(zap) = &self.channel_zap {
+            let len = zap.channels.len() as isize;
+            let index = (zap.current as isize + delta).rem_euclid(len) as usize;
+            self.zap_to(index);
+        }
+    }
+
+    fn commit_channel_number(&mut self) {
+        let Some((digits, _)) = self.channel_number_input.take() else {
+            return;
+        };
+        let Ok(num) = digits.parse::<u32>() else {
+            return;
+        };
+
+        let index = self
+            .channel_zap
+            .as_ref()
+            .and_then(|zap| zap.channels.iter().position(|s| s.num == num));
+        match index {
+            Some(index) => {
+                self.status_message = None;
+                self.zap_to(index);
+            }
+            None => {
+                self.status_message = Some(format!("No channel {} in this category", num));
+            }
+        }
+    }
+
+    /// Switch the running player to another channel in the zap list
+    fn zap_to(&mut self, index: usize) {
+        let Some(zap) = &mut self.channel_zap else {
+            return;
+        };
+        if index == zap.current || index >= zap.channels.len() {
+            return;
+        }
+        zap.last = Some(zap.current);
+        zap.current = index;
+        let stream = zap.channels[index].clone();
+
+        // Keep the list cursor on the playing channel when it is on screen
+        if matches!(self.state, AppState::StreamSelection(ContentType::Live, _))
+            && let Some(position) = self
+                .streams
+                .iter()
+                .position(|s| s.stream_id == stream.stream_id)
+            && self.filtered_indices.contains(&position)
+        {
+            self.selected_index = position;
+            self.ensure_selected_visible();
+        }
+
+        self.play_stream(&stream);
+    }
+
     fn stop_playing(&mut self) {
         // Stop the player process
         let player = self.player.clone();
@@ -3725,6 +5758,9 @@ impl App {
             let _ = player.stop_tui().await;
         });
 
+        self.channel_zap = None;
+        self.channel_number_input = None;
+
         self.state = AppState::MainMenu;
         // Restore main menu navigation state
         self.restore_navigation_state(&AppState::MainMenu);
@@ -3807,6 +5843,85 @@ impl App {
         };
     }
 
+    /// Drop streams hidden by ignore rules, either themselves or through their category
+    fn filter_ignored_streams(&mut self, content_type: ContentType) {
+        let matcher = self.matcher_for(&self.current_provider_hash());
+        let categories: HashMap<&str, &Category> = self
+            .cached_categories
+            .get(&content_type)
+            .map(|categories| {
+                categories
+                    .iter()
+                    .map(|c| (c.category_id.as_str(), c))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        self.streams.retain(|s| {
+            let category = s
+                .category_id
+                .as_deref()
+                .and_then(|id| categories.get(id).copied());
+            !matcher.hides_stream(
+                content_type.stream_type(),
+                s.stream_id,
+                &s.name,
+                category,
+                parental::is_adult(s.is_adult.as_ref()),
+            )
+        });
+    }
+
+    /// Ignore rules and, unless unlocked, parental controls for a provider
+    fn matcher_for(&self, provider_hash: &str) -> IgnoreMatcher {
+        self.ignore_config
+            .matcher(provider_hash)
+            .with_parental(ParentalFilter::from_settings(
+                &self.config.settings.parental,
+                self.parental_unlocked,
+            ))
+    }
+
+    /// Hash of the connected provider, or empty when not connected
+    fn current_provider_hash(&self) -> String {
+        self.current_api
+            .as_ref()
+            .map(|api| api.provider_hash.clone())
+            .unwrap_or_default()
+    }
+
+    /// Move ignore rules from the old global lists onto each configured provider
+    fn migrate_ignore_rules(&mut self) {
+        let providers: Vec<(String, String)> = self
+            .config
+            .providers
+            .iter()
+            .filter_map(|provider| {
+                let api = crate::XTreamAPI::new_with_id(
+                    provider.url.clone(),
+                    provider.username.clone(),
+                    provider.password.clone(),
+                    provider.name.clone(),
+                    provider.id.clone(),
+                )
+                .ok()?;
+                Some((
+                    api.provider_hash,
+                    provider
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| provider.url.clone()),
+                ))
+            })
+            .collect();
+
+        match self.ignore_config.migrate_global_rules(&providers) {
+            Ok(true) => self.add_log("Moved global ignore rules onto each provider".to_string()),
+            Ok(false) => {}
+            Err(e) => self.add_log(format!("Failed to migrate ignore rules: {}", e)),
+        }
+    }
+
     fn get_current_category(&self) -> Option<Category> {
         // selected_index is already the actual index in the categories array
         if self.selected_index < self.categories.len() {
@@ -3820,6 +5935,7 @@ impl App {
     pub fn clear_internal_caches(&mut self) {
         self.cached_categories.clear();
         self.cached_streams.clear();
+        self.vod_preview_cache.clear();
     }
 
     fn add_log(&mut self, message: String) {
@@ -3868,3 +5984,11 @@ impl App {
         self.status_message = None;
     }
 }
+
+/// Check whether a VOD info line is one of the selectable menu actions
+fn is_vod_menu_item(item: &str) -> bool {
+    item.contains("Play Movie")
+        || item.contains("Play in Detached")
+        || item.contains("Copy URL")
+        || item.contains("Back")
+}
@@ -3,11 +3,13 @@
 
 pub mod app;
 pub mod event;
+pub mod session;
 pub mod ui;
 pub mod widgets;
 
 use anyhow::Result;
 use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture, MouseEventKind},
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
@@ -38,7 +40,7 @@ impl Tui {
 
     pub fn init(&mut self) -> Result<()> {
         enable_raw_mode()?;
-        execute!(io::stdout(), EnterAlternateScreen)?;
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
         self.terminal.hide_cursor()?;
         self.terminal.clear()?;
         Ok(())
@@ -51,7 +53,7 @@ impl Tui {
 
     pub fn exit(&mut self) -> Result<()> {
         disable_raw_mode()?;
-        execute!(io::stdout(), LeaveAlternateScreen)?;
+        execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
         self.terminal.show_cursor()?;
         Ok(())
     }
@@ -61,11 +63,18 @@ pub async fn run_tui(
     config: crate::config::Config,
     player: Player,
     provider: Option<String>,
+    restore_session: bool,
 ) -> Result<()> {
     let mut tui = Tui::new()?;
     tui.init()?;
 
+    // An explicit provider takes precedence over the saved session
+    let restore_session = restore_session && provider.is_none();
+
     let mut app = App::new(config, player.clone(), provider).await;
+    if restore_session {
+        app.restore_session().await;
+    }
     let res = run_app(&mut tui, &mut app).await;
 
     // Clean up player resources before exiting
@@ -73,6 +82,10 @@ pub async fn run_tui(
 
     tui.exit()?;
 
+    if let Err(e) = app.session_state().save() {
+        eprintln!("Failed to save session: {}", e);
+    }
+
     if let Err(err) = res {
         eprintln!("Error: {:?}", err);
     }
@@ -102,54 +115,11 @@ async fn run_app(tui: &mut Tui, app: &mut App) -> Result<()> {
 
         let should_redraw = match event {
             Ok(Event::Key(key_event)) => {
-                match app.handle_key_event(key_event).await {
-                    Some(app::Action::Quit) => break,
-                    Some(app::Action::CacheRefresh) => {
-                        // Exit TUI temporarily to run cache refresh
-                        tui.exit()?;
-
-                        // Get provider name before mutable borrow
-                        let provider_name = app
-                            .current_provider_name
-                            .clone()
-                            .unwrap_or_else(|| "Unknown".to_string());
-
-                        // Run the same cache refresh as CLI with progress enabled
-                        if let Some(api) = &mut app.current_api {
-                            // Enable progress bars for the refresh operation
-                            api.enable_progress();
-
-                            // This is the exact same call the CLI makes
-                            match api.refresh_cache().await {
-                                Ok(_) => {
-                                    println!("\n✓ Cache refreshed for {}", provider_name);
-                                }
-                                Err(e) => {
-                                    eprintln!(
-                                        "\nWarning: Failed to refresh cache for {}: {}",
-                                        provider_name, e
-                                    );
-                                }
-                            }
-
-                            // Disable progress bars again for TUI mode
-                            api.disable_progress();
-                        }
-
-                        // Clear local TUI caches
-                        app.clear_internal_caches();
-
-                        // Wait for user to continue
-                        println!("\nPress Enter to return to the TUI...");
-                        let mut input = String::new();
-                        std::io::stdin().read_line(&mut input)?;
-
-                        // Re-initialize TUI
-                        tui.init()?;
-                        true // Redraw after returning
-                    }
-                    _ => true, // Always redraw after key events
+                let action = app.handle_key_event(key_event).await;
+                if !handle_action(tui, app, action).await? {
+                    break;
                 }
+                true // Always redraw after key events
             }
             Ok(Event::Resize(_, _)) => {
                 // Debounce resize events - mark as pending but don't redraw immediately
@@ -157,7 +127,18 @@ async fn run_app(tui: &mut Tui, app: &mut App) -> Result<()> {
                 pending_resize = true;
                 false // Don't redraw immediately
             }
-            Ok(Event::Mouse(_)) => false, // Don't redraw on mouse events we don't handle
+            Ok(Event::Mouse(mouse_event)) => {
+                // Plain pointer motion changes nothing, so don't redraw for it
+                if matches!(mouse_event.kind, MouseEventKind::Moved) {
+                    false
+                } else {
+                    let action = app.handle_mouse_event(mouse_event).await;
+                    if !handle_action(tui, app, action).await? {
+                        break;
+                    }
+                    true
+                }
+            }
             Ok(Event::Tick) => {
                 // Periodic update
                 app.tick();
@@ -196,3 +177,57 @@ async fn run_app(tui: &mut Tui, app: &mut App) -> Result<()> {
 
     Ok(())
 }
+
+/// Handle an action returned by the app's key or mouse handlers.
+/// Returns false when the application should quit.
+async fn handle_action(tui: &mut Tui, app: &mut App, action: Option<app::Action>) -> Result<bool> {
+    match action {
+        Some(app::Action::Quit) => return Ok(false),
+        Some(app::Action::CacheRefresh) => {
+            // Exit TUI temporarily to run cache refresh
+            tui.exit()?;
+
+            // Get provider name before mutable borrow
+            let provider_name = app
+                .current_provider_name
+                .clone()
+                .unwrap_or_else(|| "Unknown".to_string());
+
+            // Run the same cache refresh as CLI with progress enabled
+            if let Some(api) = &mut app.current_api {
+                // Enable progress bars for the refresh operation
+                api.enable_progress();
+
+                // This is the exact same call the CLI makes
+                match api.refresh_cache().await {
+                    Ok(_) => {
+                        println!("\n✓ Cache refreshed for {}", provider_name);
+                    }
+                    Err(e) => {
+                        eprintln!(
+                            "\nWarning: Failed to refresh cache for {}: {}",
+                            provider_name, e
+                        );
+                    }
+                }
+
+                // Disable progress bars again for TUI mode
+                api.disable_progress();
+            }
+
+            // Clear local TUI caches
+            app.clear_internal_caches();
+
+            // Wait for user to continue
+            println!("\nPress Enter to return to the TUI...");
+            let mut input = String::new();
+            std::io::stdin().read_line(&mut input)?;
+
+            // Re-initialize TUI
+            tui.init()?;
+        }
+        _ => {}
+    }
+
+    Ok(true)
+}
@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MIT
+// SPDX-FileCopyrightText: (C) 2025 Cranky Kernel <crankykernel@proton.me>
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use super::app::{ContentType, NavigationState};
+use crate::storage;
+
+/// TUI state saved on exit so the next launch reopens where the user left off
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionState {
+    /// Provider connected at exit
+    pub provider: Option<String>,
+    /// Items entered to reach the last screen, starting at provider selection: category IDs,
+    /// favourite group names and otherwise labels
+    pub path: Vec<String>,
+    pub provider_selection: NavigationState,
+    pub main_menu: NavigationState,
+    pub categories: Vec<(ContentType, NavigationState)>,
+    pub streams: Vec<(ContentType, String, NavigationState)>,
+}
+
+impl SessionState {
+    /// Location of the session file in the XDG state directory
+    pub fn default_path() -> Option<PathBuf> {
+        std::env::var("XDG_STATE_HOME")
+            .ok()
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".local").join("state")))
+            .map(|dir| dir.join("iptv").join("session.json"))
+    }
+
+    /// Load the saved session, if there is one
+    pub fn load() -> Result<Option<Self>> {
+        let Some(path) = Self::default_path() else {
+            return Ok(None);
+        };
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read session file: {}", path.display()))?;
+        let session = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse session file: {}", path.display()))?;
+        Ok(Some(session))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::default_path().context("Could not determine state directory")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create state directory: {}", parent.display())
+            })?;
+        }
+
+        let content = serde_json::to_string_pretty(self).context("Failed to serialize session")?;
+        storage::write_atomic(&path, content)
+    }
+}
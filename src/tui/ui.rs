@@ -9,8 +9,12 @@ use ratatui::{
     widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
 };
 
-use super::app::{App, AppState, LogDisplayMode};
+use super::app::{
+    App, AppState, DEAD_FAVOURITE_MARKER, LogDisplayMode, PreviewDetails, StreamSort,
+};
 use super::widgets::{centered_rect, create_scrollable_help_widget};
+use crate::config::StreamColumn;
+use crate::xtream::Stream;
 
 pub fn draw(frame: &mut Frame, app: &mut App) {
     let size = frame.area();
@@ -79,6 +83,12 @@ fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
         }
         AppState::VodInfo(_) => "VOD Info".to_string(),
         AppState::Configuration => "Configuration".to_string(),
+        AppState::FavouriteGroups => "Groups".to_string(),
+        AppState::IgnoredItems => "Hidden Items".to_string(),
+        AppState::CrossProviderFavourites => app
+            .current_favourite_group
+            .clone()
+            .unwrap_or_else(|| "All".to_string()),
         AppState::Playing(name) => format!("Playing: {}", name),
         _ => "IPTV Player".to_string(),
     };
@@ -94,12 +104,36 @@ fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
         base_text
     };
 
-    let header = Paragraph::new(header_text)
-        .style(
+    let mut spans = vec![Span::styled(
+        header_text,
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    )];
+    // Subscription problems of the current provider
+    if !app.account_alerts.is_empty() && !matches!(app.state, AppState::ProviderSelection) {
+        let color = if app.account_alerts.iter().any(|a| a.is_critical()) {
+            Color::Red
+        } else {
+            Color::Yellow
+        };
+        let badge = app
+            .account_alerts
+            .iter()
+            .map(|a| a.to_string())
+            .collect::<Vec<_>>()
+            .join(" · ");
+        spans.push(Span::raw("  "));
+        spans.push(Span::styled(
+            format!(" ⚠ {} ", badge),
             Style::default()
-                .fg(Color::Cyan)
+                .fg(Color::Black)
+                .bg(color)
                 .add_modifier(Modifier::BOLD),
-        )
+        ));
+    }
+
+    let header = Paragraph::new(Line::from(spans))
         .alignment(Alignment::Center)
         .block(
             Block::default()
@@ -113,8 +147,20 @@ fn draw_header(frame: &mut Frame, app: &App, area: Rect) {
 fn draw_content(frame: &mut Frame, app: &mut App, area: Rect) {
     match app.log_display_mode {
         LogDisplayMode::None => {
-            // Normal view - full width for main content
-            draw_main_list(frame, app, area);
+            // Split view with a details pane beside the list when enabled
+            if app.config.settings.show_preview
+                && let Some(details) = app.preview_details()
+            {
+                let chunks = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+                    .split(area);
+                draw_main_list(frame, app, chunks[0]);
+                draw_preview_pane(frame, &details, chunks[1]);
+            } else {
+                // Normal view - full width for main content
+                draw_main_list(frame, app, area);
+            }
         }
         LogDisplayMode::Full => {
             // Draw logs in full window with scrolling
@@ -144,14 +190,61 @@ fn draw_main_list(frame: &mut Frame, app: &mut App, area: Rect) {
         format!(" {}Content ", provider_prefix)
     };
 
+    // Stream lists show their sort order and optional columns
+    let is_stream_list = matches!(app.state, AppState::StreamSelection(_, _));
+    let title = if is_stream_list && app.stream_sort != StreamSort::Provider {
+        format!("{}[Sort: {}] ", title, app.stream_sort.label())
+    } else {
+        title
+    };
+    let columns = if is_stream_list {
+        visible_stream_columns(app)
+    } else {
+        Vec::new()
+    };
+
     let block = Block::default()
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::White))
         .title(title);
 
-    let inner_area = block.inner(area);
+    let mut inner_area = block.inner(area);
     frame.render_widget(block, area);
 
+    // Column widths for stream lists; the name takes whatever is left
+    let num_width = app
+        .streams
+        .iter()
+        .map(|s| s.num.to_string().len())
+        .max()
+        .unwrap_or(0)
+        .max(3);
+    let name_width = (inner_area.width as usize)
+        .saturating_sub(3 + 1 + stream_columns_width(&columns, num_width));
+
+    if !columns.is_empty() && inner_area.height > 1 {
+        let header = Line::from(stream_row_spans(
+            None, "Name", &columns, num_width, name_width,
+        ))
+        .style(
+            Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+        frame.render_widget(
+            Paragraph::new(header),
+            Rect {
+                height: 1,
+                ..inner_area
+            },
+        );
+        inner_area.y += 1;
+        inner_area.height -= 1;
+    }
+
+    // Remember where the list is drawn so mouse events can be mapped to items
+    app.list_area = inner_area;
+
     // Get the items to display based on filter
     let display_indices: Vec<usize> = app.filtered_indices.clone();
 
@@ -211,14 +304,29 @@ fn draw_main_list(frame: &mut Frame, app: &mut App, area: Rect) {
                 _ => item_idx == app.selected_index,
             };
 
+            let mut spans = vec![Span::raw(if should_highlight { " > " } else { "   " })];
+            match app.streams.get(item_idx) {
+                Some(stream) if !columns.is_empty() => spans.extend(stream_row_spans(
+                    Some(stream),
+                    item,
+                    &columns,
+                    num_width,
+                    name_width,
+                )),
+                _ if item.starts_with(DEAD_FAVOURITE_MARKER) => {
+                    spans.push(Span::styled(item, Style::default().fg(Color::Red)))
+                }
+                _ => spans.push(Span::raw(item)),
+            }
+
             let content = if should_highlight {
-                Line::from(vec![Span::raw(" > "), Span::raw(item)]).style(
+                Line::from(spans).style(
                     Style::default()
                         .fg(Color::Yellow)
                         .add_modifier(Modifier::BOLD),
                 )
             } else {
-                Line::from(vec![Span::raw("   "), Span::raw(item)])
+                Line::from(spans)
             };
             ListItem::new(content)
         })
@@ -240,6 +348,178 @@ fn draw_main_list(frame: &mut Frame, app: &mut App, area: Rect) {
     }
 }
 
+/// Configured columns for the current stream list, skipping ones no stream has data for
+fn visible_stream_columns(app: &App) -> Vec<StreamColumn> {
+    let streams = &app.streams;
+    app.config
+        .settings
+        .stream_columns
+        .iter()
+        .copied()
+        .filter(|column| match column {
+            StreamColumn::Favourite => true,
+            StreamColumn::Number => streams.iter().any(|s| s.num > 0),
+            StreamColumn::Added => streams.iter().any(|s| s.added_at().is_some()),
+            StreamColumn::Rating => streams.iter().any(|s| s.rating_value().is_some()),
+            StreamColumn::Archive => streams.iter().any(|s| s.has_archive()),
+        })
+        .collect()
+}
+
+/// Width of all columns other than the name
+fn stream_columns_width(columns: &[StreamColumn], num_width: usize) -> usize {
+    columns
+        .iter()
+        .map(|column| match column {
+            StreamColumn::Favourite => 2,
+            StreamColumn::Number => num_width + 1,
+            StreamColumn::Added => 11,
+            StreamColumn::Rating => 7,
+            StreamColumn::Archive => 8,
+        })
+        .sum()
+}
+
+/// Build the cells of a stream list row, or the header row when no stream is given
+fn stream_row_spans(
+    stream: Option<&Stream>,
+    item: &str,
+    columns: &[StreamColumn],
+    num_width: usize,
+    name_width: usize,
+) -> Vec<Span<'static>> {
+    let has = |column| columns.contains(&column);
+
+    // Favourites are marked in the item text; move the marker into its own column
+    let (is_favourite, name) = match item
+        .strip_prefix("⭐ ")
+        .or_else(|| item.strip_prefix("[FAV] "))
+    {
+        Some(name) if has(StreamColumn::Favourite) => (true, name),
+        _ => (false, item),
+    };
+
+    let mut spans = Vec::new();
+
+    if has(StreamColumn::Favourite) {
+        spans.push(if is_favourite {
+            Span::styled("★ ", Style::default().fg(Color::Yellow))
+        } else {
+            Span::raw("  ")
+        });
+    }
+
+    if has(StreamColumn::Number) {
+        let num = match stream {
+            Some(stream) if stream.num > 0 => stream.num.to_string(),
+            Some(_) => String::new(),
+            None => "#".to_string(),
+        };
+        spans.push(Span::styled(
+            format!("{:>width$} ", num, width = num_width),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    let name: String = if name.chars().count() > name_width {
+        let mut truncated: String = name.chars().take(name_width.saturating_sub(3)).collect();
+        truncated.push_str("...");
+        truncated
+    } else {
+        name.to_string()
+    };
+    spans.push(Span::raw(format!("{:<width$}", name, width = name_width)));
+
+    if has(StreamColumn::Added) {
+        let added = match stream {
+            Some(stream) => stream
+                .added_at()
+                .map(|added| {
+                    added
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d")
+                        .to_string()
+                })
+                .unwrap_or_default(),
+            None => "Added".to_string(),
+        };
+        spans.push(Span::raw(format!(" {:<10}", added)));
+    }
+
+    if has(StreamColumn::Rating) {
+        let rating = match stream {
+            Some(stream) => stream
+                .rating_value()
+                .map(|rating| format!("{:.1}", rating))
+                .unwrap_or_default(),
+            None => "Rating".to_string(),
+        };
+        spans.push(Span::raw(format!(" {:>6}", rating)));
+    }
+
+    if has(StreamColumn::Archive) {
+        let archive = match stream {
+            Some(stream) => match stream.archive_days() {
+                Some(days) => format!("{}d", days),
+                None if stream.has_archive() => "yes".to_string(),
+                None => String::new(),
+            },
+            None => "Archive".to_string(),
+        };
+        spans.push(Span::raw(format!(" {:>7}", archive)));
+    }
+
+    spans
+}
+
+fn draw_preview_pane(frame: &mut Frame, details: &PreviewDetails, area: Rect) {
+    let label_style = Style::default().fg(Color::Yellow);
+
+    let mut lines = vec![
+        Line::from(details.title.clone()).style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Line::from(""),
+    ];
+
+    for (label, value) in &details.fields {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}: ", label), label_style),
+            Span::raw(value.clone()),
+        ]));
+    }
+
+    if let Some(plot) = &details.plot {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("Plot:", label_style)));
+        lines.push(Line::from(plot.clone()));
+    }
+
+    if let Some(cast) = &details.cast {
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled("Cast:", label_style)));
+        lines.push(Line::from(cast.clone()));
+    }
+
+    if details.loading {
+        lines.push(Line::from(""));
+        lines.push(Line::from("Loading details...").style(Style::default().fg(Color::DarkGray)));
+    }
+
+    let preview = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::White))
+                .title(" Details "),
+        )
+        .wrap(Wrap { trim: true });
+
+    frame.render_widget(preview, area);
+}
+
 fn draw_full_window_logs(frame: &mut Frame, app: &mut App, area: Rect) {
     let block = Block::default()
         .borders(Borders::ALL)
@@ -408,7 +688,17 @@ fn draw_playback_status(frame: &mut Frame, app: &App, area: Rect) {
 }
 
 fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
-    let footer_text = if let Some(msg) = &app.status_message {
+    let footer_text = if let Some(input) = &app.text_input {
+        let value = if input.purpose.is_secret() {
+            "*".repeat(input.value.chars().count())
+        } else {
+            input.value.clone()
+        };
+        format!(
+            " {}: {}_ (Enter: Confirm | Esc: Cancel) ",
+            input.prompt, value
+        )
+    } else if let Some(msg) = &app.status_message {
         msg.clone()
     } else {
         // Special footer for full log view
@@ -416,6 +706,15 @@ fn draw_footer(frame: &mut Frame, app: &App, area: Rect) {
             " ↑↓/jk: Navigate | PgUp/PgDn: Page | Home/End: Jump | Esc/Ctrl+l: Return ".to_string()
         } else {
             match &app.state {
+                AppState::FavouriteGroups => {
+                    " Enter: Open | n: New | r: Rename | d: Delete | Ctrl+↑↓: Reorder | Esc/b: Back ".to_string()
+                }
+                AppState::Configuration => {
+                    " Enter: Select/Edit | t: Test provider | Esc/b: Back | Ctrl+l: Logs | ?: Help ".to_string()
+                }
+                AppState::IgnoredItems => {
+                    " Enter/u: Show again | /: Filter | Esc/b: Back ".to_string()
+                }
                 AppState::VodInfo(_) => {
                     " ↑↓: Menu | PgUp/PgDn/Space/Shift+Space: Scroll | Enter: Select | Esc/b: Back | Ctrl+l: Logs | ?: Help ".to_string()
                 }
@@ -469,8 +768,9 @@ fn draw_scrollbar(frame: &mut Frame, area: Rect, offset: usize, total: usize, vi
     frame.render_widget(scrollbar, scrollbar_area);
 }
 
-fn draw_help_overlay(frame: &mut Frame, app: &App, area: Rect) {
+fn draw_help_overlay(frame: &mut Frame, app: &mut App, area: Rect) {
     let help_area = centered_rect(60, 80, area);
+    app.help_area = help_area;
     frame.render_widget(Clear, help_area);
 
     // Calculate visible height for the help text (accounting for borders)
@@ -52,6 +52,7 @@ pub fn get_help_lines() -> Vec<Line<'static>> {
         Line::from("  End       - Jump to last"),
         Line::from("  Enter     - Select item / Play stream"),
         Line::from("  a         - Advanced play menu (for live streams)"),
+        Line::from("  o         - Cycle stream sort (provider/name/newest/rating)"),
         Line::from("  Esc/b     - Go back"),
         Line::from("  q         - Quit application"),
         Line::from(""),
@@ -62,12 +63,23 @@ pub fn get_help_lines() -> Vec<Line<'static>> {
                 .add_modifier(Modifier::BOLD),
         )]),
         Line::from("  /         - Fuzzy search/filter list"),
-        Line::from("  f         - Toggle favourite (in stream/favourite lists)"),
-        Line::from("  i         - Toggle ignore (category/channel)"),
+        Line::from("  f         - Toggle favourite (in stream/episode/favourite lists)"),
+        Line::from("  i         - Toggle ignore (category/channel, this provider only)"),
         Line::from("  s         - Stop any active playback"),
+        Line::from("  p         - Toggle details preview pane"),
         Line::from("  ?/F1      - Toggle this help"),
         Line::from("  Ctrl+C    - Force quit"),
         Line::from(""),
+        Line::from(vec![Span::styled(
+            "While Playing Live TV:",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from("  +/-       - Next/previous channel in category"),
+        Line::from("  Backspace - Return to last channel"),
+        Line::from("  0-9       - Jump to channel number (Enter to confirm)"),
+        Line::from(""),
         Line::from(vec![Span::styled(
             "Favourites Management:",
             Style::default()
@@ -77,6 +89,48 @@ pub fn get_help_lines() -> Vec<Line<'static>> {
         Line::from("  Ctrl+↑    - Move favourite up in list"),
         Line::from("  Ctrl+↓    - Move favourite down in list"),
         Line::from("  f         - Remove from favourites"),
+        Line::from("  m         - Move favourite to another group"),
+        Line::from("  Enter     - Play, or open a series' seasons"),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Favourite Groups:",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from("  n         - Create a new group"),
+        Line::from("  r         - Rename the selected group"),
+        Line::from("  d         - Delete group (favourites move to first group)"),
+        Line::from("  Ctrl+↑/↓  - Reorder groups"),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Hidden Items (Main Menu):",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from("  Enter/u   - Show the selected category or channel again"),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Configuration:",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from("  Enter     - Change setting / Edit provider / Add provider"),
+        Line::from("  t         - Test the selected provider and show its account"),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Mouse:",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from("  Click        - Select item"),
+        Line::from("  Double-click - Open item / Play stream"),
+        Line::from("  Wheel        - Scroll list, help and VOD info"),
+        Line::from("  Scrollbar    - Click or drag to jump"),
+        Line::from("  Drag         - Reorder favourites"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "VOD Info Mode:",
@@ -5,6 +5,7 @@ use crate::cache::{CacheManager, CacheMetadata};
 use crate::favourites::FavouritesManager;
 use anyhow::{Context, Result};
 use indicatif::{ProgressBar, ProgressStyle};
+use regex::Regex;
 use reqwest::Client;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
@@ -93,16 +94,17 @@ pub struct UserInfo {
     pub message: String,
     pub auth: u8,
     pub status: String,
-    #[serde(deserialize_with = "deserialize_number_as_string")]
-    pub exp_date: String,
-    #[serde(deserialize_with = "deserialize_number_as_string")]
-    pub is_trial: String,
-    #[serde(deserialize_with = "deserialize_number_as_string")]
-    pub active_cons: String,
-    #[serde(deserialize_with = "deserialize_number_as_string")]
-    pub created_at: String,
-    #[serde(deserialize_with = "deserialize_number_as_string")]
-    pub max_connections: String,
+    /// Unix timestamp of the expiry; null for subscriptions that don't expire
+    #[serde(default, deserialize_with = "deserialize_optional_number_as_string")]
+    pub exp_date: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_number_as_string")]
+    pub is_trial: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_number_as_string")]
+    pub active_cons: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_number_as_string")]
+    pub created_at: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_number_as_string")]
+    pub max_connections: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -169,6 +171,55 @@ pub struct Stream {
     pub container_extension: Option<String>,
 }
 
+/// Convert a loosely typed provider value (string, number or bool) to a string
+fn value_as_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(if *b { "1" } else { "0" }.to_string()),
+        _ => None,
+    }
+}
+
+impl Stream {
+    /// Time the stream was added to the provider, parsed from the `added` timestamp
+    pub fn added_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let timestamp = self.added.as_ref()?.trim().parse::<i64>().ok()?;
+        chrono::DateTime::from_timestamp(timestamp, 0)
+    }
+
+    /// Rating as a number, preferring the 10-based `rating` over `rating_5based`
+    pub fn rating_value(&self) -> Option<f64> {
+        let parse = |value: &Option<Value>| {
+            value
+                .as_ref()
+                .and_then(value_as_string)
+                .and_then(|s| s.parse::<f64>().ok())
+                .filter(|r| *r > 0.0)
+        };
+        parse(&self.rating).or_else(|| parse(&self.rating_5based).map(|r| r * 2.0))
+    }
+
+    /// Whether the provider keeps a catch-up archive for this channel
+    pub fn has_archive(&self) -> bool {
+        self.tv_archive
+            .as_ref()
+            .and_then(value_as_string)
+            .is_some_and(|s| s != "0")
+    }
+
+    /// Number of days of catch-up archive, if any
+    pub fn archive_days(&self) -> Option<u32> {
+        if !self.has_archive() {
+            return None;
+        }
+        self.tv_archive_duration
+            .as_ref()
+            .and_then(value_as_string)
+            .and_then(|s| s.parse().ok())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FavouriteStream {
     pub stream_id: u32,
@@ -177,6 +228,66 @@ pub struct FavouriteStream {
     pub provider_hash: String,
     pub added_date: chrono::DateTime<chrono::Utc>,
     pub category_id: Option<String>,
+    /// Container extension of an episode favourite, needed to build its URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_extension: Option<String>,
+    /// Series an episode favourite belongs to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series_id: Option<u32>,
+    /// Episode IDs of a series favourite that have been played, for "next unwatched"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub watched_episodes: Vec<String>,
+}
+
+impl FavouriteStream {
+    /// A favourite for a stream or series as listed by the provider
+    pub fn new(
+        provider_hash: &str,
+        stream_id: u32,
+        name: String,
+        stream_type: &str,
+        category_id: Option<String>,
+    ) -> Self {
+        Self {
+            stream_id,
+            name,
+            stream_type: stream_type.to_string(),
+            provider_hash: provider_hash.to_string(),
+            added_date: chrono::Utc::now(),
+            category_id,
+            container_extension: None,
+            series_id: None,
+            watched_episodes: Vec::new(),
+        }
+    }
+
+    /// A favourite for a single episode (stream type "episode"), keyed by the episode ID
+    ///
+    /// Returns None if the provider's episode ID isn't numeric.
+    pub fn from_episode(
+        provider_hash: &str,
+        series_id: u32,
+        series_name: &str,
+        category_id: Option<String>,
+        episode: &ApiEpisode,
+    ) -> Option<Self> {
+        let name = if episode.title.contains(series_name) {
+            episode.title.clone()
+        } else {
+            format!("{} - {}", series_name, episode.title)
+        };
+        Some(Self {
+            stream_id: episode.id.parse().ok()?,
+            name,
+            stream_type: "episode".to_string(),
+            provider_hash: provider_hash.to_string(),
+            added_date: chrono::Utc::now(),
+            category_id,
+            container_extension: episode.container_extension.clone(),
+            series_id: Some(series_id),
+            watched_episodes: Vec::new(),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -290,6 +401,19 @@ pub struct SeriesInfo {
     pub tmdb: Option<String>,
 }
 
+impl SeriesInfo {
+    /// Time the series was added to the provider, parsed from the `added` timestamp
+    pub fn added_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let timestamp = self.added.as_ref()?.trim().parse::<i64>().ok()?;
+        chrono::DateTime::from_timestamp(timestamp, 0)
+    }
+
+    /// Rating as a number, preferring the 10-based `rating` over `rating_5based`
+    pub fn rating_value(&self) -> Option<f64> {
+        series_rating(self.rating.as_deref(), self.rating_5based.as_ref())
+    }
+}
+
 // Series info object that comes inside the series detail response (without series_id)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SeriesDetailInfo {
@@ -322,6 +446,28 @@ pub struct SeriesDetailInfo {
     pub category_id: Option<String>,
 }
 
+impl SeriesDetailInfo {
+    /// Rating as a number, preferring the 10-based `rating` over `rating_5based`
+    pub fn rating_value(&self) -> Option<f64> {
+        series_rating(self.rating.as_deref(), self.rating_5based.as_ref())
+    }
+}
+
+/// A positive rating out of 10 from a series' `rating` or, failing that, `rating_5based`
+fn series_rating(rating: Option<&str>, rating_5based: Option<&Value>) -> Option<f64> {
+    let positive = |r: f64| Some(r).filter(|r| *r > 0.0);
+    rating
+        .and_then(|r| r.trim().parse::<f64>().ok())
+        .and_then(positive)
+        .or_else(|| {
+            rating_5based
+                .and_then(value_as_string)
+                .and_then(|r| r.parse::<f64>().ok())
+                .and_then(positive)
+                .map(|r| r * 2.0)
+        })
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Episode {
     pub id: String,
@@ -411,6 +557,40 @@ pub struct ApiEpisode {
     pub direct_source: Option<String>,
 }
 
+impl ApiEpisode {
+    /// Short label such as "S02E05 Title"
+    pub fn label(&self) -> String {
+        format!("S{:02}E{:02} {}", self.season, self.episode_num, self.title)
+    }
+}
+
+impl SeriesInfoResponse {
+    /// Every episode in season and episode order
+    pub fn sorted_episodes(&self) -> Vec<&ApiEpisode> {
+        let mut episodes: Vec<&ApiEpisode> = self
+            .episodes
+            .iter()
+            .flat_map(|by_season| by_season.values().flatten())
+            .collect();
+        episodes.sort_by_key(|e| (e.season, e.episode_num));
+        episodes
+    }
+
+    /// The episode after the last watched one, or the first unwatched one
+    pub fn next_unwatched(&self, watched: &[String]) -> Option<&ApiEpisode> {
+        let episodes = self.sorted_episodes();
+        let after_last_watched = episodes
+            .iter()
+            .rposition(|e| watched.contains(&e.id))
+            .map_or(0, |index| index + 1);
+        episodes[after_last_watched..]
+            .iter()
+            .chain(&episodes[..after_last_watched])
+            .find(|e| !watched.contains(&e.id))
+            .copied()
+    }
+}
+
 // Keep the old structures for compatibility
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Season {
@@ -447,6 +627,27 @@ impl std::fmt::Debug for XTreamAPI {
     }
 }
 
+/// Identifier a provider's cache and favourites are keyed by: its ID if it has one, otherwise
+/// its name, otherwise the server's host and port
+pub fn provider_identifier(
+    url: &reqwest::Url,
+    provider_name: Option<&str>,
+    provider_id: Option<&str>,
+) -> String {
+    if let Some(id) = provider_id {
+        id.to_string()
+    } else if let Some(name) = provider_name {
+        name.to_string()
+    } else {
+        // Fallback to hostname:port for backward compatibility
+        let host = url.host_str().unwrap_or("localhost");
+        match url.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        }
+    }
+}
+
 impl XTreamAPI {
     pub fn new(
         server_url: String,
@@ -481,19 +682,8 @@ impl XTreamAPI {
             )
         };
 
-        // Use provider_id if provided, otherwise use provider_name, otherwise generate from URL
-        let provider_identifier = if let Some(id) = provider_id {
-            id
-        } else if let Some(name) = provider_name.as_ref() {
-            name.clone()
-        } else {
-            // Fallback to hostname:port for backward compatibility
-            if let Some(port) = url.port() {
-                format!("{}:{}", url.host_str().unwrap_or("localhost"), port)
-            } else {
-                url.host_str().unwrap_or("localhost").to_string()
-            }
-        };
+        let provider_identifier =
+            provider_identifier(&url, provider_name.as_deref(), provider_id.as_deref());
 
         let mut cache_manager = CacheManager::new()?;
         let provider_hash = cache_manager.get_provider_hash(&provider_identifier, None)?;
@@ -530,24 +720,27 @@ impl XTreamAPI {
         self.logger = None; // Clear any logger to allow progress bars
     }
 
-    /// Make a raw API request and return the JSON response without deserialization
+    /// Make a request for any API action with extra query parameters, returning the body
+    /// as sent
+    ///
+    /// Unlike the typed requests the response doesn't have to be JSON, so provider quirks
+    /// can be looked at as they are.
     pub async fn make_request_raw(
         &self,
         action: &str,
-        category_id: Option<&str>,
-    ) -> Result<serde_json::Value> {
-        let mut url = format!(
-            "{}/player_api.php?username={}&password={}&action={}",
-            self.base_url, self.username, self.password, action
-        );
-
-        if let Some(cat_id) = category_id {
-            url.push_str(&format!("&category_id={}", cat_id));
-        }
+        params: &[(String, String)],
+    ) -> Result<String> {
+        let mut url = reqwest::Url::parse(&format!("{}/player_api.php", self.base_url))
+            .with_context(|| format!("Invalid provider URL: {}", self.base_url))?;
+        url.query_pairs_mut()
+            .append_pair("username", &self.username)
+            .append_pair("password", &self.password)
+            .append_pair("action", action)
+            .extend_pairs(params);
 
         let response = self
             .client
-            .get(&url)
+            .get(url.clone())
             .send()
             .await
             .with_context(|| format!("Failed to send request to {}", url))?;
@@ -565,58 +758,18 @@ impl XTreamAPI {
             return Err(anyhow::anyhow!("Empty response from server"));
         }
 
-        // Parse as raw JSON value
-        let json: serde_json::Value = serde_json::from_str(&response_text)
-            .with_context(|| "Failed to parse response as JSON")?;
-
-        Ok(json)
+        Ok(response_text)
     }
 
-    /// Make a raw API request for series/vod info and return the JSON response
-    pub async fn make_info_request_raw(
-        &self,
-        endpoint: &str,
-        id: u32,
-    ) -> Result<serde_json::Value> {
-        let url = format!(
-            "{}/player_api.php?username={}&password={}&action={}&{}_id={}",
-            self.base_url,
-            self.username,
-            self.password,
-            endpoint,
-            if endpoint == "get_series_info" {
-                "series"
-            } else {
-                "vod"
-            },
-            id
-        );
-
-        let response = self
-            .client
-            .get(&url)
-            .send()
-            .await
-            .with_context(|| format!("Failed to send request to {}", url))?;
-
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!(
-                "HTTP request failed with status: {}",
-                response.status()
-            ));
-        }
-
-        let response_text = response.text().await?;
-
-        if response_text.trim().is_empty() {
-            return Err(anyhow::anyhow!("Empty response from server"));
-        }
-
-        // Parse as raw JSON value
-        let json: serde_json::Value = serde_json::from_str(&response_text)
-            .with_context(|| "Failed to parse response as JSON")?;
+    /// Replace this provider's username and password in a response, i.e. the user info's
+    /// fields and the credentials in stream URLs, so it can be shared
+    pub fn redact_credentials(&self, value: &mut Value) {
+        redact_credentials(value, &self.username, &self.password);
+    }
 
-        Ok(json)
+    /// Replace the credentials in any URLs in some text, e.g. an error or a non-JSON response
+    pub fn redact_text(&self, text: &str) -> String {
+        redact_text(text, &self.username, &self.password)
     }
 
     async fn make_request<T>(&self, action: &str, category_id: Option<&str>) -> Result<T>
@@ -820,9 +973,19 @@ impl XTreamAPI {
         {
             return Ok(cached);
         }
+        self.fetch_user_info().await
+    }
 
+    /// Get user info from the provider rather than the cache, which it then updates; the
+    /// subscription status and connection count change over time
+    pub async fn fetch_user_info(&mut self) -> Result<UserInfo> {
+        Ok(self.fetch_account_info().await?.user_info)
+    }
+
+    /// Fetch the account and server info, bypassing the cache; the user info is cached
+    pub async fn fetch_account_info(&mut self) -> Result<UserInfoResponse> {
         let response: UserInfoResponse = self.make_request("get_user_info", None).await?;
-        let user_info = response.user_info;
+        let user_info = response.user_info.clone();
 
         let metadata = CacheMetadata::new(self.base_url.clone(), self.provider_name.clone());
 
@@ -840,7 +1003,7 @@ impl XTreamAPI {
             eprintln!("Warning: Failed to cache user info: {}", e);
         }
 
-        Ok(user_info)
+        Ok(response)
     }
 
     pub async fn get_live_categories(&mut self) -> Result<Vec<Category>> {
@@ -1314,6 +1477,23 @@ impl XTreamAPI {
         }
     }
 
+    /// Playback URL of a favourite; episodes are served from the series path with their own
+    /// container extension
+    pub fn get_favourite_url(
+        &self,
+        favourite: &FavouriteStream,
+        extension: Option<&str>,
+    ) -> String {
+        match favourite.stream_type.as_str() {
+            "episode" => self.get_stream_url(
+                favourite.stream_id,
+                "series",
+                favourite.container_extension.as_deref().or(extension),
+            ),
+            stream_type => self.get_stream_url(favourite.stream_id, stream_type, extension),
+        }
+    }
+
     pub async fn clear_cache(&mut self) -> Result<()> {
         self.cache_manager
             .clear_provider_cache(&self.provider_hash)
@@ -1452,3 +1632,104 @@ impl XTreamAPI {
         Ok(())
     }
 }
+
+/// Redact the user info's username and password fields and the credentials in any URLs,
+/// leaving other values alone even when they happen to contain the same digits
+fn redact_credentials(value: &mut Value, username: &str, password: &str) {
+    match value {
+        Value::String(s) => *s = redact_text(s, username, password),
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| redact_credentials(item, username, password)),
+        Value::Object(map) => {
+            if let Some(Value::Object(user_info)) = map.get_mut("user_info") {
+                for field in ["username", "password"] {
+                    if let Some(value) = user_info.get_mut(field) {
+                        *value = Value::String("REDACTED".to_string());
+                    }
+                }
+            }
+            map.values_mut()
+                .for_each(|item| redact_credentials(item, username, password));
+        }
+        _ => {}
+    }
+}
+
+/// Redact `username=`/`password=` query values and the `/live|movie|series/<user>/<pass>/`
+/// path segments of stream URLs
+fn redact_text(text: &str, username: &str, password: &str) -> String {
+    let query = Regex::new(r#"([?&](?:username|password)=)[^&#\s"'<>]*"#).expect("valid regex");
+    let text = query.replace_all(text, "${1}REDACTED");
+    if username.is_empty() || password.is_empty() {
+        return text.into_owned();
+    }
+    let path = Regex::new(&format!(
+        r"(/(?:live|movie|series)/){}/{}/",
+        regex::escape(username),
+        regex::escape(password)
+    ))
+    .expect("valid regex");
+    path.replace_all(&text, "${1}REDACTED/REDACTED/")
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn redacts_only_credentials_when_they_are_short_and_numeric() {
+        let mut response = json!({
+            "user_info": {"username": "123", "password": "789", "exp_date": "1716789012"},
+            "server_info": {"timestamp_now": 1716123789},
+            "id": "12345",
+            "stream_id": 789,
+            "start_timestamp": "1716789012",
+            "direct_source": "http://example.com:8080/live/123/789/12345.ts",
+        });
+        redact_credentials(&mut response, "123", "789");
+        assert_eq!(
+            response,
+            json!({
+                "user_info": {"username": "REDACTED", "password": "REDACTED", "exp_date": "1716789012"},
+                "server_info": {"timestamp_now": 1716123789},
+                "id": "12345",
+                "stream_id": 789,
+                "start_timestamp": "1716789012",
+                "direct_source": "http://example.com:8080/live/REDACTED/REDACTED/12345.ts",
+            })
+        );
+    }
+
+    #[test]
+    fn redacts_query_credentials() {
+        assert_eq!(
+            redact_text(
+                "error sending request for url (http://example.com/player_api.php?username=123&password=789&action=get_vod_info&vod_id=1234789)",
+                "123",
+                "789"
+            ),
+            "error sending request for url (http://example.com/player_api.php?username=REDACTED&password=REDACTED&action=get_vod_info&vod_id=1234789)"
+        );
+    }
+
+    #[test]
+    fn redacts_stream_path_credentials() {
+        for kind in ["live", "movie", "series"] {
+            assert_eq!(
+                redact_text(
+                    &format!("http://example.com/{}/123/789/1237890.mkv", kind),
+                    "123",
+                    "789"
+                ),
+                format!("http://example.com/{}/REDACTED/REDACTED/1237890.mkv", kind)
+            );
+        }
+        assert_eq!(
+            redact_text("/epg/123/789/ and 123789", "123", "789"),
+            "/epg/123/789/ and 123789"
+        );
+    }
+}